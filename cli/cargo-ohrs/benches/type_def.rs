@@ -0,0 +1,95 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use ohrs::bench::{process_type_def, DtsOptions};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tempfile::TempPath;
+
+// count the allocated bytes to get the peak memory of one generation
+struct CountingAllocator;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+  unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+    let ptr = System.alloc(layout);
+    if !ptr.is_null() {
+      let current = CURRENT.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+      PEAK.fetch_max(current, Ordering::Relaxed);
+    }
+    ptr
+  }
+
+  unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+    System.dealloc(ptr, layout);
+    CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+  }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const NAMESPACES: usize = 8;
+// struct, impl, fn, interface and enum for every item
+const ITEMS: usize = 400;
+
+/// about 2k declarations across namespaces, with the top level ones, it's removed when dropped
+fn fixture() -> TempPath {
+  let mut lines = Vec::new();
+  for i in 0..ITEMS {
+    let js_mod = match i % (NAMESPACES + 1) {
+      0 => String::new(),
+      n => format!(r#", "js_mod": "ns{}""#, n),
+    };
+    lines.push(format!(
+      r#"{{"kind": "struct", "name": "Class{i}", "original_name": "Class{i}", "js_doc": "/** class {i} */\n", "def": "readonly id: number\nname?: string\nconstructor(id: number, name?: string)"{js_mod}}}"#
+    ));
+    lines.push(format!(
+      r#"{{"kind": "impl", "name": "Class{i}", "js_doc": "", "def": "/** get the label */\nlabel(prefix: string): string\nstatic create(id: number): Class{i}\nget size(): number"{js_mod}}}"#
+    ));
+    lines.push(format!(
+      r#"{{"kind": "fn", "name": "call{i}", "js_doc": "/** call {i} */\n", "def": "function call{i}(input: ArrayBuffer, options?: Options{i} | undefined | null): Promise<Class{i}>"{js_mod}}}"#
+    ));
+    lines.push(format!(
+      r#"{{"kind": "interface", "name": "Options{i}", "js_doc": "", "def": "timeout?: number\ncallback: (err: Error | null, value: Either<string, number>) => void"{js_mod}}}"#
+    ));
+    lines.push(format!(
+      r#"{{"kind": "enum", "name": "Kind{i}", "js_doc": "", "def": "A = 0,\nB = 1,\nC = 2"{js_mod}}}"#
+    ));
+  }
+  let file = tempfile::Builder::new()
+    .prefix("ohrs-bench")
+    .suffix(".napi_type_def.tmp")
+    .tempfile()
+    .unwrap()
+    .into_temp_path();
+  fs::write(&file, lines.join("\n")).unwrap();
+  file
+}
+
+fn bench_type_def(c: &mut Criterion) {
+  let fixture = fixture();
+  let file = fixture.to_str().unwrap();
+  let options = DtsOptions::default();
+
+  let base = CURRENT.load(Ordering::SeqCst);
+  PEAK.store(base, Ordering::SeqCst);
+  let (dts, exports) = process_type_def(file, true, "", &options).unwrap();
+  println!(
+    "type_def: {} bytes for {} exports, peak allocation {} KiB",
+    dts.len(),
+    exports.len(),
+    (PEAK.load(Ordering::SeqCst) - base) / 1024
+  );
+
+  let mut group = c.benchmark_group("type_def");
+  group.sample_size(20);
+  group.bench_function("process_type_def", |b| {
+    b.iter(|| process_type_def(file, true, "", &options).unwrap())
+  });
+  group.finish();
+}
+
+criterion_group!(benches, bench_type_def);
+criterion_main!(benches);
@@ -0,0 +1,141 @@
+use crate::util::Arch;
+use cargo_metadata::{MetadataCommand, Package};
+use regex::Regex;
+use std::fs;
+use std::path::Path;
+
+/// read the first string field of oh-package.json5, it should be the field of package
+fn package_field(content: &str, field: &str) -> Option<String> {
+  let reg = Regex::new(&format!(r#""?{}"?\s*:\s*"([^"]*)""#, regex::escape(field))).ok()?;
+  reg.captures(content).map(|caps| caps[1].to_string())
+}
+
+fn is_non_empty_file(p: &Path) -> bool {
+  fs::metadata(p)
+    .map(|m| m.is_file() && m.len() > 0)
+    .unwrap_or(false)
+}
+
+/// the crate in current folder
+pub fn current_package(pwd: &Path) -> Option<Package> {
+  let manifest = pwd.join("Cargo.toml");
+  if !manifest.is_file() {
+    return None;
+  }
+  let metadata = MetadataCommand::new()
+    .no_deps()
+    .manifest_path(&manifest)
+    .exec()
+    .ok()?;
+  metadata
+    .packages
+    .iter()
+    .find(|p| p.manifest_path.as_std_path() == manifest)
+    .cloned()
+}
+
+/// version of the crate in current folder
+fn crate_version(pwd: &Path) -> Option<String> {
+  current_package(pwd).map(|p| p.version.to_string())
+}
+
+/// all the problems which will make the .har file broken
+pub fn check(pwd: &Path, dist: &str, arch: &[Arch]) -> Vec<String> {
+  let mut problems = Vec::new();
+  let package_source = pwd.join("package");
+  let dist_source = pwd.join(dist);
+
+  if !package_source.is_dir() {
+    problems.push(format!("{:?} is not a folder.", &package_source));
+  }
+  if !dist_source.is_dir() {
+    problems.push(format!("{:?} is not a folder.", &dist_source));
+  }
+
+  for a in arch {
+    let abi = dist_source.join(a.to_arch());
+    let has_library = fs::read_dir(&abi)
+      .map(|d| {
+        d.filter_map(|e| e.ok())
+          .any(|e| e.path().extension().is_some_and(|ext| ext == "so"))
+      })
+      .unwrap_or(false);
+    if !has_library {
+      problems.push(format!(
+        "Can't find any shared library for {} in {:?}.",
+        a.to_arch(),
+        &abi
+      ));
+    }
+  }
+
+  let package_file = package_source.join("oh-package.json5");
+  let content = match fs::read_to_string(&package_file) {
+    Ok(c) => c,
+    Err(_) => {
+      problems.push(format!("{:?} is not existed.", &package_file));
+      return problems;
+    }
+  };
+
+  match (package_field(&content, "version"), crate_version(pwd)) {
+    (None, _) => problems.push(String::from(
+      "Can't find the version field in package/oh-package.json5.",
+    )),
+    (Some(version), Some(crate_version)) if version != crate_version => problems.push(format!(
+      "The version of package/oh-package.json5 is {}, but the version of crate is {}.",
+      version, crate_version
+    )),
+    _ => {}
+  }
+
+  // libs folder will be replaced with dist folder when packaging
+  let resolve = |p: &str| match Path::new(p).strip_prefix("libs") {
+    Ok(rest) => dist_source.join(rest),
+    Err(_) => package_source.join(p),
+  };
+  let types = package_field(&content, "types").unwrap_or(String::from("libs/index.d.ts"));
+  if !is_non_empty_file(&resolve(&types)) {
+    problems.push(format!(
+      "The types file {} is not existed or empty, please run build before artifact.",
+      types
+    ));
+  }
+  if let Some(main) = package_field(&content, "main") {
+    if !resolve(&main).is_file() {
+      problems.push(format!("The main file {} is not existed.", main));
+    }
+  }
+
+  problems
+}
+
+#[cfg(test)]
+mod test {
+  use super::check;
+  use crate::util::{test_dir, Arch};
+  use std::fs;
+
+  #[test]
+  fn test_artifact_check() {
+    let temp = test_dir();
+    let pwd = temp.path().to_path_buf();
+    fs::create_dir_all(pwd.join("package")).unwrap();
+    fs::create_dir_all(pwd.join("dist/arm64-v8a")).unwrap();
+    fs::write(
+      pwd.join("package/oh-package.json5"),
+      r#"{ name: "pkg", main: "index.ets", version: "0.1.0", types: "libs/index.d.ts" }"#,
+    )
+    .unwrap();
+    fs::write(pwd.join("package/index.ets"), "").unwrap();
+    fs::write(pwd.join("dist/arm64-v8a/libpkg.so"), "").unwrap();
+
+    let problems = check(&pwd, "dist", &[Arch::ARM64, Arch::X86_64]);
+    assert_eq!(problems.len(), 2);
+    assert!(problems[0].starts_with("Can't find any shared library for x86_64"));
+    assert!(problems[1].starts_with("The types file libs/index.d.ts"));
+
+    fs::write(pwd.join("dist/index.d.ts"), "export {}").unwrap();
+    assert!(check(&pwd, "dist", &[Arch::ARM64]).is_empty());
+  }
+}
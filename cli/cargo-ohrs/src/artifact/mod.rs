@@ -1,16 +1,105 @@
 use crate::artifact::tgz::generate_har;
 use crate::check_and_clean_file_or_dir;
+use crate::util::Arch;
 use anyhow::Error;
 use fs_extra::dir::CopyOptions;
+use owo_colors::OwoColorize;
+use serde_json::json;
 use std::fs;
+use std::path::Path;
+use std::str::FromStr;
 use std::{env, path::PathBuf};
 
+mod check;
+mod name;
 mod tgz;
 
+// written into libs, `ohrs diff` compares it
+const META_FILE: &str = "ohrs-meta.json";
+
+/// how the architectures are packed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArtifactSplit {
+  // one .har file with all architectures
+  Fat,
+  // one .har file for every architecture
+  PerAbi,
+}
+
+impl FromStr for ArtifactSplit {
+  type Err = String;
+  fn from_str(s: &str) -> Result<Self, String> {
+    match s {
+      "fat" => Ok(ArtifactSplit::Fat),
+      "per-abi" => Ok(ArtifactSplit::PerAbi),
+      _ => Err(format!(
+        "Unsupported artifact split {}, only fat and per-abi are supported.",
+        s
+      )),
+    }
+  }
+}
+
+// the architectures which are built into dist
+fn built_arch(dist: &Path) -> Vec<Arch> {
+  [Arch::ARM64, Arch::ARM32, Arch::X86_64]
+    .into_iter()
+    .filter(|a| dist.join(a.to_arch()).is_dir())
+    .collect()
+}
+
+// copy dist to package/libs with the architectures only
+fn copy_libs(pwd: &Path, dist: &Path, package_source: &Path, arch: &[Arch]) -> anyhow::Result<()> {
+  let libs = package_source.join("libs");
+  // clean the folder before we copy it
+  check_and_clean_file_or_dir!(libs);
+
+  let mut op = CopyOptions::new();
+  op.overwrite = true;
+  op.copy_inside = true;
+  fs_extra::dir::copy(dist, &libs, &op)?;
+  for other in [Arch::ARM64, Arch::ARM32, Arch::X86_64] {
+    if !arch.contains(&other) {
+      check_and_clean_file_or_dir!(libs.join(other.to_arch()));
+    }
+  }
+
+  let mut meta = json!({
+    "tool_version": env!("CARGO_PKG_VERSION"),
+    "abi": arch.iter().map(|a| a.to_arch()).collect::<Vec<_>>(),
+  });
+  if let Some(package) = check::current_package(pwd) {
+    meta["name"] = json!(package.name.to_string());
+    meta["version"] = json!(package.version.to_string());
+  }
+  fs::write(libs.join(META_FILE), serde_json::to_string_pretty(&meta)?)?;
+  Ok(())
+}
+
 pub fn artifact(args: crate::ArtifactArgs) -> anyhow::Result<()> {
-  let pwd = env::current_dir().unwrap();
+  artifact_in(&env::current_dir().unwrap(), args).map(|_| ())
+}
+
+/// generate .har files in the folder, return the paths of them
+fn artifact_in(pwd: &Path, args: crate::ArtifactArgs) -> anyhow::Result<Vec<PathBuf>> {
+  if args.check {
+    let arch = args.arch.unwrap_or(vec![
+      crate::Arch::ARM64,
+      crate::Arch::ARM32,
+      crate::Arch::X86_64,
+    ]);
+    let problems = check::check(pwd, &args.dist, &arch);
+    if !problems.is_empty() {
+      return Err(Error::msg(format!(
+        "Package can't be generated:\n  {}",
+        problems.join("\n  ")
+      )));
+    }
+    println!("{}", "All checks passed, package is ready.".green());
+    return Ok(vec![]);
+  }
 
-  let package_source = (&pwd).join("package");
+  let package_source = pwd.join("package");
   if !package_source.exists() {
     return Err(Error::msg(format!(
       "{:?} is not existed,please create this folder",
@@ -24,7 +113,7 @@ pub fn artifact(args: crate::ArtifactArgs) -> anyhow::Result<()> {
     )));
   }
 
-  let dist_source = (&pwd).join(&args.dist);
+  let dist_source = pwd.join(&args.dist);
 
   if !dist_source.is_dir() {
     return Err(Error::msg(format!(
@@ -46,17 +135,160 @@ pub fn artifact(args: crate::ArtifactArgs) -> anyhow::Result<()> {
     )));
   }
 
-  // clean the folder before we copy it
-  check_and_clean_file_or_dir!((&package_source).join("libs"));
+  let mut arch = built_arch(&dist_source);
+  if let Some(selected) = &args.arch {
+    arch.retain(|a| selected.contains(a));
+  }
+  if arch.is_empty() {
+    return Err(Error::msg(format!(
+      "Can't find any architecture in {:?}, please run build before artifact.",
+      &dist_source
+    )));
+  }
+  let groups = match args.artifact_split {
+    ArtifactSplit::Fat => vec![arch.clone()],
+    ArtifactSplit::PerAbi => arch.iter().map(|a| vec![*a]).collect(),
+  };
 
-  // copy dist
-  let mut op = CopyOptions::new();
-  op.overwrite = true;
-  op.copy_inside = true;
-  fs_extra::dir::copy((&pwd).join(&args.dist), (&package_source).join("libs"), &op).unwrap();
+  // resolve the names before changing anything
+  let template = match (&args.artifact_name, args.artifact_split) {
+    (Some(template), _) => Some(template.clone()),
+    (None, ArtifactSplit::Fat) => None,
+    (None, ArtifactSplit::PerAbi) => Some(format!("{}-{{abi}}", args.name)),
+  };
+  let mut names = Vec::new();
+  for arch in &groups {
+    let name = match &template {
+      Some(template) => name::render_artifact_name(template, &name::ProjectVars { pwd, arch })?,
+      None => args.name.clone(),
+    };
+    if names.contains(&name) {
+      return Err(Error::msg(format!(
+        "The artifact name {} is used by multiple architectures, please add {{abi}} to --artifact-name.",
+        name
+      )));
+    }
+    names.push(name);
+  }
+
+  let mut hars = Vec::new();
+  for (arch, name) in groups.iter().zip(names) {
+    copy_libs(pwd, &dist_source, &package_source, arch)?;
+    let package_path = pwd.join(format!("{}.har", name));
+    generate_har(package_path.clone(), package_source.clone());
+    hars.push(package_path);
+  }
+  // package/libs keeps all the architectures, like the one of fat
+  if groups.len() > 1 {
+    copy_libs(pwd, &dist_source, &package_source, &arch)?;
+  }
+  Ok(hars)
+}
 
-  let package_path = PathBuf::from(&pwd).join(format!("{}.har", args.name));
+#[cfg(test)]
+mod test {
+  use super::{artifact_in, ArtifactSplit};
+  use crate::util::test_dir;
+  use flate2::read::GzDecoder;
+  use std::fs::{self, File};
+  use std::io::Read;
+  use std::path::Path;
 
-  generate_har(package_path, package_source);
-  Ok(())
+  // the files in .har and the abi in ohrs-meta.json
+  fn read_har(har: &Path) -> (Vec<String>, serde_json::Value) {
+    let mut archive = tar::Archive::new(GzDecoder::new(File::open(har).unwrap()));
+    let mut files = Vec::new();
+    let mut meta = serde_json::Value::Null;
+    for entry in archive.entries().unwrap() {
+      let mut entry = entry.unwrap();
+      if !entry.header().entry_type().is_file() {
+        continue;
+      }
+      let name = entry.path().unwrap().to_string_lossy().to_string();
+      if name.ends_with("ohrs-meta.json") {
+        let mut content = String::new();
+        entry.read_to_string(&mut content).unwrap();
+        meta = serde_json::from_str::<serde_json::Value>(&content).unwrap()["abi"].clone();
+      }
+      files.push(name);
+    }
+    files.sort();
+    (files, meta)
+  }
+
+  fn args(split: ArtifactSplit, artifact_name: Option<&str>) -> crate::ArtifactArgs {
+    crate::ArtifactArgs {
+      dist: String::from("dist"),
+      name: String::from("pkg"),
+      artifact_name: artifact_name.map(String::from),
+      artifact_split: split,
+      arch: None,
+      check: false,
+    }
+  }
+
+  #[test]
+  fn test_artifact_split() {
+    let temp = test_dir();
+    let pwd = temp.path().to_path_buf();
+    fs::create_dir_all(pwd.join("package")).unwrap();
+    fs::write(pwd.join("package/oh-package.json5"), "{}").unwrap();
+    for arch in ["arm64-v8a", "x86_64"] {
+      fs::create_dir_all(pwd.join("dist").join(arch)).unwrap();
+      fs::write(pwd.join("dist").join(arch).join("libpkg.so"), arch).unwrap();
+    }
+    fs::write(pwd.join("dist/index.d.ts"), "export {}").unwrap();
+
+    let hars = artifact_in(&pwd, args(ArtifactSplit::Fat, None)).unwrap();
+    assert_eq!(hars, vec![pwd.join("pkg.har")]);
+    let (files, abi) = read_har(&hars[0]);
+    assert_eq!(
+      files,
+      vec![
+        "package/libs/arm64-v8a/libpkg.so",
+        "package/libs/index.d.ts",
+        "package/libs/ohrs-meta.json",
+        "package/libs/x86_64/libpkg.so",
+        "package/oh-package.json5",
+      ]
+    );
+    assert_eq!(abi, serde_json::json!(["arm64-v8a", "x86_64"]));
+
+    let hars = artifact_in(&pwd, args(ArtifactSplit::PerAbi, None)).unwrap();
+    assert_eq!(
+      hars,
+      vec![pwd.join("pkg-aarch64.har"), pwd.join("pkg-x86_64.har")]
+    );
+    let (files, abi) = read_har(&hars[1]);
+    assert!(files.contains(&String::from("package/libs/x86_64/libpkg.so")));
+    assert!(files.contains(&String::from("package/libs/index.d.ts")));
+    assert!(!files.iter().any(|f| f.contains("arm64-v8a")));
+    assert_eq!(abi, serde_json::json!(["x86_64"]));
+    for arch in ["arm64-v8a", "x86_64"] {
+      assert!(pwd
+        .join("package/libs")
+        .join(arch)
+        .join("libpkg.so")
+        .is_file());
+    }
+
+    // every .har file needs its own name
+    assert!(artifact_in(&pwd, args(ArtifactSplit::PerAbi, Some("pkg"))).is_err());
+
+    // no library is built
+    for arch in ["arm64-v8a", "x86_64"] {
+      fs::remove_dir_all(pwd.join("dist").join(arch)).unwrap();
+    }
+    assert!(artifact_in(&pwd, args(ArtifactSplit::Fat, None)).is_err());
+  }
+
+  #[test]
+  fn test_parse_artifact_split() {
+    assert_eq!("fat".parse::<ArtifactSplit>(), Ok(ArtifactSplit::Fat));
+    assert_eq!(
+      "per-abi".parse::<ArtifactSplit>(),
+      Ok(ArtifactSplit::PerAbi)
+    );
+    assert!("thin".parse::<ArtifactSplit>().is_err());
+  }
 }
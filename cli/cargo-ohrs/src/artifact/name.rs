@@ -0,0 +1,143 @@
+use crate::util::Arch;
+use anyhow::Error;
+use regex::{Captures, Regex};
+use std::path::Path;
+use std::process::Command;
+
+const TOKENS: &[&str] = &["name", "version", "abi", "sha"];
+
+/// the values of tokens in `--artifact-name`, they are resolved lazily since git may be missing
+pub trait NameVars {
+  fn value(&self, token: &str) -> anyhow::Result<String>;
+}
+
+/// the values from the crate in current folder, the architectures in .har file and git
+pub struct ProjectVars<'a> {
+  pub pwd: &'a Path,
+  pub arch: &'a [Arch],
+}
+
+impl NameVars for ProjectVars<'_> {
+  fn value(&self, token: &str) -> anyhow::Result<String> {
+    match token {
+      "name" | "version" => {
+        let package = super::check::current_package(self.pwd).ok_or(Error::msg(format!(
+          "Can't find the crate in {:?} to resolve {{{}}}.",
+          self.pwd, token
+        )))?;
+        Ok(if token == "name" {
+          package.name.to_string()
+        } else {
+          package.version.to_string()
+        })
+      }
+      "abi" => {
+        if self.arch.is_empty() {
+          return Err(Error::msg(
+            "Can't find any architecture in dist to resolve {abi}.",
+          ));
+        }
+        Ok(abi_name(self.arch))
+      }
+      "sha" => {
+        let output = Command::new("git")
+          .args(["rev-parse", "--short", "HEAD"])
+          .current_dir(self.pwd)
+          .output()?;
+        if !output.status.success() {
+          return Err(Error::msg(format!(
+            "Can't get the git commit to resolve {{sha}}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+          )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+      }
+      _ => unreachable!(),
+    }
+  }
+}
+
+/// `aarch64+x86_64`, the first part of rust target
+pub fn abi_name(arch: &[Arch]) -> String {
+  arch
+    .iter()
+    .map(|a| a.rust_target().split('-').next().unwrap_or_default())
+    .collect::<Vec<_>>()
+    .join("+")
+}
+
+/// interpolate the tokens of template, the result should be a plain file name without extension
+pub fn render_artifact_name(template: &str, vars: &impl NameVars) -> anyhow::Result<String> {
+  let reg = Regex::new(r"\{([^{}]*)\}").unwrap();
+  if let Some(caps) = reg
+    .captures_iter(template)
+    .find(|caps| !TOKENS.contains(&&caps[1]))
+  {
+    return Err(Error::msg(format!(
+      "Unsupported token {} in --artifact-name, only {{name}}, {{version}}, {{abi}} and {{sha}} are supported.",
+      &caps[0]
+    )));
+  }
+  let mut error = None;
+  let name = reg.replace_all(template, |caps: &Captures| {
+    vars.value(&caps[1]).unwrap_or_else(|e| {
+      error.get_or_insert(e);
+      String::new()
+    })
+  });
+  if let Some(e) = error {
+    return Err(e);
+  }
+
+  let name = name.strip_suffix(".har").unwrap_or(&name);
+  let safe = !name.is_empty()
+    && !name.starts_with('.')
+    && name
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '+' | '@'));
+  if !safe {
+    return Err(Error::msg(format!(
+      "Invalid artifact name `{}` of template `{}`, only ASCII letters, digits, `.`, `-`, `_`, `+` and `@` are supported and it can't start with `.`.",
+      name, template
+    )));
+  }
+  Ok(name.to_string())
+}
+
+#[cfg(test)]
+mod test {
+  use super::{abi_name, render_artifact_name, NameVars};
+  use crate::util::Arch;
+  use anyhow::Error;
+
+  struct FixedVars;
+
+  impl NameVars for FixedVars {
+    fn value(&self, token: &str) -> anyhow::Result<String> {
+      match token {
+        "name" => Ok(String::from("foo")),
+        "version" => Ok(String::from("1.2.3")),
+        "abi" => Ok(abi_name(&[Arch::ARM64])),
+        "sha" => Err(Error::msg("not a git repository")),
+        _ => unreachable!(),
+      }
+    }
+  }
+
+  #[test]
+  fn test_render_artifact_name() {
+    let render = |template: &str| render_artifact_name(template, &FixedVars);
+    assert_eq!(
+      render("{name}-{version}-{abi}").unwrap(),
+      "foo-1.2.3-aarch64"
+    );
+    assert_eq!(render("package.har").unwrap(), "package");
+    assert_eq!(abi_name(&[Arch::ARM64, Arch::X86_64]), "aarch64+x86_64");
+    assert!(render("{name}-{sha}").is_err());
+    assert!(render("{name}-{branch}").is_err());
+    assert!(render("../{name}").is_err());
+    assert!(render("{name} {version}").is_err());
+    assert!(render(".{name}").is_err());
+    assert!(render("").is_err());
+  }
+}
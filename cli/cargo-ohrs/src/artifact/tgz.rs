@@ -1,13 +1,111 @@
-use flate2::write::GzEncoder;
-use flate2::Compression;
-use std::fs::File;
-use std::path::PathBuf;
+use crate::util::{register_cleanup, temp_path, unregister_cleanup};
+use flate2::{Compression, GzBuilder};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
 
-/// build file to har
+// all the files and folders in the path, sorted by relative path
+fn collect_entries(root: &Path, dir: &Path, entries: &mut Vec<PathBuf>) -> io::Result<()> {
+  let mut children = fs::read_dir(dir)?
+    .map(|e| e.map(|e| e.path()))
+    .collect::<io::Result<Vec<_>>>()?;
+  children.sort();
+  for child in children {
+    entries.push(child.strip_prefix(root).unwrap().to_path_buf());
+    if child.is_dir() {
+      collect_entries(root, &child, entries)?;
+    }
+  }
+  Ok(())
+}
+
+// fixed mtime, owner and mode, only the executable bit of file is kept
+fn header_for(metadata: &fs::Metadata) -> tar::Header {
+  let mut header = tar::Header::new_gnu();
+  header.set_mtime(0);
+  header.set_uid(0);
+  header.set_gid(0);
+  let _ = header.set_username("");
+  let _ = header.set_groupname("");
+  #[cfg(not(target_os = "windows"))]
+  let executable = {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+  };
+  #[cfg(target_os = "windows")]
+  let executable = false;
+
+  if metadata.is_dir() {
+    header.set_entry_type(tar::EntryType::Directory);
+    header.set_mode(0o755);
+    header.set_size(0);
+  } else {
+    header.set_entry_type(tar::EntryType::Regular);
+    header.set_mode(if executable { 0o755 } else { 0o644 });
+    header.set_size(metadata.len());
+  }
+  header
+}
+
+/// build file to har, the output is the same for the same files
 pub fn generate_har(package_name: PathBuf, package_path: PathBuf) {
-  let har = File::create(package_name).unwrap();
-  let enc = GzEncoder::new(har, Compression::default());
+  // write into the temp file and rename it, so there is no partial .har file when cancelled
+  let tmp_path = temp_path(&package_name);
+  register_cleanup(&tmp_path);
+  let har = File::create(&tmp_path).unwrap();
+  let enc = GzBuilder::new().mtime(0).write(har, Compression::default());
   let mut tar = tar::Builder::new(enc);
-  tar.append_dir_all("package", package_path).unwrap();
-  tar.finish().unwrap();
+
+  let mut entries = Vec::new();
+  collect_entries(&package_path, &package_path, &mut entries).unwrap();
+
+  let mut root = header_for(&fs::metadata(&package_path).unwrap());
+  tar.append_data(&mut root, "package", io::empty()).unwrap();
+  for entry in entries {
+    let path = package_path.join(&entry);
+    let metadata = fs::metadata(&path).unwrap();
+    let mut header = header_for(&metadata);
+    let name = Path::new("package").join(&entry);
+    if metadata.is_dir() {
+      tar.append_data(&mut header, name, io::empty()).unwrap();
+    } else {
+      tar
+        .append_data(&mut header, name, File::open(&path).unwrap())
+        .unwrap();
+    }
+  }
+  tar.into_inner().unwrap().finish().unwrap();
+  fs::rename(&tmp_path, &package_name).unwrap();
+  unregister_cleanup(&tmp_path);
+}
+
+#[cfg(test)]
+mod test {
+  use super::generate_har;
+  use crate::util::test_dir;
+  use std::fs;
+  use std::thread;
+  use std::time::Duration;
+
+  #[test]
+  fn test_reproducible_har() {
+    let temp = test_dir();
+    let dir = temp.path().to_path_buf();
+    let package = dir.join("package");
+    fs::create_dir_all(package.join("libs/arm64-v8a")).unwrap();
+    fs::write(package.join("oh-package.json5"), "{}").unwrap();
+    fs::write(package.join("libs/arm64-v8a/liba.so"), "a").unwrap();
+    fs::write(package.join("libs/index.d.ts"), "export {}").unwrap();
+
+    generate_har(dir.join("first.har"), package.clone());
+    // touch the files, mtime shouldn't change the output
+    thread::sleep(Duration::from_millis(1100));
+    fs::write(package.join("libs/index.d.ts"), "export {}").unwrap();
+    generate_har(dir.join("second.har"), package.clone());
+
+    assert_eq!(
+      fs::read(dir.join("first.har")).unwrap(),
+      fs::read(dir.join("second.har")).unwrap()
+    );
+  }
 }
@@ -1,8 +1,14 @@
-use cargo_metadata::{Artifact, BuildScript};
-use std::path::PathBuf;
+use crate::build::Context;
+use crate::util::Arch;
+use anyhow::Error;
+use cargo_metadata::{Artifact, BuildScript, Package, Target};
+use owo_colors::OwoColorize;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
 
-pub fn resolve_dependence_library(script: BuildScript, ndk: String) -> Option<Vec<PathBuf>> {
-  let sysroot = format!("{ndk}/native/sysroot/usr/lib");
+pub fn resolve_dependence_library(script: BuildScript, sysroot: &Path) -> Option<Vec<PathBuf>> {
+  let sysroot = sysroot.join("usr").join("lib");
 
   if !script.linked_libs.is_empty() && !script.linked_paths.is_empty() {
     let libs = script
@@ -27,7 +33,7 @@ pub fn resolve_dependence_library(script: BuildScript, ndk: String) -> Option<Ve
         }
         let item_path = i.as_str();
         // ignore sysroot lib
-        if item_path.starts_with(&sysroot) {
+        if i.as_std_path().starts_with(&sysroot) {
           return None;
         }
         if item_path.starts_with("native=") {
@@ -54,6 +60,11 @@ pub fn resolve_dependence_library(script: BuildScript, ndk: String) -> Option<Ve
 }
 
 pub fn resolve_artifact_library(target: Artifact) -> Option<Vec<PathBuf>> {
+  let crate_types = &target.target.crate_types;
+  let has_crate_type = |t: &str| crate_types.iter().any(|c| c == t);
+  // the file is selected by crate-type, so `rlib` of `["cdylib", "rlib"]` is ignored
+  let shared = has_crate_type("cdylib") || has_crate_type("dylib");
+  let stat = has_crate_type("staticlib");
   Some(
     target
       .filenames
@@ -62,15 +73,231 @@ pub fn resolve_artifact_library(target: Artifact) -> Option<Vec<PathBuf>> {
         // avoid final target has the same package name with crate
         // for example: build reqwest
         // support build exec, but ignore it
-        if let Some(ext) = i.extension() {
-          if ext == "so" || ext == "a" {
-            return Some(i);
-          }
-          return None;
+        match i.extension() {
+          Some("so") if shared => Some(i),
+          Some("a") if stat => Some(i),
+          _ => None,
         }
-        None
       })
       .map(|i| i.canonicalize().expect("Convert to absolute path failed."))
       .collect::<Vec<_>>(),
   )
 }
+
+pub const CDYLIB_SNIPPET: &str = r#"[lib]
+  crate-type = ["cdylib"]"#;
+
+/// resolve the profile folder name under target dir, `dev` and `test` are mapped to `debug`
+pub fn resolve_profile_dir(init_args: &[&str], cargo_args: &[String]) -> String {
+  let mut profile = init_args
+    .contains(&"--release")
+    .then(|| String::from("release"));
+
+  let mut iter = cargo_args.iter();
+  while let Some(arg) = iter.next() {
+    if arg == "--release" {
+      profile = Some(String::from("release"));
+    } else if arg == "--profile" {
+      profile = iter.next().cloned();
+    } else if let Some(p) = arg.strip_prefix("--profile=") {
+      profile = Some(String::from(p));
+    }
+  }
+
+  match profile.as_deref() {
+    None | Some("dev") | Some("test") => String::from("debug"),
+    Some(p) => String::from(p),
+  }
+}
+
+/// the lib target which is built as `cdylib`, examples, tests and benches are not included
+pub fn cdylib_target(pkg: &Package) -> Option<&Target> {
+  pkg.targets.iter().find(|t| {
+    t.crate_types.iter().any(|c| c == "cdylib")
+      && !t
+        .kind
+        .iter()
+        .any(|k| k == "example" || k == "test" || k == "bench")
+  })
+}
+
+/// only one `cdylib` target of current package can be packaged
+pub fn check_single_cdylib(pkg: &Package, built: &[String]) -> anyhow::Result<()> {
+  if built.len() > 1 {
+    return Err(Error::msg(format!(
+      "Multiple cdylib targets of {} are built: {}, please only build one of them, e.g. with --lib.",
+      pkg.name,
+      built.join(", ")
+    )));
+  }
+  Ok(())
+}
+
+/// check the final `cdylib` library is existed after cargo build
+pub fn check_cdylib_library(
+  ctx: &Context,
+  arch: &Arch,
+  cargo_args: &[String],
+) -> anyhow::Result<PathBuf> {
+  let (Some(pkg), Some(target_dir)) = (&ctx.package, &ctx.cargo_build_target_dir) else {
+    return Err(Error::msg("Try to get package meta-info failed."));
+  };
+
+  let lib_target = cdylib_target(pkg).or(
+    pkg
+      .targets
+      .iter()
+      .find(|t| t.kind.iter().any(|k| k == "lib" || k == "cdylib")),
+  );
+
+  let lib_name = lib_target
+    .map(|t| t.name.clone())
+    .unwrap_or(pkg.name.clone())
+    .replace('-', "_");
+
+  let expected = target_dir
+    .as_std_path()
+    .join(arch.rust_target())
+    .join(resolve_profile_dir(&ctx.init_args, cargo_args))
+    .join(format!("lib{}.so", lib_name));
+
+  if expected.is_file() {
+    return Ok(expected);
+  }
+
+  let crate_types = lib_target
+    .map(|t| t.crate_types.join(", "))
+    .unwrap_or(String::from("none"));
+
+  Err(Error::msg(format!(
+    "Can't find the final library: {}.
+  Detected crate-type: [{}]
+  Hint: OHOS native module must be a cdylib, please add the following to your Cargo.toml:
+
+  {}",
+    expected.display(),
+    crate_types,
+    CDYLIB_SNIPPET
+  )))
+}
+
+/// read `e_machine` from ELF header, return None if it's not an ELF file
+fn read_elf_machine(p: &Path) -> Option<u16> {
+  let mut header = [0u8; 20];
+  File::open(p).ok()?.read_exact(&mut header).ok()?;
+  if header[..4] != [0x7f, b'E', b'L', b'F'] {
+    return None;
+  }
+  let machine = [header[18], header[19]];
+  // EI_DATA: 1 is little endian, 2 is big endian
+  match header[5] {
+    2 => Some(u16::from_be_bytes(machine)),
+    _ => Some(u16::from_le_bytes(machine)),
+  }
+}
+
+/// warn those extra shared libraries which can't be matched to target architecture
+pub fn check_bundle_library(libs: &[PathBuf], build_arch: &[Arch]) {
+  libs.iter().for_each(|l| match read_elf_machine(l) {
+    Some(machine) => {
+      if !build_arch.iter().any(|a| a.elf_machine() == machine) {
+        eprintln!(
+          "{}: {} doesn't match any target architecture, it will be ignored.",
+          "Warning".bold().yellow(),
+          l.display()
+        );
+      }
+    }
+    None => {
+      eprintln!(
+        "{}: Can't detect the architecture of {}, it will be copied to all targets.",
+        "Warning".bold().yellow(),
+        l.display()
+      );
+    }
+  })
+}
+
+/// filter extra shared libraries for current arch, library whose architecture is unknown will be kept
+pub fn resolve_bundle_library(libs: &[PathBuf], arch: &Arch) -> Vec<PathBuf> {
+  libs
+    .iter()
+    .filter(|l| !matches!(read_elf_machine(l), Some(m) if m != arch.elf_machine()))
+    .cloned()
+    .collect()
+}
+
+#[cfg(test)]
+mod test {
+  use super::{cdylib_target, check_single_cdylib, resolve_artifact_library};
+  use crate::util::test_dir;
+  use cargo_metadata::{Artifact, MetadataCommand};
+  use std::fs;
+
+  #[test]
+  fn test_cdylib_with_rlib() {
+    let temp = test_dir();
+    let dir = temp.path().to_path_buf();
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::create_dir_all(dir.join("examples")).unwrap();
+    fs::write(
+      dir.join("Cargo.toml"),
+      r#"[package]
+name = "both-types"
+version = "0.1.0"
+edition = "2021"
+
+[lib]
+crate-type = ["cdylib", "rlib"]
+
+[[example]]
+name = "plugin"
+crate-type = ["cdylib"]
+"#,
+    )
+    .unwrap();
+    fs::write(dir.join("src/lib.rs"), "").unwrap();
+    fs::write(dir.join("examples/plugin.rs"), "").unwrap();
+
+    let metadata = MetadataCommand::new()
+      .no_deps()
+      .manifest_path(dir.join("Cargo.toml"))
+      .exec()
+      .unwrap();
+    let pkg = &metadata.packages[0];
+    let target = cdylib_target(pkg).unwrap();
+    assert_eq!(target.name, "both_types");
+
+    // cargo reports both files of the lib target
+    let out = dir.join("target");
+    fs::create_dir_all(&out).unwrap();
+    for f in ["libboth_types.so", "libboth_types.rlib"] {
+      fs::write(out.join(f), "").unwrap();
+    }
+    let artifact: Artifact = serde_json::from_value(serde_json::json!({
+      "package_id": pkg.id,
+      "manifest_path": pkg.manifest_path,
+      "target": target,
+      "profile": {
+        "opt_level": "0",
+        "debuginfo": 2,
+        "debug_assertions": true,
+        "overflow_checks": true,
+        "test": false
+      },
+      "features": [],
+      "filenames": [out.join("libboth_types.so"), out.join("libboth_types.rlib")],
+      "executable": null,
+      "fresh": false
+    }))
+    .unwrap();
+    let files = resolve_artifact_library(artifact).unwrap();
+    assert_eq!(files.len(), 1);
+    assert!(files[0].ends_with("libboth_types.so"));
+
+    assert!(check_single_cdylib(pkg, &[String::from("both_types")]).is_ok());
+    assert!(
+      check_single_cdylib(pkg, &[String::from("both_types"), String::from("plugin")]).is_err()
+    );
+  }
+}
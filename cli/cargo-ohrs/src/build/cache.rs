@@ -0,0 +1,130 @@
+use crate::build::Context;
+use crate::util::write_atomic;
+use cargo_metadata::Package;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::Path;
+
+// the flags which may change the generated .d.ts file
+fn dts_fingerprint(ctx: &Context) -> String {
+  let options = &ctx.dts_options;
+  let header = ctx
+    .template
+    .as_ref()
+    .and_then(|t| t.header.clone())
+    .unwrap_or_default();
+  format!(
+    "flatten_singletons={};strict={};dual={};split={};annotate_throws={};no_struct_first={};lint_ignore={};optional_style={};namespace_keyword={};export_equals={};polyfills={};external_types={};transform={};formatter={};namespace_order={};header={}",
+    options.flatten_singletons,
+    options.strict,
+    options.dual,
+    options.split,
+    options.annotate_throws,
+    options.no_struct_first,
+    options.lint_ignore.name(),
+    options.optional_style.name(),
+    options.namespace_keyword.name(),
+    options.export_equals.as_deref().unwrap_or_default(),
+    options
+      .polyfills
+      .iter()
+      .map(|p| p.name())
+      .collect::<Vec<_>>()
+      .join(","),
+    options
+      .external_types
+      .iter()
+      .map(|e| e.name())
+      .collect::<Vec<_>>()
+      .join(","),
+    options.transform.as_deref().unwrap_or_default(),
+    options.formatter.as_deref().unwrap_or_default(),
+    options.namespace_order.join(","),
+    header
+  )
+}
+
+/// name of the intermediate type def file
+/// use the hash of manifest path by default, or the hash of build inputs without any path with content hash,
+/// so the name will be the same in different checkout paths.
+pub fn intermediate_file_name(
+  pkg: &Package,
+  ctx: &Context,
+  cargo_args: &[String],
+  content_hash: bool,
+) -> String {
+  let mut hasher = Sha256::new();
+  if content_hash {
+    hasher.update(env!("CARGO_PKG_VERSION"));
+    hasher.update(pkg.name.as_str());
+    hasher.update(pkg.version.to_string());
+    hasher.update(ctx.init_args.join(" "));
+    hasher.update(cargo_args.join(" "));
+    hasher.update(dts_fingerprint(ctx));
+  } else {
+    hasher.update(pkg.manifest_path.as_str());
+  }
+  let hash_hex = format!("{:x}", hasher.finalize());
+  format!("{}-{}.napi_type_def.tmp", &pkg.name, &hash_hex[..8])
+}
+
+/// hash of intermediate content, tool version and dts flags
+pub fn cache_key(ctx: &Context, intermediate: &str) -> String {
+  // the order of lines is decided by the expansion order of macro, sort them to make key stable
+  let mut lines = intermediate
+    .lines()
+    .map(|l| l.trim())
+    .filter(|l| !l.is_empty())
+    .collect::<Vec<_>>();
+  lines.sort_unstable();
+
+  let mut hasher = Sha256::new();
+  hasher.update(env!("CARGO_PKG_VERSION"));
+  hasher.update(dts_fingerprint(ctx));
+  lines.iter().for_each(|l| {
+    hasher.update(l);
+    hasher.update("\n");
+  });
+  format!("{:x}", hasher.finalize())
+}
+
+/// write the cache key and outputs for CI
+pub fn emit_metadata(ctx: &Context, path: &Path, dts: Option<&Path>) -> anyhow::Result<()> {
+  let intermediate = fs::read_to_string(&ctx.tmp_ts_file_path).unwrap_or_default();
+  let metadata = json!({
+    "tool_version": env!("CARGO_PKG_VERSION"),
+    "cache_key": cache_key(ctx, &intermediate),
+    "intermediate": ctx.tmp_ts_file_path.display().to_string(),
+    "dts": dts.map(|p| p.display().to_string()),
+  });
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent)?;
+  }
+  write_atomic(path, serde_json::to_string_pretty(&metadata)?.as_bytes())?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod test {
+  use super::cache_key;
+  use crate::build::Context;
+
+  #[test]
+  fn test_cache_key() {
+    let mut ctx = Context::default();
+    let key = cache_key(&ctx, "{\"kind\": \"fn\"}\n{\"kind\": \"struct\"}\n");
+    // line order and checkout path don't change the key
+    ctx.pwd = "/other/checkout".into();
+    assert_eq!(
+      key,
+      cache_key(&ctx, "{\"kind\": \"struct\"}\n{\"kind\": \"fn\"}\n")
+    );
+
+    ctx.dts_options.dual = true;
+    assert_ne!(
+      key,
+      cache_key(&ctx, "{\"kind\": \"fn\"}\n{\"kind\": \"struct\"}\n")
+    );
+  }
+}
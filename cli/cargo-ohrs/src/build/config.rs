@@ -0,0 +1,234 @@
+use crate::build::{resolve_profile_dir, Context};
+use crate::util::Arch;
+use owo_colors::OwoColorize;
+
+const DEFAULT_ARCH: [Arch; 3] = [Arch::ARM64, Arch::ARM32, Arch::X86_64];
+
+fn source(is_set: bool, set: &'static str) -> &'static str {
+  if is_set {
+    return set;
+  }
+  "default"
+}
+
+// the value in [package.metadata.ohrs] is only used when the flag is not set
+fn flag_source(ctx: &Context, key: &str, is_set: bool) -> &'static str {
+  if ctx.manifest_keys.contains(&key) {
+    return "manifest";
+  }
+  source(is_set, "flag")
+}
+
+fn print_item(key: &str, value: String, source: &str) {
+  println!(
+    "{:<14}{}  {}",
+    key.green(),
+    value,
+    format!("({})", source).dimmed()
+  );
+}
+
+/// print the effective configuration for `--print-config`
+pub fn print_config(args: &crate::BuildArgs, ctx: &Context) {
+  print_item(
+    "dist",
+    ctx.dist.display().to_string(),
+    flag_source(ctx, "dist", args.dist.is_some()),
+  );
+
+  let arch = args.arch.clone().unwrap_or(DEFAULT_ARCH.to_vec());
+  print_item(
+    "targets",
+    arch
+      .iter()
+      .map(|a| a.rust_target())
+      .collect::<Vec<_>>()
+      .join(", "),
+    flag_source(ctx, "targets", args.arch.is_some()),
+  );
+
+  print_item(
+    "env file",
+    if ctx.env_keys.is_empty() {
+      String::from("none")
+    } else {
+      ctx.env_keys.join(", ")
+    },
+    source(args.env_file.is_some(), "flag"),
+  );
+  print_item(
+    "ndk",
+    ctx.ndk.display().to_string(),
+    if args.ndk.is_some() {
+      "flag"
+    } else if ctx.env_keys.iter().any(|k| k == "OHOS_NDK_HOME") {
+      "env file"
+    } else {
+      "env"
+    },
+  );
+  print_item(
+    "sysroot",
+    ctx.sysroot.display().to_string(),
+    if args.sysroot.is_some() {
+      "flag"
+    } else if ctx.env_keys.iter().any(|k| k == "OHOS_SYSROOT") {
+      "env file"
+    } else if std::env::var("OHOS_SYSROOT").is_ok() {
+      "env"
+    } else {
+      "ndk"
+    },
+  );
+  print_item(
+    "ndk version",
+    ctx.ndk_version.clone().unwrap_or(String::from("unknown")),
+    "ndk",
+  );
+
+  let cargo_args = args.cargo_args.clone().unwrap_or_default();
+  let is_profile_set = args.release
+    || args.profile.is_some()
+    || cargo_args
+      .iter()
+      .any(|a| a == "--release" || a.starts_with("--profile"));
+  print_item(
+    "profile",
+    resolve_profile_dir(&ctx.init_args, &cargo_args),
+    source(is_profile_set, "flag"),
+  );
+  print_item(
+    "static",
+    ctx.copy_static.to_string(),
+    flag_source(ctx, "static", args.copy_static.is_some()),
+  );
+  print_item(
+    "strict",
+    ctx.dts_options.strict.to_string(),
+    flag_source(ctx, "strict", args.strict.is_some()),
+  );
+
+  print_item(
+    "dts",
+    String::from(if args.no_dts.unwrap_or_default() {
+      "skip"
+    } else {
+      "generate"
+    }),
+    flag_source(ctx, "no-dts", args.no_dts.is_some()),
+  );
+  print_item(
+    "dts lint ignore",
+    ctx.dts_options.lint_ignore.name().to_string(),
+    flag_source(ctx, "lint-ignore", args.lint_ignore.is_some()),
+  );
+  print_item(
+    "dts optional style",
+    ctx.dts_options.optional_style.name().to_string(),
+    flag_source(ctx, "optional-style", args.optional_style.is_some()),
+  );
+  print_item(
+    "dts namespace keyword",
+    ctx.dts_options.namespace_keyword.name().to_string(),
+    flag_source(ctx, "namespace-keyword", args.namespace_keyword.is_some()),
+  );
+  print_item(
+    "dts export equals",
+    ctx
+      .dts_options
+      .export_equals
+      .clone()
+      .unwrap_or(String::from("none")),
+    flag_source(ctx, "export-equals", args.export_equals.is_some()),
+  );
+  print_item(
+    "dts polyfills",
+    if ctx.dts_options.polyfills.is_empty() {
+      String::from("inline")
+    } else {
+      ctx
+        .dts_options
+        .polyfills
+        .iter()
+        .map(|p| p.name())
+        .collect::<Vec<_>>()
+        .join(", ")
+    },
+    flag_source(ctx, "polyfill", !args.polyfill.is_empty()),
+  );
+  print_item(
+    "dts external types",
+    if ctx.dts_options.external_types.is_empty() {
+      String::from("none")
+    } else {
+      ctx
+        .dts_options
+        .external_types
+        .iter()
+        .map(|e| e.name())
+        .collect::<Vec<_>>()
+        .join(", ")
+    },
+    flag_source(ctx, "external-type", !args.external_type.is_empty()),
+  );
+  let header = ctx.template.as_ref().and_then(|t| t.header.clone());
+  print_item(
+    "dts header",
+    header.clone().unwrap_or_default().trim().to_string(),
+    source(header.is_some(), "file"),
+  );
+  print_item(
+    "dts out",
+    ctx
+      .dts_options
+      .out
+      .clone()
+      .unwrap_or(ctx.dist.join("index.d.ts"))
+      .display()
+      .to_string(),
+    flag_source(ctx, "dts-out", ctx.dts_options.out.is_some()),
+  );
+  print_item(
+    "js out",
+    ctx
+      .dts_options
+      .js_out
+      .clone()
+      .or_else(|| {
+        ctx.dts_options.export_equals.as_ref().map(|_| {
+          ctx
+            .dts_options
+            .out
+            .clone()
+            .unwrap_or(ctx.dist.join("index.d.ts"))
+            .with_file_name("index.js")
+        })
+      })
+      .map_or(String::from("none"), |p| p.display().to_string()),
+    flag_source(ctx, "js-out", ctx.dts_options.js_out.is_some()),
+  );
+  print_item(
+    "types package",
+    ctx
+      .dts_options
+      .types_package
+      .as_ref()
+      .map(|p| p.display().to_string())
+      .unwrap_or(String::from("none")),
+    flag_source(
+      ctx,
+      "types-package",
+      ctx.dts_options.types_package.is_some(),
+    ),
+  );
+  print_item(
+    "dts tmp file",
+    ctx.tmp_ts_file_path.display().to_string(),
+    source(args.content_hash, "content hash"),
+  );
+  print_item(
+    "cache dir",
+    ctx.cache_dir.display().to_string(),
+    flag_source(ctx, "cache-dir", args.cache_dir.is_some()),
+  );
+}
@@ -0,0 +1,166 @@
+use cargo_metadata::diagnostic::{Diagnostic, DiagnosticLevel};
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+// count of diagnostics shown in summary
+const SUMMARY_LIMIT: usize = 5;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticItem {
+  pub target: String,
+  pub level: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub code: Option<String>,
+  pub message: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub file: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub line: Option<usize>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub column: Option<usize>,
+}
+
+/// compiler errors and warnings of all targets
+#[derive(Debug, Clone, Default)]
+pub struct Diagnostics {
+  pub items: Vec<DiagnosticItem>,
+}
+
+impl Diagnostics {
+  /// record the error or warning, return false for the notes and the summary of rustc
+  pub fn push(&mut self, target: &str, diagnostic: &Diagnostic) -> bool {
+    let level = match diagnostic.level {
+      DiagnosticLevel::Error | DiagnosticLevel::Ice => "error",
+      DiagnosticLevel::Warning => "warning",
+      _ => return false,
+    };
+    // `aborting due to 2 previous errors` and `1 warning emitted`
+    if diagnostic.message.starts_with("aborting due to") || diagnostic.message.ends_with(" emitted")
+    {
+      return false;
+    }
+    let span = diagnostic
+      .spans
+      .iter()
+      .find(|s| s.is_primary)
+      .or(diagnostic.spans.first());
+    self.items.push(DiagnosticItem {
+      target: target.to_string(),
+      level: level.to_string(),
+      code: diagnostic.code.as_ref().map(|c| c.code.clone()),
+      message: diagnostic.message.clone(),
+      file: span.map(|s| s.file_name.clone()),
+      line: span.map(|s| s.line_start),
+      column: span.map(|s| s.column_start),
+    });
+    true
+  }
+
+  fn count(&self, target: &str, level: &str) -> usize {
+    self
+      .items
+      .iter()
+      .filter(|i| i.target == target && i.level == level)
+      .count()
+  }
+
+  /// `1 error, 2 warnings` and the first few diagnostics of the target
+  pub fn summary(&self, target: &str) -> Option<String> {
+    let errors = self.count(target, "error");
+    let warnings = self.count(target, "warning");
+    if errors == 0 && warnings == 0 {
+      return None;
+    }
+    let plural = |n: usize, s: &str| format!("{} {}{}", n, s, if n > 1 { "s" } else { "" });
+    let mut ret = format!(
+      "{}: {}, {}",
+      target,
+      plural(errors, "error"),
+      plural(warnings, "warning")
+    );
+    // errors first
+    let mut items = self
+      .items
+      .iter()
+      .filter(|i| i.target == target)
+      .collect::<Vec<_>>();
+    items.sort_by_key(|i| i.level != "error");
+    for item in items.iter().take(SUMMARY_LIMIT) {
+      let level = match &item.code {
+        Some(code) => format!("{}[{}]", item.level, code),
+        None => item.level.clone(),
+      };
+      let level = if item.level == "error" {
+        level.red().bold().to_string()
+      } else {
+        level.yellow().bold().to_string()
+      };
+      ret += &format!("\n  {}: {}", level, item.message);
+      if let (Some(file), Some(line), Some(column)) = (&item.file, item.line, item.column) {
+        ret += &format!(" {}", format!("--> {}:{}:{}", file, line, column).dimmed());
+      }
+    }
+    if items.len() > SUMMARY_LIMIT {
+      ret += &format!("\n  ... and {} more", items.len() - SUMMARY_LIMIT);
+    }
+    Some(ret)
+  }
+
+  pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(&self.items)?)?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::Diagnostics;
+  use cargo_metadata::diagnostic::Diagnostic;
+
+  fn diagnostic(level: &str, message: &str) -> Diagnostic {
+    serde_json::from_value(serde_json::json!({
+      "message": message,
+      "code": null,
+      "level": level,
+      "spans": [{
+        "file_name": "src/lib.rs",
+        "byte_start": 0,
+        "byte_end": 1,
+        "line_start": 3,
+        "line_end": 3,
+        "column_start": 5,
+        "column_end": 6,
+        "is_primary": true,
+        "text": [],
+        "label": null,
+        "suggested_replacement": null,
+        "suggestion_applicability": null,
+        "expansion": null
+      }],
+      "children": [],
+      "rendered": null
+    }))
+    .unwrap()
+  }
+
+  #[test]
+  fn test_diagnostics_summary() {
+    let mut diagnostics = Diagnostics::default();
+    let target = "aarch64-unknown-linux-ohos";
+    assert!(diagnostics.push(target, &diagnostic("warning", "unused variable: `a`")));
+    assert!(diagnostics.push(target, &diagnostic("error", "mismatched types")));
+    assert!(!diagnostics.push(target, &diagnostic("warning", "1 warning emitted")));
+    assert!(!diagnostics.push(target, &diagnostic("note", "some note")));
+
+    let summary = diagnostics.summary(target).unwrap();
+    assert!(summary.starts_with("aarch64-unknown-linux-ohos: 1 error, 1 warning"));
+    assert!(summary.find("mismatched types").unwrap() < summary.find("unused variable").unwrap());
+    assert!(summary.contains("src/lib.rs:3:5"));
+    assert_eq!(diagnostics.summary("x86_64-unknown-linux-ohos"), None);
+  }
+}
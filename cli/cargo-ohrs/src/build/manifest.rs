@@ -0,0 +1,268 @@
+use crate::util::Arch;
+use anyhow::Error;
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// build settings in `[package.metadata.ohrs]` of Cargo.toml, the keys are the same as the flags
+/// the flags always win, a value in manifest is only used when the flag is not set,
+/// and the defaults are applied after merging
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct ManifestConfig {
+  dist: Option<String>,
+  targets: Option<Vec<String>>,
+  #[serde(rename = "static")]
+  copy_static: Option<bool>,
+  strict: Option<bool>,
+  bundle_lib: Option<Vec<String>>,
+  no_dts: Option<bool>,
+  dts_out: Option<String>,
+  js_out: Option<String>,
+  split_dts: Option<bool>,
+  types_package: Option<String>,
+  dual: Option<bool>,
+  flatten_singletons: Option<bool>,
+  annotate_throws: Option<bool>,
+  no_struct_first: Option<bool>,
+  namespace_order: Option<Vec<String>>,
+  lint_ignore: Option<String>,
+  optional_style: Option<String>,
+  namespace_keyword: Option<String>,
+  dts_transform: Option<String>,
+  dts_formatter: Option<String>,
+  dts_closed_world: Option<bool>,
+  export_equals: Option<String>,
+  polyfill: Option<Vec<String>>,
+  external_type: Option<Vec<String>>,
+  cache_dir: Option<String>,
+}
+
+fn parse<T: FromStr<Err = String>>(key: &str, value: &str) -> anyhow::Result<T> {
+  value
+    .parse()
+    .map_err(|e| Error::msg(format!("Invalid {} in [package.metadata.ohrs]: {}", key, e)))
+}
+
+/// merge `[package.metadata.ohrs]` into the args which are not set by flags
+/// return the keys which are taken from manifest
+pub fn merge_manifest_metadata(
+  args: &mut crate::BuildArgs,
+  metadata: &serde_json::Value,
+) -> anyhow::Result<Vec<&'static str>> {
+  let Some(value) = metadata.get("ohrs") else {
+    return Ok(vec![]);
+  };
+  let config: ManifestConfig = serde_json::from_value(value.clone())
+    .map_err(|e| Error::msg(format!("Invalid [package.metadata.ohrs]: {}", e)))?;
+
+  let mut keys = Vec::new();
+
+  if let Some(dist) = config.dist.filter(|_| args.dist.is_none()) {
+    args.dist = Some(dist);
+    keys.push("dist");
+  }
+  if let Some(targets) = config.targets.filter(|_| args.arch.is_none()) {
+    let arch = targets
+      .iter()
+      .map(|t| parse::<Arch>("targets", t))
+      .collect::<anyhow::Result<Vec<_>>>()?;
+    if arch.is_empty() {
+      return Err(Error::msg(
+        "Invalid targets in [package.metadata.ohrs]: please provide at least one architecture.",
+      ));
+    }
+    args.arch = Some(arch);
+    keys.push("targets");
+  }
+  if let Some(polyfill) = config.polyfill.filter(|_| args.polyfill.is_empty()) {
+    args.polyfill = polyfill
+      .iter()
+      .map(|p| parse("polyfill", p))
+      .collect::<anyhow::Result<Vec<_>>>()?;
+    keys.push("polyfill");
+  }
+  if let Some(external_type) = config
+    .external_type
+    .filter(|_| args.external_type.is_empty())
+  {
+    args.external_type = external_type
+      .iter()
+      .map(|e| parse("external-type", e))
+      .collect::<anyhow::Result<Vec<_>>>()?;
+    keys.push("external-type");
+  }
+  if let Some(bundle_lib) = config.bundle_lib.filter(|_| args.bundle_lib.is_empty()) {
+    args.bundle_lib = bundle_lib;
+    keys.push("bundle-lib");
+  }
+
+  // `--strict=false` still turns off the switch set by manifest
+  for (key, flag, value) in [
+    ("static", &mut args.copy_static, config.copy_static),
+    ("strict", &mut args.strict, config.strict),
+    ("no-dts", &mut args.no_dts, config.no_dts),
+    ("split-dts", &mut args.split_dts, config.split_dts),
+    ("dual", &mut args.dual, config.dual),
+    (
+      "flatten-singletons",
+      &mut args.flatten_singletons,
+      config.flatten_singletons,
+    ),
+    (
+      "annotate-throws",
+      &mut args.annotate_throws,
+      config.annotate_throws,
+    ),
+    (
+      "no-struct-first",
+      &mut args.no_struct_first,
+      config.no_struct_first,
+    ),
+    (
+      "dts-closed-world",
+      &mut args.dts_closed_world,
+      config.dts_closed_world,
+    ),
+  ] {
+    if flag.is_none() && value.is_some() {
+      *flag = value;
+      keys.push(key);
+    }
+  }
+
+  for (key, arg, value) in [
+    ("dts-out", &mut args.dts_out, config.dts_out),
+    ("js-out", &mut args.js_out, config.js_out),
+    (
+      "types-package",
+      &mut args.types_package,
+      config.types_package,
+    ),
+    (
+      "dts-transform",
+      &mut args.dts_transform,
+      config.dts_transform,
+    ),
+    (
+      "dts-formatter",
+      &mut args.dts_formatter,
+      config.dts_formatter,
+    ),
+    (
+      "export-equals",
+      &mut args.export_equals,
+      config.export_equals,
+    ),
+    ("cache-dir", &mut args.cache_dir, config.cache_dir),
+    (
+      "namespace-order",
+      &mut args.namespace_order,
+      config.namespace_order.map(|n| n.join(",")),
+    ),
+  ] {
+    if arg.is_none() && value.is_some() {
+      *arg = value;
+      keys.push(key);
+    }
+  }
+
+  if let Some(lint) = config.lint_ignore.filter(|_| args.lint_ignore.is_none()) {
+    args.lint_ignore = Some(parse("lint-ignore", &lint)?);
+    keys.push("lint-ignore");
+  }
+  if let Some(style) = config
+    .optional_style
+    .filter(|_| args.optional_style.is_none())
+  {
+    args.optional_style = Some(parse("optional-style", &style)?);
+    keys.push("optional-style");
+  }
+  if let Some(keyword) = config
+    .namespace_keyword
+    .filter(|_| args.namespace_keyword.is_none())
+  {
+    args.namespace_keyword = Some(parse("namespace-keyword", &keyword)?);
+    keys.push("namespace-keyword");
+  }
+
+  Ok(keys)
+}
+
+#[cfg(test)]
+mod test {
+  use super::merge_manifest_metadata;
+  use crate::build::LintIgnore;
+  use crate::util::Arch;
+  use serde_json::json;
+
+  fn build_args(flags: &[&str]) -> crate::BuildArgs {
+    let args = [&["build"], flags].concat();
+    match crate::cli::cli_run().run_inner(args.as_slice()).unwrap() {
+      crate::Options::Build(args) => args,
+      _ => unreachable!(),
+    }
+  }
+
+  #[test]
+  fn test_merge_manifest_metadata() {
+    let metadata = json!({
+      "ohrs": {
+        "dist": "out",
+        "targets": ["arm64"],
+        "strict": true,
+        "split-dts": true,
+        "lint-ignore": "biome",
+        "namespace-order": ["core", "utils"]
+      }
+    });
+
+    let mut args = build_args(&[]);
+    let keys = merge_manifest_metadata(&mut args, &metadata).unwrap();
+    assert_eq!(args.dist(), "out");
+    assert_eq!(args.arch, Some(vec![Arch::ARM64]));
+    assert_eq!((args.strict, args.split_dts), (Some(true), Some(true)));
+    assert_eq!(args.lint_ignore, Some(LintIgnore::Biome));
+    assert_eq!(args.namespace_order.as_deref(), Some("core,utils"));
+    assert_eq!(keys.len(), 6);
+
+    // flags win
+    let mut args = build_args(&["--dist", "pkg", "--arch", "x86_64", "--lint-ignore", "none"]);
+    let keys = merge_manifest_metadata(&mut args, &metadata).unwrap();
+    assert_eq!(args.dist(), "pkg");
+    assert_eq!(args.arch, Some(vec![Arch::X86_64]));
+    assert_eq!(args.lint_ignore, Some(LintIgnore::None));
+    assert!(!keys.contains(&"dist"));
+
+    // the flags with default values still win
+    let mut args = build_args(&[
+      "--dist",
+      "dist",
+      "--arch",
+      "arm64",
+      "--arch",
+      "arm",
+      "--arch",
+      "x86_64",
+      "--lint-ignore",
+      "eslint",
+      "--strict=false",
+    ]);
+    let keys = merge_manifest_metadata(&mut args, &metadata).unwrap();
+    assert_eq!(args.dist(), "dist");
+    assert_eq!(args.arch.as_ref().map(|a| a.len()), Some(3));
+    assert_eq!(args.lint_ignore, Some(LintIgnore::Eslint));
+    assert_eq!((args.strict, args.split_dts), (Some(false), Some(true)));
+    assert_eq!(keys, vec!["split-dts", "namespace-order"]);
+
+    let mut args = build_args(&[]);
+    assert!(merge_manifest_metadata(&mut args, &json!({ "ohrs": { "dist-dir": "out" } })).is_err());
+    assert!(
+      merge_manifest_metadata(&mut args, &json!({ "ohrs": { "targets": ["mips"] } })).is_err()
+    );
+    assert!(
+      merge_manifest_metadata(&mut args, &json!({ "template": {} }))
+        .unwrap()
+        .is_empty()
+    );
+  }
+}
@@ -1,19 +1,28 @@
 use crate::check_and_clean_file_or_dir;
-use crate::util::Arch;
+use crate::util::{Arch, NdkToolchain, Progress, METADATA};
 use cargo_metadata::camino::Utf8PathBuf;
 use cargo_metadata::Package;
+use once_cell::sync::OnceCell;
 use serde::Deserialize;
 use std::env;
 use std::path::PathBuf;
 
 mod abort_tmp;
 mod artifact;
+mod cache;
+mod config;
+mod diagnostics;
+mod manifest;
 mod prepare;
 mod run;
+mod since;
 mod ts;
 
 #[allow(unused_imports)]
 pub use artifact::*;
+#[doc(hidden)]
+pub use ts::{process_type_def, DtsOptions};
+pub use ts::{ExportsFormat, ExternalType, LintIgnore, NamespaceKeyword, OptionalStyle, Polyfill};
 
 #[derive(Debug, Clone, Default, Deserialize)]
 pub struct Template {
@@ -37,7 +46,12 @@ pub struct Context<'a> {
   // 当前构建项目的产物地址 用于支持cargo workspace的构建
   pub cargo_build_target_dir: Option<Utf8PathBuf>,
   // ndk 路径
-  pub ndk: String,
+  pub ndk: PathBuf,
+  pub sysroot: PathBuf,
+  // 从 ndk 中解析出的版本号
+  pub ndk_version: Option<String>,
+  // ndk 工具链 所有 target 共用 只检查一次
+  pub ndk_toolchain: OnceCell<NdkToolchain>,
   // 所有产物的文件路径 避免重复获取
   #[allow(dead_code)]
   pub dist_files: Vec<PathBuf>,
@@ -45,15 +59,60 @@ pub struct Context<'a> {
 
   pub copy_static: bool,
   pub tmp_ts_file_path: PathBuf,
+  // 额外需要拷贝到产物中的动态库
+  pub bundle_libs: Vec<PathBuf>,
+  // .d.ts 生成配置
+  pub dts_options: ts::DtsOptions,
+  // 构建完成后写入 cache key 等信息的文件
+  pub emit_metadata: Option<PathBuf>,
+  // 输出完整的编译信息
+  pub verbose: bool,
+  // 写入编译错误和警告的 json 文件
+  pub emit_diagnostics: Option<PathBuf>,
+  // 只需要类型定义 不拷贝产物到 dist
+  pub skip_artifacts: bool,
+  // 从 [package.metadata.ohrs] 读取的配置项
+  pub manifest_keys: Vec<&'static str>,
+  // 从 --env-file 设置的环境变量
+  pub env_keys: Vec<String>,
+  // ndk lock、.d.ts hash 等构建状态的缓存目录
+  pub cache_dir: PathBuf,
+}
+
+impl Context<'_> {
+  /// 获取 ndk 工具链 首次调用时检查
+  pub fn ndk_toolchain(&self) -> anyhow::Result<&NdkToolchain> {
+    self
+      .ndk_toolchain
+      .get_or_try_init(|| NdkToolchain::probe(&self.ndk))
+  }
 }
 
 /// build逻辑
-pub fn build(args: crate::BuildArgs) -> anyhow::Result<()> {
+pub fn build(mut args: crate::BuildArgs) -> anyhow::Result<()> {
+  // --since 会切换到各个 crate 目录构建，env file 需要相对于当前目录解析
+  if let Some(env_file) = &args.env_file {
+    args.env_file = Some(env::current_dir()?.join(env_file).display().to_string());
+  }
+  if let Some(git_ref) = args.since.clone() {
+    return since::build_changed(args, &git_ref);
+  }
+  build_package(args)?;
+  Ok(())
+}
+
+/// 构建并返回当前构建的 package 信息 打印配置时不会构建
+pub fn build_package(args: crate::BuildArgs) -> anyhow::Result<Option<Package>> {
   let mut current_args = args.clone();
   let mut ctx = Context::default();
 
   prepare::prepare(&mut current_args, &mut ctx)?;
 
+  if current_args.print_config {
+    config::print_config(&current_args, &ctx);
+    return Ok(None);
+  }
+
   let build_arch = current_args.arch.unwrap_or(vec![
     crate::Arch::ARM64,
     crate::Arch::ARM32,
@@ -62,7 +121,7 @@ pub fn build(args: crate::BuildArgs) -> anyhow::Result<()> {
 
   let cargo_args = current_args.cargo_args.unwrap_or_default();
 
-  [Arch::ARM64, Arch::ARM32, Arch::X86_64]
+  let mut targets = [Arch::ARM64, Arch::ARM32, Arch::X86_64]
     .iter()
     .filter_map(|&i| {
       if build_arch.contains(&i) {
@@ -70,17 +129,79 @@ pub fn build(args: crate::BuildArgs) -> anyhow::Result<()> {
       }
       None
     })
+    .collect::<Vec<_>>();
+  // the type defs are the same for all targets
+  if current_args.print_exports.is_some() {
+    targets.truncate(1);
+  }
+
+  let progress = Progress::new(targets.len() as u64);
+  let mut diagnostics = diagnostics::Diagnostics::default();
+  let ret = targets
+    .iter()
     .map(|arch| -> anyhow::Result<()> {
       let tmp_file_env = env::var("TYPE_DEF_TMP_PATH");
       if let Ok(tmp_file) = tmp_file_env {
         check_and_clean_file_or_dir!(PathBuf::from(&tmp_file));
       }
 
-      run::build(&cargo_args, &ctx, &arch)?;
+      progress.set_message(format!("{}: building", arch.rust_target()));
+      run::build(&cargo_args, &ctx, arch, &progress, &mut diagnostics)?;
+      progress.inc();
       Ok(())
     })
-    .collect::<anyhow::Result<Vec<_>>>()?;
+    .collect::<anyhow::Result<Vec<_>>>();
+  progress.finish();
+  if let Some(path) = &ctx.emit_diagnostics {
+    diagnostics.write(path)?;
+  }
+  ret?;
 
-  ts::generate_d_ts_file(&ctx)?;
-  Ok(())
+  if let Some(format) = current_args.print_exports {
+    ts::print_exports(&ctx, format)?;
+    return Ok(ctx.package);
+  }
+
+  // the type def file is still generated by macro, just ignore it
+  let dts = if current_args.no_dts.unwrap_or_default() {
+    None
+  } else {
+    ts::generate_d_ts_file(&ctx)?
+  };
+  if let Some(path) = &ctx.emit_metadata {
+    cache::emit_metadata(&ctx, path, dts.as_deref())?;
+  }
+  cache::emit_metadata(&ctx, &ctx.cache_dir.join(METADATA), dts.as_deref())?;
+  Ok(ctx.package)
+}
+
+#[cfg(test)]
+mod test {
+  use super::Context;
+  use crate::util::{test_dir, PROBED_NDKS};
+  use std::env;
+  use std::fs;
+
+  #[test]
+  fn test_ndk_toolchain_probe_once() {
+    let temp = test_dir();
+    let ndk = temp.path().to_path_buf();
+    let bin = ndk.join("native/llvm/bin");
+    fs::create_dir_all(&bin).unwrap();
+    for t in ["clang", "clang++", "llvm-ar"] {
+      fs::write(bin.join(format!("{}{}", t, env::consts::EXE_SUFFIX)), "").unwrap();
+    }
+
+    let ctx = Context {
+      ndk: ndk.clone(),
+      ..Default::default()
+    };
+    // one for each target
+    for _ in 0..3 {
+      let toolchain = ctx.ndk_toolchain().unwrap();
+      assert!(toolchain.cc.ends_with("native/llvm/bin/clang"));
+    }
+    let probed = PROBED_NDKS.lock().unwrap().clone();
+    assert_eq!(probed.iter().filter(|p| **p == ndk).count(), 1);
+  }
 }
@@ -1,17 +1,29 @@
-use crate::build::{Context, Template};
+use crate::build::cache::intermediate_file_name;
+use crate::build::manifest::merge_manifest_metadata;
+use crate::build::{check_bundle_library, resolve_profile_dir, Context, Template, CDYLIB_SNIPPET};
 use crate::create_dist_dir;
+use crate::util::{
+  check_output_path, detect_ndk_version, load_env_file, read_ndk_lock, register_cleanup,
+  resolve_cache_dir, resolve_ndk, resolve_sysroot, write_ndk_lock, Arch, NDK_LOCK,
+};
 use anyhow::Error;
-use cargo_metadata::MetadataCommand;
-use sha2::{Digest, Sha256};
+use cargo_metadata::{MetadataCommand, Package};
+use owo_colors::OwoColorize;
 use std::env;
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 /// 构建前初始化工作，包括获取当前运行环境等。  
 pub fn prepare(args: &mut crate::BuildArgs, ctx: &mut Context) -> anyhow::Result<()> {
   ctx.pwd = env::current_dir()?;
 
-  // set copy_static variable
-  ctx.copy_static = args.copy_static;
+  // 在读取环境变量之前加载 env file，只打印变量名
+  if let Some(env_file) = &args.env_file {
+    ctx.env_keys = load_env_file(&ctx.pwd.join(env_file))?;
+    if args.verbose && !ctx.env_keys.is_empty() {
+      eprintln!("Load {} from {}.", ctx.env_keys.join(", "), env_file);
+    }
+  }
 
   // 判断当前构建环境以及获取metadata信息
   let cargo_file = ctx.pwd.join("./Cargo.toml");
@@ -36,6 +48,13 @@ pub fn prepare(args: &mut crate::BuildArgs, ctx: &mut Context) -> anyhow::Result
     })
     .ok_or(Error::msg("Try to get package meta-info failed."))?;
 
+  // 未通过参数设置的配置项 使用 [package.metadata.ohrs] 中的值
+  if args.manifest_metadata {
+    ctx.manifest_keys = merge_manifest_metadata(args, &pkg.metadata)?;
+  }
+
+  check_cdylib_target(pkg, args.strict.unwrap_or_default())?;
+
   let toml_content: Option<Template> = pkg
     .metadata
     .get("template")
@@ -45,9 +64,74 @@ pub fn prepare(args: &mut crate::BuildArgs, ctx: &mut Context) -> anyhow::Result
 
   ctx.package = Some((*pkg).clone());
   ctx.cargo_build_target_dir = Some(metadata.target_directory);
+  let workspace_root = metadata.workspace_root;
+
+  // set copy_static variable
+  ctx.copy_static = args.copy_static.unwrap_or_default();
+
+  ctx.dts_options.flatten_singletons = args.flatten_singletons.unwrap_or_default();
+  ctx.dts_options.strict = args.strict.unwrap_or_default();
+  ctx.dts_options.dual = args.dual.unwrap_or_default();
+  ctx.dts_options.split = args.split_dts.unwrap_or_default();
+  ctx.dts_options.annotate_throws = args.annotate_throws.unwrap_or_default();
+  ctx.dts_options.emit_map = args.emit_dts_map;
+  ctx.dts_options.no_struct_first = args.no_struct_first.unwrap_or_default();
+  ctx.dts_options.lint_ignore = args.lint_ignore.unwrap_or_default();
+  ctx.dts_options.optional_style = args.optional_style.unwrap_or_default();
+  ctx.dts_options.namespace_keyword = args.namespace_keyword.unwrap_or_default();
+  if args.export_equals.is_some() && (ctx.dts_options.dual || ctx.dts_options.split) {
+    return Err(Error::msg(
+      "--export-equals can't be used with --dual or --split-dts, they emit ESM declarations.",
+    ));
+  }
+  if args.js_out.is_some() && args.export_equals.is_none() {
+    return Err(Error::msg(
+      "--js-out can only be used with --export-equals, no JS entry is emitted without it.",
+    ));
+  }
+  ctx.dts_options.export_equals = args.export_equals.clone();
+  ctx.dts_options.polyfills = args.polyfill.clone();
+  ctx.dts_options.external_types = args.external_type.clone();
+  ctx.dts_options.closed_world = args.dts_closed_world.unwrap_or_default();
+  ctx.dts_options.transform = args.dts_transform.clone();
+  ctx.dts_options.formatter = args.dts_formatter.clone();
+  ctx.dts_options.namespace_order = args
+    .namespace_order
+    .as_deref()
+    .unwrap_or_default()
+    .split(',')
+    .map(|n| n.trim().to_string())
+    .filter(|n| !n.is_empty())
+    .collect();
+
+  // check extra shared libraries
+  ctx.bundle_libs = args
+    .bundle_lib
+    .iter()
+    .map(|l| {
+      let p = ctx.pwd.join(l);
+      if !p.is_file() {
+        return Err(Error::msg(format!("Bundle library {} is not existed.", l)));
+      }
+      Ok(p)
+    })
+    .collect::<anyhow::Result<Vec<_>>>()?;
+  check_bundle_library(
+    &ctx.bundle_libs,
+    &args
+      .arch
+      .clone()
+      .unwrap_or(vec![Arch::ARM64, Arch::ARM32, Arch::X86_64]),
+  );
 
   ctx.init_args = vec!["build"];
 
+  check_profile_args(
+    args.release,
+    args.profile.as_deref(),
+    args.cargo_args.as_deref().unwrap_or_default(),
+  )?;
+
   if let Some(cargo_args) = &args.cargo_args {
     // release mode and --release arg should be ignored
     if args.release && !cargo_args.contains(&String::from("--release")) {
@@ -55,19 +139,65 @@ pub fn prepare(args: &mut crate::BuildArgs, ctx: &mut Context) -> anyhow::Result
     }
   }
 
+  // pass profile to cargo, and the profile dir will be resolved from cargo_args
+  if let Some(profile) = &args.profile {
+    args
+      .cargo_args
+      .get_or_insert_with(Vec::new)
+      .extend([String::from("--profile"), profile.clone()]);
+  }
+  check_panic_strategy(
+    ctx,
+    workspace_root.as_std_path(),
+    args.cargo_args.as_deref().unwrap_or_default(),
+  );
+
+  ctx.dist = ctx.pwd.join(args.dist());
+  ctx.dts_options.out = args.dts_out.as_ref().map(|p| ctx.pwd.join(p));
+  ctx.dts_options.js_out = args.js_out.as_ref().map(|p| ctx.pwd.join(p));
+  ctx.dts_options.types_package = args.types_package.as_ref().map(|p| ctx.pwd.join(p));
+  ctx.emit_metadata = args.emit_metadata.as_ref().map(|p| ctx.pwd.join(p));
+  ctx.emit_diagnostics = args.emit_diagnostics.as_ref().map(|p| ctx.pwd.join(p));
+  ctx.cache_dir = resolve_cache_dir(&ctx.pwd, args.cache_dir.as_deref());
+  // 产物路径不能超出 workspace 根目录
+  if !args.allow_outside {
+    let allowed = args
+      .allow_dir
+      .iter()
+      .map(|d| ctx.pwd.join(d))
+      .collect::<Vec<_>>();
+    let outputs = [
+      ("--dist", Some(&ctx.dist)),
+      ("--dts-out", ctx.dts_options.out.as_ref()),
+      ("--js-out", ctx.dts_options.js_out.as_ref()),
+      ("--types-package", ctx.dts_options.types_package.as_ref()),
+      ("--emit-metadata", ctx.emit_metadata.as_ref()),
+      ("--emit-diagnostics", ctx.emit_diagnostics.as_ref()),
+      ("--cache-dir", Some(&ctx.cache_dir)),
+    ];
+    for (flag, path) in outputs {
+      if let Some(path) = path {
+        check_output_path(flag, path, workspace_root.as_std_path(), &allowed)?;
+      }
+    }
+  }
+
+  ctx.skip_artifacts = args.print_exports.is_some();
   // 创建目标文件夹
-  ctx.dist = ctx.pwd.join(&args.dist);
-  create_dist_dir!(ctx.dist.clone());
+  if !args.print_config && !ctx.skip_artifacts {
+    create_dist_dir!(ctx.dist.clone());
+  }
+  ctx.verbose = args.verbose;
 
   // 设置生成.d.ts tmp file路径的环境变量
   let tmp_dir = env::temp_dir();
 
-  let mut hasher = Sha256::new();
-  hasher.update(&pkg.manifest_path.as_str());
-  let hash_result = hasher.finalize();
-  let hash_hex = format!("{:x}", hash_result);
-  let short_hash = &hash_hex[..8];
-  let file_name = format!("{}-{}.napi_type_def.tmp", &pkg.name, short_hash);
+  let file_name = intermediate_file_name(
+    pkg,
+    ctx,
+    args.cargo_args.as_deref().unwrap_or_default(),
+    args.content_hash,
+  );
 
   // 拼接完整的文件路径
   let file_path = PathBuf::from(tmp_dir).join(file_name);
@@ -77,15 +207,312 @@ pub fn prepare(args: &mut crate::BuildArgs, ctx: &mut Context) -> anyhow::Result
       .to_str()
       .ok_or(Error::msg("Try to set TYPE_DEF_TMP_PATH failed."))?,
   );
+  // 取消构建时删除未写完的中间文件
+  register_cleanup(&file_path);
   ctx.tmp_ts_file_path = file_path;
 
-  // 获取 ndk 环境变量配置
-  let ndk = env::var("OHOS_NDK_HOME").map_err(|_| {
-    Error::msg(
-      "Failed to get the OHOS_NDK_HOME environment variable, please make sure you have set it.",
-    )
-  })?;
-  ctx.ndk = ndk;
+  // 获取 ndk 配置 优先使用 --ndk 参数
+  ctx.ndk = resolve_ndk(args.ndk.as_deref())?;
+  // sysroot 优先使用 --sysroot 参数，其次是 OHOS_SYSROOT 环境变量
+  ctx.sysroot = resolve_sysroot(args.sysroot.as_deref(), &ctx.ndk)?;
 
+  ctx.ndk_version = detect_ndk_version(&ctx.ndk);
+  check_ndk_lock(args, ctx)?;
+
+  Ok(())
+}
+
+/// update or verify the ndk version with `ndk-lock.json` of cache dir
+fn check_ndk_lock(args: &crate::BuildArgs, ctx: &Context) -> anyhow::Result<()> {
+  if !args.update_ndk_lock && !args.locked_ndk {
+    return Ok(());
+  }
+  let version = ctx.ndk_version.as_ref().ok_or(Error::msg(format!(
+    "Can't detect the version of ndk {}, please check native/oh-uni-package.json.",
+    ctx.ndk.display()
+  )))?;
+
+  if args.update_ndk_lock {
+    write_ndk_lock(&ctx.cache_dir, version)?;
+    println!(
+      "Update {} with ndk version {}.",
+      ctx.cache_dir.join(NDK_LOCK).display(),
+      version.green()
+    );
+    return Ok(());
+  }
+
+  let locked = read_ndk_lock(&ctx.cache_dir, &ctx.pwd)?;
+  if &locked != version {
+    return Err(Error::msg(format!(
+      "The ndk version {} doesn't match the locked version {}, please switch the ndk or run build with --update-ndk-lock.",
+      version, locked
+    )));
+  }
   Ok(())
 }
+
+/// whether the profile is built with `panic = "abort"`, the inherited profiles are followed.
+/// `tables` are the parsed cargo configs and Cargo.toml, the earlier one has higher priority.
+fn is_panic_abort(tables: &[toml::Table], profile: &str) -> bool {
+  let value = |name: &str, key: &str| {
+    tables.iter().find_map(|t| {
+      t.get("profile")?
+        .get(name)?
+        .get(key)?
+        .as_str()
+        .map(String::from)
+    })
+  };
+
+  let mut name = profile.to_string();
+  // the depth is limited in case of the cyclic inherits
+  for _ in 0..8 {
+    if let Some(panic) = value(&name, "panic") {
+      return panic == "abort";
+    }
+    name = match (value(&name, "inherits"), name.as_str()) {
+      (Some(i), _) => i,
+      (None, "bench") => String::from("release"),
+      (None, "test") => String::from("dev"),
+      _ => return false,
+    };
+  }
+  false
+}
+
+/// the cargo config files like cargo looks them up, the ancestors of cwd first and then `$CARGO_HOME`
+fn cargo_config_paths(cwd: &Path, cargo_home: Option<PathBuf>) -> Vec<PathBuf> {
+  let mut dirs = cwd
+    .ancestors()
+    .map(|dir| dir.join(".cargo"))
+    .collect::<Vec<_>>();
+  if let Some(home) = cargo_home {
+    if !dirs.contains(&home) {
+      dirs.push(home);
+    }
+  }
+  // cargo uses `config` if both of them exist
+  dirs
+    .iter()
+    .filter_map(|dir| {
+      [dir.join("config"), dir.join("config.toml")]
+        .into_iter()
+        .find(|p| p.is_file())
+    })
+    .collect()
+}
+
+/// the cargo configs and Cargo.toml of the workspace, ordered by the priority of cargo
+fn profile_tables(workspace_root: &Path) -> Vec<toml::Table> {
+  let cwd = env::current_dir().unwrap_or_else(|_| workspace_root.to_path_buf());
+  let cargo_home = env::var_os("CARGO_HOME")
+    .map(PathBuf::from)
+    .or_else(|| env::var_os("HOME").map(|h| PathBuf::from(h).join(".cargo")));
+  cargo_config_paths(&cwd, cargo_home)
+    .into_iter()
+    .chain([workspace_root.join("Cargo.toml")])
+    .filter_map(|p| fs::read_to_string(p).ok())
+    .filter_map(|content| content.parse::<toml::Table>().ok())
+    .collect()
+}
+
+/// panic can't be converted to JavaScript error with `panic = "abort"`, warn it before building
+fn check_panic_strategy(ctx: &Context, workspace_root: &Path, cargo_args: &[String]) {
+  let profile = match resolve_profile_dir(&ctx.init_args, cargo_args).as_str() {
+    "debug" => String::from("dev"),
+    p => p.to_string(),
+  };
+  let env_name = format!(
+    "CARGO_PROFILE_{}_PANIC",
+    profile.to_uppercase().replace('-', "_")
+  );
+  let from_env = env::var(env_name).is_ok_and(|v| v == "abort")
+    || ["RUSTFLAGS", "CARGO_ENCODED_RUSTFLAGS"]
+      .iter()
+      .any(|k| env::var(k).is_ok_and(|v| v.replace(' ', "").contains("panic=abort")));
+  let from_args = cargo_args
+    .iter()
+    .any(|a| a.contains("panic=\"abort\"") || a.contains("panic=abort"));
+  let from_profile = is_panic_abort(&profile_tables(workspace_root), &profile);
+  if from_env || from_args || from_profile {
+    eprintln!(
+      "{}: panic = \"abort\" is set for profile {}, a panic in Rust code will abort the whole app instead of throwing a JavaScript error, and #[napi(catch_unwind)] has no effect.",
+      "Warning".bold().yellow(),
+      profile
+    );
+  }
+}
+
+/// `--release` is the same as `--profile release`, they can't be used together
+fn check_profile_args(
+  release: bool,
+  profile: Option<&str>,
+  cargo_args: &[String],
+) -> anyhow::Result<()> {
+  let mut flags = Vec::new();
+  if release {
+    flags.push(String::from("--release"));
+  }
+  if let Some(p) = profile {
+    flags.push(format!("--profile {}", p));
+  }
+  let mut iter = cargo_args.iter();
+  while let Some(arg) = iter.next() {
+    if arg == "--release" {
+      // --release in both ohrs and cargo args is allowed
+      if !release {
+        flags.push(String::from("--release"));
+      }
+    } else if arg == "--profile" {
+      flags.push(format!(
+        "--profile {}",
+        iter.next().map(|s| s.as_str()).unwrap_or_default()
+      ));
+    } else if arg.starts_with("--profile=") {
+      flags.push(arg.clone());
+    }
+  }
+
+  if flags.len() > 1 {
+    return Err(Error::msg(format!(
+      "Conflicting profile flags: {}. --release is the same as --profile release, please only use one of them.",
+      flags.join(", ")
+    )));
+  }
+  Ok(())
+}
+
+/// OHOS native module must be a `cdylib`, check it before cargo build.
+fn check_cdylib_target(pkg: &Package, strict: bool) -> anyhow::Result<()> {
+  let has_cdylib = pkg
+    .targets
+    .iter()
+    .any(|t| t.crate_types.iter().any(|c| c == "cdylib"));
+  if has_cdylib {
+    return Ok(());
+  }
+
+  let info = format!(
+    "No cdylib target found in {}, OHOS native module must be built as cdylib.
+  Please add the following to your Cargo.toml:
+
+  {}",
+    &pkg.manifest_path, CDYLIB_SNIPPET
+  );
+  if strict {
+    return Err(Error::msg(info));
+  }
+  eprintln!("{}: {}", "Warning".bold().yellow(), info);
+  Ok(())
+}
+
+#[cfg(test)]
+mod test {
+  use super::{cargo_config_paths, check_profile_args, is_panic_abort};
+  use crate::util::test_dir;
+  use std::fs;
+
+  fn args(a: &[&str]) -> Vec<String> {
+    a.iter().map(|s| s.to_string()).collect()
+  }
+
+  #[test]
+  fn test_check_profile_args() {
+    assert!(check_profile_args(true, None, &[]).is_ok());
+    assert!(check_profile_args(false, Some("bench"), &[]).is_ok());
+    assert!(check_profile_args(true, None, &args(&["--release"])).is_ok());
+    assert!(check_profile_args(false, None, &args(&["--profile", "bench"])).is_ok());
+
+    assert!(check_profile_args(true, Some("release"), &[]).is_err());
+    assert!(check_profile_args(false, Some("release"), &args(&["--release"])).is_err());
+    assert!(check_profile_args(true, None, &args(&["--profile=bench"])).is_err());
+    assert!(
+      check_profile_args(false, None, &args(&["--release", "--profile", "release"])).is_err()
+    );
+  }
+
+  #[test]
+  fn test_is_panic_abort() {
+    let parse = |s: &str| s.parse::<toml::Table>().unwrap();
+    let manifest = parse(
+      r#"
+[package]
+name = "demo"
+
+[profile.release] # comment
+panic = "abort"
+lto = true
+
+[profile.release.package.foo]
+panic = "unwind"
+
+[profile.size]
+inherits = "release"
+
+[profile.dev]
+opt-level = 1
+"#,
+    );
+    let tables = [manifest.clone()];
+    assert!(is_panic_abort(&tables, "release"));
+    assert!(is_panic_abort(&tables, "size"));
+    assert!(is_panic_abort(&tables, "bench"));
+    assert!(!is_panic_abort(&tables, "dev"));
+    assert!(!is_panic_abort(&tables, "test"));
+    assert!(!is_panic_abort(
+      &[parse(
+        "[profile.a]\ninherits = \"b\"\n[profile.b]\ninherits = \"a\"\n"
+      )],
+      "a"
+    ));
+
+    // dotted keys
+    let dotted = parse("profile.dev.panic = \"abort\"\nprofile.local.inherits = \"dev\"\n");
+    assert!(is_panic_abort(&[dotted.clone()], "test"));
+    assert!(is_panic_abort(&[dotted], "local"));
+    assert!(is_panic_abort(
+      &[parse("[profile]\nrelease.panic = \"abort\"\n")],
+      "bench"
+    ));
+
+    // the cargo config overrides Cargo.toml
+    let config = parse("[profile.release]\npanic = 'unwind'\n[profile.dev]\npanic = 'abort'\n");
+    let tables = [config, manifest];
+    assert!(!is_panic_abort(&tables, "release"));
+    assert!(!is_panic_abort(&tables, "size"));
+    assert!(is_panic_abort(&tables, "dev"));
+  }
+
+  #[test]
+  fn test_cargo_config_paths() {
+    let temp = test_dir();
+    let root = temp.path().to_path_buf();
+    let cwd = root.join("ws").join("sub");
+    let home = root.join("home");
+    for dir in [
+      cwd.join(".cargo"),
+      root.join("ws").join(".cargo"),
+      home.clone(),
+    ] {
+      fs::create_dir_all(dir).unwrap();
+    }
+    fs::write(cwd.join(".cargo").join("config.toml"), "").unwrap();
+    fs::write(root.join("ws").join(".cargo").join("config"), "").unwrap();
+    fs::write(root.join("ws").join(".cargo").join("config.toml"), "").unwrap();
+    fs::write(home.join("config.toml"), "").unwrap();
+
+    let paths = cargo_config_paths(&cwd, Some(home.clone()));
+    assert_eq!(
+      paths
+        .into_iter()
+        .filter(|p| p.starts_with(&root))
+        .collect::<Vec<_>>(),
+      [
+        cwd.join(".cargo").join("config.toml"),
+        root.join("ws").join(".cargo").join("config"),
+        home.join("config.toml"),
+      ]
+    );
+  }
+}
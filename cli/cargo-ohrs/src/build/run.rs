@@ -1,117 +1,78 @@
 use crate::build::Context;
-use crate::util::Arch;
+use crate::util::{Arch, Progress};
 use crate::{check_and_clean_file_or_dir, create_dist_dir, move_file};
 use anyhow::Error;
 use cargo_metadata::Message;
-use std::collections::HashMap;
-use std::env;
-use std::io::BufReader;
+use std::io::{BufRead, BufReader, IsTerminal};
 use std::path::PathBuf;
-use std::process::{exit, Command, Stdio};
-
-use super::artifact::{resolve_artifact_library, resolve_dependence_library};
-
-pub fn build(cargo_args: &Vec<String>, ctx: &Context, arch: &Arch) -> anyhow::Result<()> {
-  let linker_name = format!("CARGO_TARGET_{}_LINKER", &arch.rust_link_target());
-  let ran_path = format!("{}/native/llvm/bin/llvm-ranlib", &ctx.ndk);
-  let ar_path = format!("{}/native/llvm/bin/llvm-ar", &ctx.ndk);
-  let cc_path = format!("{}/native/llvm/bin/clang", &ctx.ndk);
-  let cxx_path = format!("{}/native/llvm/bin/clang++", &ctx.ndk);
-  let as_path = format!("{}/native/llvm/bin/llvm-as", &ctx.ndk);
-  let ld_path = format!("{}/native/llvm/bin/ld.lld", &ctx.ndk);
-  let strip_path = format!("{}/native/llvm/bin/llvm-strip", &ctx.ndk);
-  let obj_dump_path = format!("{}/native/llvm/bin/llvm-objdump", &ctx.ndk);
-  let obj_copy_path = format!("{}/native/llvm/bin/llvm-objcopy", &ctx.ndk);
-  let nm_path = format!("{}/native/llvm/bin/llvm-nm", &ctx.ndk);
-  let bin_path = format!("{}/native/llvm/bin", &ctx.ndk);
-  // for bindgen, you may need to change to builtin clang or clang++ etc. You can set LIBCLANG_PATH and CLANG_PATH
-  let lib_path = format!("{}/native/llvm/lib", &ctx.ndk);
-  let std_lib = format!("CXXSTDLIB_{}", &arch.rust_link_target());
-  let std_lib_type = String::from("c++");
-
-  let mut base_flags = format!(
-    "-target {} --sysroot={}/native/sysroot -D__MUSL__",
-    &arch.c_target(),
-    &ctx.ndk
-  );
-
-  let mut path = env::var("PATH").unwrap_or(String::default());
-  // for windows, path need to use ; as split symbol
-  // for unix, should use :
-  #[cfg(target_os = "windows")]
-  {
-    path = format!("{};{}", &bin_path, &path);
-  }
-  #[cfg(not(target_os = "windows"))]
-  {
-    path = format!("{}:{}", &bin_path, &path);
-  }
-
-  let args =
-    env::var("CARGO_RUSTFLAGS").unwrap_or(env::var("CARGO_ENCODED_RUSTFLAGS").unwrap_or_default());
-
-  if arch.to_arch() == "armeabi-v7a" {
-    base_flags = format!(
-      "{} -march=armv7-a -mfloat-abi=softfp -mtune=generic-armv7-a -mthumb",
-      base_flags
-    );
-  }
-
-  let tmp_path_str = ctx.tmp_ts_file_path.to_str().ok_or(Error::msg(
-    "Try to set TYPE_DEF_TMP_PATH before build failed.",
-  ))?;
-  let tmp_path = String::from(tmp_path_str);
-
-  // for some package deps on atomic
-  let builtins = String::from("clang_rt.builtins");
-
-  let mut rust_flags = format!("-Clink-args={}", &base_flags);
-
-  if !args.is_empty() {
-    rust_flags = format!("{} {}", &rust_flags, &args)
-  }
-
-  let prepare_env = HashMap::from([
-    (linker_name.as_str(), &cc_path),
-    ("LIBCLANG_PATH", &lib_path),
-    ("CLANG_PATH", &cxx_path),
-    (std_lib.as_str(), &std_lib_type),
-    ("TARGET_CC", &cc_path),
-    ("TARGET_CXX", &cxx_path),
-    ("TARGET_RANLIB", &ran_path),
-    ("TARGET_AR", &ar_path),
-    ("TARGET_AS", &as_path),
-    ("TARGET_LD", &ld_path),
-    ("TARGET_STRIP", &strip_path),
-    ("TARGET_OBJDUMP", &obj_dump_path),
-    ("TARGET_OBJCOPY", &obj_copy_path),
-    ("TARGET_NM", &nm_path),
-    ("CARGO_ENCODED_RUSTFLAGS", &rust_flags),
-    ("PATH", &path),
-    ("TYPE_DEF_TMP_PATH", &tmp_path),
-    // support opencv-rust
-    ("OPENCV_CLANG_ARGS", &base_flags),
-    ("DEP_ATOMIC", &builtins),
-  ]);
+use std::process::{Command, Stdio};
+
+use super::artifact::{
+  check_cdylib_library, check_single_cdylib, resolve_artifact_library, resolve_bundle_library,
+  resolve_dependence_library,
+};
+use super::diagnostics::Diagnostics;
+
+pub fn build(
+  cargo_args: &Vec<String>,
+  ctx: &Context,
+  arch: &Arch,
+  progress: &Progress,
+  diagnostics: &mut Diagnostics,
+) -> anyhow::Result<()> {
+  let toolchain = ctx.ndk_toolchain()?;
+
+  let mut prepare_env = toolchain.cargo_envs(arch, &ctx.sysroot)?;
+  prepare_env.push((
+    String::from("TYPE_DEF_TMP_PATH"),
+    ctx.tmp_ts_file_path.clone().into(),
+  ));
 
   let mut args = ctx.init_args.clone();
   args.extend([
     "--target",
     &arch.rust_target(),
-    "--message-format=json-render-diagnostics",
+    // diagnostics are collected and only printed with --verbose
+    if std::io::stderr().is_terminal() {
+      "--message-format=json-diagnostic-rendered-ansi"
+    } else {
+      "--message-format=json"
+    },
   ]);
 
   // respect cli extra args
   args.extend(cargo_args.iter().map(|s| s.as_str()));
 
   let mut artifact_files: Vec<PathBuf> = Vec::new();
+  // cdylib targets of current package, such as lib and the examples built with `--examples`
+  let mut cdylib_targets: Vec<String> = Vec::new();
 
   let mut child = Command::new("cargo")
     .args(args)
-    .envs(&prepare_env)
+    .envs(prepare_env)
     .stdout(Stdio::piped())
+    .stderr(if progress.is_visible() {
+      Stdio::piped()
+    } else {
+      Stdio::inherit()
+    })
     .spawn()?;
 
+  // print cargo output above the progress, and show the compiling crate in it
+  let stderr_thread = child.stderr.take().map(|stderr| {
+    let progress = progress.clone();
+    let target = arch.rust_target();
+    std::thread::spawn(move || {
+      BufReader::new(stderr)
+        .lines()
+        .map_while(Result::ok)
+        .for_each(|line| match line.trim_start().strip_prefix("Compiling ") {
+          Some(krate) => progress.set_message(format!("{}: compiling {}", target, krate)),
+          None => progress.println(line),
+        });
+    })
+  });
+
   if let Some(ref mut stdout) = child.stdout {
     let reader = BufReader::new(stdout);
 
@@ -120,21 +81,42 @@ pub fn build(cargo_args: &Vec<String>, ctx: &Context, arch: &Arch) -> anyhow::Re
         Ok(m) => {
           match m {
             Message::CompilerMessage(msg) => {
-              println!("{:?}", msg);
+              diagnostics.push(arch.rust_target(), &msg.message);
+              if ctx.verbose {
+                if let Some(rendered) = &msg.message.rendered {
+                  progress.println(rendered.trim_end());
+                }
+              }
             }
             // get final compiled library
             Message::CompilerArtifact(artifact) => {
+              let is_cdylib = artifact.target.crate_types.iter().any(|c| c == "cdylib");
+              if is_cdylib
+                && ctx.package.as_ref().map(|p| &p.id) == Some(&artifact.package_id)
+                && !cdylib_targets.contains(&artifact.target.name)
+              {
+                cdylib_targets.push(artifact.target.name.clone());
+              }
               if let Some(p) = resolve_artifact_library(artifact) {
                 artifact_files.extend(p);
               }
             }
             Message::BuildScriptExecuted(script) => {
-              if let Some(lib) = resolve_dependence_library(script, (&ctx.ndk).clone()) {
+              if let Some(lib) = resolve_dependence_library(script, &ctx.sysroot) {
                 artifact_files.extend(lib);
               }
             }
             Message::BuildFinished(finished) => match finished.success {
               true => {
+                print_summary(ctx, diagnostics, arch);
+                if let Some(pkg) = &ctx.package {
+                  check_single_cdylib(pkg, &cdylib_targets)?;
+                }
+                check_cdylib_library(ctx, arch, cargo_args)?;
+                if ctx.skip_artifacts {
+                  continue;
+                }
+
                 let bin_dir = &ctx.dist.join(&arch.to_arch());
                 check_and_clean_file_or_dir!(bin_dir);
                 create_dist_dir!(bin_dir);
@@ -158,9 +140,25 @@ pub fn build(cargo_args: &Vec<String>, ctx: &Context, arch: &Arch) -> anyhow::Re
                       let dist = bin_dir.join(f);
                       move_file!(i, dist);
                     }
-                  })
+                  });
+
+                resolve_bundle_library(&ctx.bundle_libs, arch)
+                  .iter()
+                  .for_each(|i| {
+                    if let Some(f) = i.file_name() {
+                      let dist = bin_dir.join(f);
+                      move_file!(i, dist);
+                    }
+                  });
+              }
+              false => {
+                if let Some(t) = stderr_thread {
+                  let _ = t.join();
+                }
+                progress.finish();
+                print_summary(ctx, diagnostics, arch);
+                return Err(Error::msg(format!("Build {} failed.", arch.rust_target())));
               }
-              false => exit(-1),
             },
             _ => (), // Unknown message
           }
@@ -171,5 +169,17 @@ pub fn build(cargo_args: &Vec<String>, ctx: &Context, arch: &Arch) -> anyhow::Re
       }
     }
   }
+  if let Some(t) = stderr_thread {
+    let _ = t.join();
+  }
   Ok(())
 }
+
+fn print_summary(ctx: &Context, diagnostics: &Diagnostics, arch: &Arch) {
+  if let Some(summary) = diagnostics.summary(arch.rust_target()) {
+    eprintln!("{}", summary);
+    if !ctx.verbose {
+      eprintln!("Run build with --verbose to see the full diagnostics.");
+    }
+  }
+}
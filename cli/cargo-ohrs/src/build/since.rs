@@ -0,0 +1,257 @@
+use anyhow::Error;
+use cargo_metadata::{MetadataCommand, Package};
+use owo_colors::OwoColorize;
+use std::collections::HashSet;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// the files which may change the build result of all members
+const WORKSPACE_FILES: &[&str] = &[
+  "Cargo.toml",
+  "Cargo.lock",
+  ".cargo/config.toml",
+  ".cargo/config",
+];
+
+fn git(pwd: &Path, args: &[&str]) -> anyhow::Result<String> {
+  let output = Command::new("git").args(args).current_dir(pwd).output()?;
+  if !output.status.success() {
+    return Err(Error::msg(format!(
+      "git {} failed: {}",
+      args.join(" "),
+      String::from_utf8_lossy(&output.stderr).trim()
+    )));
+  }
+  Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// absolute paths of the files changed since the ref, the uncommitted and untracked files are included
+fn changed_files(pwd: &Path, git_ref: &str) -> anyhow::Result<Vec<PathBuf>> {
+  let root = PathBuf::from(git(pwd, &["rev-parse", "--show-toplevel"])?.trim());
+  // ls-files prints the paths relative to the current dir, so run both in the root
+  let diff = git(&root, &["diff", "--name-only", git_ref, "--"])?;
+  let untracked = git(&root, &["ls-files", "--others", "--exclude-standard"])?;
+  Ok(
+    diff
+      .lines()
+      .chain(untracked.lines().map(|l| l.trim()))
+      .filter(|l| !l.is_empty())
+      .map(|l| root.join(l))
+      .collect(),
+  )
+}
+
+fn package_dir(pkg: &Package) -> PathBuf {
+  pkg
+    .manifest_path
+    .parent()
+    .map(|p| p.as_std_path().to_path_buf())
+    .unwrap_or_default()
+}
+
+/// names of the members which are changed, or depend on the changed members by path
+fn affected_members(
+  workspace_root: &Path,
+  members: &[Package],
+  files: &[PathBuf],
+) -> HashSet<String> {
+  if files
+    .iter()
+    .any(|f| WORKSPACE_FILES.iter().any(|w| f == &workspace_root.join(w)))
+  {
+    return members.iter().map(|m| m.name.clone()).collect();
+  }
+
+  // the nested member has the longer dir, so the file belongs to the deepest one
+  let mut affected = HashSet::new();
+  for file in files {
+    if let Some(member) = members
+      .iter()
+      .filter(|m| file.starts_with(package_dir(m)))
+      .max_by_key(|m| package_dir(m).components().count())
+    {
+      affected.insert(member.name.clone());
+    }
+  }
+
+  loop {
+    let dependents = members
+      .iter()
+      .filter(|m| !affected.contains(&m.name))
+      .filter(|m| {
+        m.dependencies
+          .iter()
+          .any(|d| d.path.is_some() && affected.contains(&d.name))
+      })
+      .map(|m| m.name.clone())
+      .collect::<Vec<_>>();
+    if dependents.is_empty() {
+      break;
+    }
+    affected.extend(dependents);
+  }
+  affected
+}
+
+/// build the napi crates changed since the git ref, and skip the others
+/// all cdylib members are the candidates in workspace root, otherwise only the current crate
+pub fn build_changed(args: crate::BuildArgs, git_ref: &str) -> anyhow::Result<()> {
+  let pwd = env::current_dir()?;
+  let cargo_file = pwd.join("Cargo.toml");
+  let metadata = MetadataCommand::new()
+    .no_deps()
+    .manifest_path(&cargo_file)
+    .exec()?;
+  let members = metadata.workspace_packages();
+  let members = members.into_iter().cloned().collect::<Vec<_>>();
+
+  let candidates = match members.iter().find(|p| p.manifest_path == cargo_file) {
+    Some(pkg) => vec![pkg.clone()],
+    None => members
+      .iter()
+      .filter(|p| {
+        p.targets
+          .iter()
+          .any(|t| t.crate_types.iter().any(|c| c == "cdylib"))
+      })
+      .cloned()
+      .collect(),
+  };
+  if candidates.is_empty() {
+    return Err(Error::msg(format!(
+      "No cdylib member found in workspace {}.",
+      metadata.workspace_root
+    )));
+  }
+
+  let files = changed_files(&pwd, git_ref)?;
+  let affected = affected_members(metadata.workspace_root.as_std_path(), &members, &files);
+
+  for pkg in candidates {
+    if !affected.contains(&pkg.name) {
+      println!(
+        "{} {}: no changes since {}.",
+        "Skip".bold().yellow(),
+        pkg.name,
+        git_ref
+      );
+      continue;
+    }
+    println!("{} {}", "Build".bold().green(), pkg.name);
+    // prepare reads the crate from current dir
+    env::set_current_dir(package_dir(&pkg))?;
+    let ret = super::build_package(args.clone());
+    env::set_current_dir(&pwd)?;
+    ret.map_err(|e| Error::msg(format!("Build {} failed: {}", pkg.name, e)))?;
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod test {
+  use super::{affected_members, changed_files, git};
+  use crate::util::test_dir;
+  use cargo_metadata::Package;
+  use std::fs;
+  use std::path::PathBuf;
+
+  fn member(name: &str, dir: &str, path_deps: &[&str]) -> Package {
+    let dependencies = path_deps
+      .iter()
+      .map(|d| {
+        serde_json::json!({
+          "name": d,
+          "source": null,
+          "req": "*",
+          "kind": null,
+          "rename": null,
+          "optional": false,
+          "uses_default_features": true,
+          "features": [],
+          "target": null,
+          "registry": null,
+          "path": format!("/ws/{}", d)
+        })
+      })
+      .collect::<Vec<_>>();
+    serde_json::from_value(serde_json::json!({
+      "name": name,
+      "version": "0.1.0",
+      "id": format!("{} 0.1.0", name),
+      "source": null,
+      "dependencies": dependencies,
+      "targets": [],
+      "features": {},
+      "manifest_path": format!("/ws/{}/Cargo.toml", dir),
+      "authors": [],
+      "categories": [],
+      "keywords": [],
+      "edition": "2021",
+      "metadata": null,
+      "publish": null,
+      "links": null
+    }))
+    .unwrap()
+  }
+
+  #[test]
+  fn test_affected_members() {
+    let members = [
+      member("core", "core", &[]),
+      member("a", "a", &["core"]),
+      member("b", "b", &[]),
+      member("nested", "b/nested", &[]),
+    ];
+    let root = PathBuf::from("/ws");
+    let affected = |files: &[&str]| {
+      let files = files.iter().map(|f| root.join(f)).collect::<Vec<_>>();
+      let mut ret = affected_members(&root, &members, &files)
+        .into_iter()
+        .collect::<Vec<_>>();
+      ret.sort();
+      ret
+    };
+
+    assert_eq!(affected(&["b/src/lib.rs"]), vec!["b"]);
+    assert_eq!(affected(&["b/nested/src/lib.rs"]), vec!["nested"]);
+    assert_eq!(affected(&["core/src/lib.rs"]), vec!["a", "core"]);
+    assert_eq!(affected(&["README.md"]), Vec::<String>::new());
+    assert_eq!(affected(&["Cargo.lock"]), vec!["a", "b", "core", "nested"]);
+  }
+
+  #[test]
+  fn test_changed_files_in_sub_dir() {
+    let temp = test_dir();
+    let root = temp.path().to_path_buf();
+    let sub = root.join("sub");
+    fs::create_dir_all(&sub).unwrap();
+    git(&root, &["init", "-q"]).unwrap();
+    fs::write(root.join("tracked.txt"), "a").unwrap();
+    git(&root, &["add", "."]).unwrap();
+    git(
+      &root,
+      &[
+        "-c",
+        "user.name=ohrs",
+        "-c",
+        "user.email=ohrs@example.com",
+        "commit",
+        "-q",
+        "-m",
+        "init",
+      ],
+    )
+    .unwrap();
+    fs::write(root.join("tracked.txt"), "b").unwrap();
+    fs::write(sub.join("new.txt"), "c").unwrap();
+
+    let root = fs::canonicalize(&root).unwrap();
+    let mut files = changed_files(&sub, "HEAD").unwrap();
+    files.sort();
+    assert_eq!(
+      files,
+      vec![root.join("sub").join("new.txt"), root.join("tracked.txt")]
+    );
+  }
+}
@@ -1,20 +1,297 @@
 use crate::build::Context;
-use crate::create_project_file;
+use crate::util::{write_atomic, write_cache, DTS_HASH};
+use crate::{create_dist_dir, create_project_file};
 use anyhow::Error;
 use owo_colors::OwoColorize;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env;
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
+use std::fs::{self, File};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::str::FromStr;
 
 const TOP_LEVEL_NAMESPACE: &str = "__TOP_LEVEL_MODULE__";
-const DEFAULT_TYPE_DEF_HEADER: &str = "/* auto-generated by OHOS-RS */
-/* eslint-disable */
+const DEFAULT_TYPE_DEF_HEADER: &str = "/* auto-generated by OHOS-RS */\n";
 
-";
+/// The lint directive in the header of .d.ts file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LintIgnore {
+  #[default]
+  Eslint,
+  Biome,
+  Oxlint,
+  None,
+}
+
+impl LintIgnore {
+  pub fn name(self) -> &'static str {
+    match self {
+      LintIgnore::Eslint => "eslint",
+      LintIgnore::Biome => "biome",
+      LintIgnore::Oxlint => "oxlint",
+      LintIgnore::None => "none",
+    }
+  }
+
+  fn directive(self) -> &'static str {
+    match self {
+      LintIgnore::Eslint => "/* eslint-disable */\n",
+      LintIgnore::Biome => "/* biome-ignore-all lint: auto-generated */\n",
+      LintIgnore::Oxlint => "/* oxlint-disable */\n",
+      LintIgnore::None => "",
+    }
+  }
+}
+
+impl FromStr for LintIgnore {
+  type Err = String;
+  fn from_str(s: &str) -> Result<Self, String> {
+    [
+      LintIgnore::Eslint,
+      LintIgnore::Biome,
+      LintIgnore::Oxlint,
+      LintIgnore::None,
+    ]
+    .into_iter()
+    .find(|l| l.name() == s.to_lowercase())
+    .ok_or(format!(
+      "Unsupported lint {}, only eslint, biome, oxlint and none are supported.",
+      s
+    ))
+  }
+}
+
+/// How the optional properties of interface and class are declared
+/// - `omit`: `field?: T`, for the default compiler options
+/// - `undefined`: `field?: T | undefined`, valid with or without `exactOptionalPropertyTypes`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OptionalStyle {
+  #[default]
+  Omit,
+  Undefined,
+}
+
+impl OptionalStyle {
+  pub fn name(self) -> &'static str {
+    match self {
+      OptionalStyle::Omit => "omit",
+      OptionalStyle::Undefined => "undefined",
+    }
+  }
+}
+
+impl FromStr for OptionalStyle {
+  type Err = String;
+  fn from_str(s: &str) -> Result<Self, String> {
+    [OptionalStyle::Omit, OptionalStyle::Undefined]
+      .into_iter()
+      .find(|o| o.name() == s.to_lowercase())
+      .ok_or(format!(
+        "Unsupported optional style {}, only omit and undefined are supported.",
+        s
+      ))
+  }
+}
+
+/// The keyword of grouped declarations, `module` is kept for the legacy consumers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NamespaceKeyword {
+  #[default]
+  Namespace,
+  Module,
+}
+
+impl NamespaceKeyword {
+  pub fn name(self) -> &'static str {
+    match self {
+      NamespaceKeyword::Namespace => "namespace",
+      NamespaceKeyword::Module => "module",
+    }
+  }
+}
+
+impl FromStr for NamespaceKeyword {
+  type Err = String;
+  fn from_str(s: &str) -> Result<Self, String> {
+    [NamespaceKeyword::Namespace, NamespaceKeyword::Module]
+      .into_iter()
+      .find(|k| k.name() == s.to_lowercase())
+      .ok_or(format!(
+        "Unsupported namespace keyword {}, only namespace and module are supported.",
+        s
+      ))
+  }
+}
+
+/// The output format of `--print-exports`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportsFormat {
+  Lines,
+  Json,
+}
+
+impl ExportsFormat {
+  pub fn name(self) -> &'static str {
+    match self {
+      ExportsFormat::Lines => "lines",
+      ExportsFormat::Json => "json",
+    }
+  }
+}
+
+impl FromStr for ExportsFormat {
+  type Err = String;
+  fn from_str(s: &str) -> Result<Self, String> {
+    [ExportsFormat::Lines, ExportsFormat::Json]
+      .into_iter()
+      .find(|f| f.name() == s.to_lowercase())
+      .ok_or(format!(
+        "Unsupported exports format {}, only lines and json are supported.",
+        s
+      ))
+  }
+}
+
+/// The types which are declared in the header when they are referenced
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolyfillName {
+  AbortSignal,
+  ExternalObject,
+}
+
+impl PolyfillName {
+  pub fn name(self) -> &'static str {
+    match self {
+      PolyfillName::AbortSignal => "abort-signal",
+      PolyfillName::ExternalObject => "external-object",
+    }
+  }
+
+  fn type_name(self) -> &'static str {
+    match self {
+      PolyfillName::AbortSignal => "AbortSignal",
+      PolyfillName::ExternalObject => "ExternalObject",
+    }
+  }
+
+  // the package which can be imported without a module
+  fn default_module(self) -> Option<&'static str> {
+    match self {
+      PolyfillName::AbortSignal => Some("@ohos-rs/abort-controller"),
+      PolyfillName::ExternalObject => None,
+    }
+  }
+}
+
+/// How the polyfill is provided, inline is the default
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum PolyfillMode {
+  #[default]
+  Inline,
+  // import the type from the module
+  Import(String),
+  // the consumer provides it
+  Off,
+}
+
+/// `name=inline`, `name=import[:module]` or `name=off`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polyfill {
+  pub name: PolyfillName,
+  pub mode: PolyfillMode,
+}
+
+impl Polyfill {
+  pub fn name(&self) -> String {
+    let mode = match &self.mode {
+      PolyfillMode::Inline => String::from("inline"),
+      PolyfillMode::Import(module) => format!("import:{}", module),
+      PolyfillMode::Off => String::from("off"),
+    };
+    format!("{}={}", self.name.name(), mode)
+  }
+}
+
+impl FromStr for Polyfill {
+  type Err = String;
+  fn from_str(s: &str) -> Result<Self, String> {
+    let (name, mode) = s.split_once('=').ok_or(format!(
+      "Invalid polyfill {}, it should be NAME=MODE, e.g. abort-signal=off.",
+      s
+    ))?;
+    let name = [PolyfillName::AbortSignal, PolyfillName::ExternalObject]
+      .into_iter()
+      .find(|p| p.name() == name.trim().to_lowercase())
+      .ok_or(format!(
+        "Unsupported polyfill {}, only abort-signal and external-object are supported.",
+        name
+      ))?;
+    let mode = match mode.trim().split_once(':') {
+      Some(("import", module)) if !module.trim().is_empty() => {
+        PolyfillMode::Import(module.trim().to_string())
+      }
+      None if mode.trim() == "import" => match name.default_module() {
+        Some(module) => PolyfillMode::Import(module.to_string()),
+        None => {
+          return Err(format!(
+            "Polyfill {}=import needs the module, e.g. {}=import:@scope/types.",
+            name.name(),
+            name.name()
+          ))
+        }
+      },
+      None if mode.trim() == "inline" => PolyfillMode::Inline,
+      None if mode.trim() == "off" => PolyfillMode::Off,
+      _ => {
+        return Err(format!(
+          "Unsupported polyfill mode {}, only inline, import[:module] and off are supported.",
+          mode
+        ))
+      }
+    };
+    Ok(Polyfill { name, mode })
+  }
+}
+
+/// `NAME=MODULE`, the type is imported from the module instead of being declared
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalType {
+  pub name: String,
+  pub module: String,
+}
+
+impl ExternalType {
+  pub fn name(&self) -> String {
+    format!("{}={}", self.name, self.module)
+  }
+}
+
+impl FromStr for ExternalType {
+  type Err = String;
+  fn from_str(s: &str) -> Result<Self, String> {
+    match s.split_once('=') {
+      Some((name, module)) if is_valid_js_identifier(name.trim()) && !module.trim().is_empty() => {
+        Ok(ExternalType {
+          name: name.trim().to_string(),
+          module: module.trim().to_string(),
+        })
+      }
+      _ => Err(format!(
+        "Invalid external type {}, it should be NAME=MODULE, e.g. Base=@scope/base.",
+        s
+      )),
+    }
+  }
+}
+
+// attribution and the lint directive
+fn type_def_header(lint_ignore: LintIgnore) -> String {
+  format!("{}{}\n", DEFAULT_TYPE_DEF_HEADER, lint_ignore.directive())
+}
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy)]
 enum TypeDefKind {
@@ -34,6 +311,90 @@ enum TypeDefKind {
   Impl,
   #[serde(rename = "string_enum")]
   StringEnum,
+  #[serde(rename = "type_alias")]
+  TypeAlias,
+  #[serde(rename = "mod")]
+  Mod,
+  // kinds from newer or custom macros, the def will be emitted as is
+  #[serde(other)]
+  Unknown,
+}
+
+/// Options to control the generated .d.ts file
+#[derive(Debug, Clone, Default)]
+pub struct DtsOptions {
+  // hoist the only member of a namespace to top level
+  pub flatten_singletons: bool,
+  // external command to transform the final .d.ts content
+  pub transform: Option<String>,
+  // namespaces listed here are emitted first, others are sorted alphabetically
+  pub namespace_order: Vec<String>,
+  // custom .d.ts file path, default is `{dist}/index.d.ts`
+  pub out: Option<PathBuf>,
+  // custom JS entry path, default is `index.js` beside the .d.ts file
+  pub js_out: Option<PathBuf>,
+  // invalid identifiers will be treated as error
+  pub strict: bool,
+  // emit `.d.mts` for ESM alongside the `.d.ts`
+  pub dual: bool,
+  // emit a .d.ts file for every namespace
+  pub split: bool,
+  // add `@throws` to the doc of fallible functions
+  pub annotate_throws: bool,
+  // emit `index.d.ts.map.json` which maps declarations to rust source
+  pub emit_map: bool,
+  // sort classes by name with other declarations instead of grouping them
+  pub no_struct_first: bool,
+  // emit the declarations and a types only `package.json` into the folder
+  pub types_package: Option<PathBuf>,
+  // which linter should ignore the generated file
+  pub lint_ignore: LintIgnore,
+  // external formatter for the final .d.ts content, failure is not fatal
+  pub formatter: Option<String>,
+  // every referenced type should be declared in the output or built in
+  pub closed_world: bool,
+  // declaration of optional properties
+  pub optional_style: OptionalStyle,
+  // keyword of the grouped declarations
+  pub namespace_keyword: NamespaceKeyword,
+  // wrap the declarations into the namespace which is exported by `export =`
+  pub export_equals: Option<String>,
+  // how the polyfills are provided, the later one wins
+  pub polyfills: Vec<Polyfill>,
+  // the types which are imported from other packages
+  pub external_types: Vec<ExternalType>,
+}
+
+impl DtsOptions {
+  fn polyfill_mode(&self, name: PolyfillName) -> PolyfillMode {
+    self
+      .polyfills
+      .iter()
+      .rev()
+      .find(|p| p.name == name)
+      .map(|p| p.mode.clone())
+      .unwrap_or_default()
+  }
+}
+
+// where the declaration is defined, file and line are missing with rustc older than 1.88
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct TypeDefSource {
+  #[serde(rename = "crate")]
+  krate: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  module: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  file: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  line: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct TypeDefMember {
+  name: String,
+  value: String,
+  js_doc: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -44,9 +405,26 @@ struct TypeDefLine {
   def: String,
   js_doc: Option<String>,
   js_mod: Option<String>,
+  // enum members, older napi-derive-ohos doesn't provide it
+  #[serde(default)]
+  members: Vec<TypeDefMember>,
+  #[serde(default)]
+  throws: bool,
+  #[serde(default)]
+  index_signature: Option<String>,
+  #[serde(default)]
+  source: Option<TypeDefSource>,
+  #[serde(default)]
+  global: bool,
+  // the old name which is exported with the same value
+  #[serde(default)]
+  alias: Option<String>,
+  // the literal type of const value, with `#[napi(literal_const)]`
+  #[serde(default)]
+  literal: Option<String>,
 }
 
-fn read_intermediate_type_file(file_path: &str) -> Vec<TypeDefLine> {
+fn read_intermediate_type_file(file_path: &str, struct_first: bool) -> Vec<TypeDefLine> {
   let path = Path::new(file_path);
   let file = File::open(path).unwrap();
   let lines = io::BufReader::new(file).lines();
@@ -66,12 +444,28 @@ fn read_intermediate_type_file(file_path: &str) -> Vec<TypeDefLine> {
       }
       if !format_line.is_empty() {
         let json_value: TypeDefLine = serde_json::from_str(&format_line).unwrap();
+        if json_value.kind == TypeDefKind::Unknown {
+          if json_value.def.trim().is_empty() {
+            eprintln!(
+              "{}: Unknown type def kind of `{}` is skipped.",
+              "Warning".bold().yellow(),
+              json_value.name
+            );
+            continue;
+          }
+          eprintln!(
+            "{}: Unknown type def kind of `{}`, its def will be emitted as is.",
+            "Warning".bold().yellow(),
+            json_value.name
+          );
+        }
         defs.push(json_value);
       }
     }
   }
 
   defs.sort_unstable_by(|a, b| match (a.kind, b.kind) {
+    _ if !struct_first => a.name.cmp(&b.name),
     (TypeDefKind::Struct, TypeDefKind::Struct) => a.name.cmp(&b.name),
     (TypeDefKind::Struct, _) => std::cmp::Ordering::Less,
     (_, TypeDefKind::Struct) => std::cmp::Ordering::Greater,
@@ -80,61 +474,297 @@ fn read_intermediate_type_file(file_path: &str) -> Vec<TypeDefLine> {
   defs
 }
 
+// Generated declarations, every namespace is kept as a block so they can be split into files
+struct TypeDefOutput {
+  // (type, module) of the imported polyfills
+  imports: Vec<(String, String)>,
+  header: String,
+  top_level_doc: String,
+  // `None` for top level declarations
+  blocks: Vec<(Option<String>, String)>,
+  // the names can be imported from top level
+  top_level_names: Vec<String>,
+  exports: Vec<String>,
+}
+
 // The process_type_def function to process type definitions
-fn process_type_def(
+pub fn process_type_def(
+  intermediate_type_file: &str,
+  const_enum: bool,
+  header: &str,
+  options: &DtsOptions,
+) -> anyhow::Result<(String, Vec<String>)> {
+  if let Some(name) = options
+    .export_equals
+    .as_deref()
+    .filter(|n| !is_valid_js_identifier(n))
+  {
+    return Err(Error::msg(format!(
+      "--export-equals {} is not a valid JavaScript identifier.",
+      name
+    )));
+  }
+  let output = process_type_def_blocks(intermediate_type_file, const_enum, header, options)?;
+  if let Some(name) = &options.export_equals {
+    return Ok((export_equals_dts(&output, name), output.exports));
+  }
+  let mut dts = polyfill_imports(&output.imports, |_| true);
+  dts += &output.header;
+  dts += &output.top_level_doc;
+  output.blocks.iter().for_each(|(_, block)| dts += block);
+  Ok((dts, output.exports))
+}
+
+// Wrap all declarations except `declare global` into an ambient namespace for `export =`,
+// the file can't have other exports, so the generated header is wrapped too
+fn export_equals_dts(output: &TypeDefOutput, name: &str) -> String {
+  let mut body = output.header.clone();
+  let mut globals = String::new();
+  for (_, block) in &output.blocks {
+    if block.starts_with("declare global {") {
+      globals += block;
+    } else {
+      body += block;
+    }
+  }
+
+  let mut dts = polyfill_imports(&output.imports, |_| true);
+  dts += &output.top_level_doc;
+  dts += &format!("declare namespace {} {{\n", name);
+  for line in body.lines() {
+    if line.trim().is_empty() {
+      dts.push('\n');
+      continue;
+    }
+    // `declare` can't be used in an ambient context
+    let line = match line.strip_prefix("export declare ") {
+      Some(rest) => format!("export {}", rest),
+      None => line.to_string(),
+    };
+    dts += &format!("  {}\n", line);
+  }
+  dts.push_str("}\n\n");
+  dts += &globals;
+  if !globals.is_empty() {
+    dts.push('\n');
+  }
+  dts += &format!("export = {}\n", name);
+  dts
+}
+
+/// the js entry of `export =` style, every export of the native module is bundled into one object
+pub fn export_equals_entry(library: &str, exports: &[String]) -> String {
+  let mut entry = format!(
+    "const binding = require('{}')\n\nmodule.exports = {{\n",
+    library
+  );
+  for name in exports {
+    entry += &format!("  {}: binding.{},\n", name, name);
+  }
+  entry.push_str("}\n");
+  entry
+}
+
+// Import the polyfills which are not inlined
+fn polyfill_imports(imports: &[(String, String)], is_used: impl Fn(&str) -> bool) -> String {
+  let mut ret = imports
+    .iter()
+    .filter(|(name, _)| is_used(name))
+    .map(|(name, module)| format!("import type {{ {} }} from '{}'\n", name, module))
+    .collect::<String>();
+  if !ret.is_empty() {
+    ret.push('\n');
+  }
+  ret
+}
+
+// (namespace, content) of every namespace file
+type NamespaceFiles = Vec<(String, String)>;
+
+// Split the declarations, returns the root index content and the file content for each namespace
+fn process_type_def_split(
+  intermediate_type_file: &str,
+  const_enum: bool,
+  header: &str,
+  options: &DtsOptions,
+) -> anyhow::Result<(String, NamespaceFiles, Vec<String>)> {
+  let output = process_type_def_blocks(intermediate_type_file, const_enum, header, options)?;
+  let mut index = polyfill_imports(&output.imports, |_| true);
+  index += &output.header;
+  index += &output.top_level_doc;
+  let mut re_exports = String::new();
+  let mut files = Vec::new();
+
+  for (namespace, block) in output.blocks {
+    let Some(namespace) = namespace else {
+      index += &block;
+      continue;
+    };
+    re_exports += &format!("export * from './types/{}'\n", namespace);
+
+    // the declarations in namespace may refer to top level or other namespaces
+    let imports = output
+      .top_level_names
+      .iter()
+      .filter(|name| *name != &namespace)
+      .filter(|name| {
+        Regex::new(&format!(r"\b{}\b", regex::escape(name)))
+          .map(|r| r.is_match(&block))
+          .unwrap_or(false)
+      })
+      .cloned()
+      .collect::<Vec<_>>();
+    let mut content = polyfill_imports(&output.imports, |name| {
+      Regex::new(&format!(r"\b{}\b", name))
+        .map(|r| r.is_match(&block))
+        .unwrap_or(false)
+    });
+    if !imports.is_empty() {
+      content += &format!("import {{ {} }} from '../index'\n\n", imports.join(", "));
+    }
+    content += &block;
+    files.push((namespace, content));
+  }
+
+  if !re_exports.is_empty() {
+    index.push('\n');
+    index += &re_exports;
+  }
+  Ok((index, files, output.exports))
+}
+
+fn process_type_def_blocks(
   intermediate_type_file: &str,
   const_enum: bool,
   header: &str,
-) -> (String, Vec<String>) {
+  options: &DtsOptions,
+) -> anyhow::Result<TypeDefOutput> {
   let mut exports: Vec<String> = Vec::new();
-  let defs = read_intermediate_type_file(intermediate_type_file);
-  let mut grouped_defs = preprocess_type_def(defs);
+  let defs = read_intermediate_type_file(intermediate_type_file, !options.no_struct_first);
+  let mut grouped_defs = preprocess_type_def(defs, !options.no_struct_first);
+  let mut module_docs = take_module_docs(&mut grouped_defs);
+
+  if options.flatten_singletons {
+    flatten_singleton_namespace(&mut grouped_defs);
+  }
+
+  validate_identifiers(&grouped_defs, options.strict)?;
+  check_alias_conflicts(&grouped_defs)?;
+  validate_enum_members(&grouped_defs, options.strict)?;
+  if options.optional_style == OptionalStyle::Undefined {
+    grouped_defs
+      .values_mut()
+      .flatten()
+      .filter(|d| matches!(d.kind, TypeDefKind::Interface | TypeDefKind::Struct))
+      .for_each(|d| d.def = with_explicit_undefined(&d.def));
+  }
+  // the external types are imported, so the local declarations are dropped
+  if let Some(defs) = grouped_defs.get_mut(TOP_LEVEL_NAMESPACE) {
+    defs.retain(|d| !options.external_types.iter().any(|e| e.name == d.name));
+  }
+  let global_defs = take_global_defs(&mut grouped_defs);
+  check_namespace_shadowing(&grouped_defs, options.strict)?;
+  if options.closed_world {
+    check_closed_world(
+      &grouped_defs,
+      &global_defs,
+      const_enum,
+      &options.external_types,
+    )?;
+  }
 
   let mut header = String::from(header);
-  let mut dts = String::new();
+  let mut top_level_doc = String::new();
+  let mut blocks: Vec<(Option<String>, String)> = Vec::new();
+  let mut top_level_names: Vec<String> = Vec::new();
+
+  // top level module doc is placed at the file top
+  if let Some(js_doc) = module_docs.remove(TOP_LEVEL_NAMESPACE) {
+    top_level_doc += &js_doc;
+    top_level_doc.push('\n');
+  }
 
   // Sort and process the definitions
   let mut sorted_grouped_defs = grouped_defs.drain().collect::<Vec<_>>();
   sorted_grouped_defs.sort_by_key(|(namespace, _)| namespace.clone());
+  // top level module is always the first one, then the ordered namespaces and the rest
+  sorted_grouped_defs.sort_by_key(|(namespace, _)| {
+    match options.namespace_order.iter().position(|n| n == namespace) {
+      _ if namespace == TOP_LEVEL_NAMESPACE => (0, 0),
+      Some(index) => (1, index),
+      None => (2, 0),
+    }
+  });
 
-  for (namespace, defs) in sorted_grouped_defs {
+  for (namespace, mut defs) in sorted_grouped_defs {
+    if options.annotate_throws {
+      defs
+        .iter_mut()
+        .filter(|def| def.throws)
+        .for_each(|def| def.js_doc = Some(with_throws_tag(def.js_doc.take())));
+    }
+    let mut dts = String::new();
     if namespace == TOP_LEVEL_NAMESPACE {
       for def in defs {
         dts += &pretty_print(&def, const_enum, 0, false);
         dts.push('\n');
-        match def.kind {
-          TypeDefKind::Const
-          | TypeDefKind::Enum
-          | TypeDefKind::Fn
-          | TypeDefKind::Struct
-          | TypeDefKind::StringEnum => {
-            exports.push(def.name.clone());
-            if let Some(original_name) = def.original_name {
-              if original_name != def.name {
-                exports.push(original_name);
-              }
+        top_level_names.push(def.name.clone());
+        top_level_names.extend(def.alias.clone());
+        // type alias and interface have nothing registered at runtime, so they aren't listed
+        // in exports, which feed the export-equals index.js and --print-exports
+        if is_runtime_export(&def.kind) {
+          exports.push(def.name.clone());
+          if let Some(original_name) = def.original_name {
+            if original_name != def.name {
+              exports.push(original_name);
             }
           }
-          _ => {}
+          exports.extend(def.alias);
         }
       }
     } else {
       exports.push(namespace.clone());
-      dts += &format!("export namespace {} {{\n", namespace);
+      if let Some(js_doc) = module_docs.get(&namespace) {
+        dts += js_doc;
+      }
+      dts += &format!(
+        "export {} {} {{\n",
+        options.namespace_keyword.name(),
+        namespace
+      );
       for def in defs {
         dts += &pretty_print(&def, const_enum, 2, true);
         dts.push('\n');
       }
       dts.push_str("}\n");
     }
+    // `Either` may be leaked from custom ts type, convert it to union
+    dts = normalize_either(&dts);
+    blocks.push(((namespace != TOP_LEVEL_NAMESPACE).then_some(namespace), dts));
+  }
+  if !global_defs.is_empty() {
+    let mut dts = String::from("declare global {\n");
+    for def in global_defs {
+      dts += &strip_export(&pretty_print(&def, const_enum, 2, true), 2);
+      dts.push('\n');
+    }
+    dts.push_str("}\n");
+    blocks.push((None, normalize_either(&dts)));
   }
+  // namespaces can be referred in other namespaces
+  top_level_names.extend(blocks.iter().filter_map(|(n, _)| n.clone()));
 
   let mut has_import = false;
 
   let buffer_reg = Regex::new(r"\bBuffer\b").unwrap();
-  if buffer_reg.is_match(&dts) {
+  if blocks
+    .iter()
+    .any(|(_, b)| has_type_reference(b, &buffer_reg))
+  {
     has_import = true;
-    dts = buffer_reg.replace_all(&dts, "ArrayBuffer").to_string();
+    blocks.iter_mut().for_each(|(_, b)| {
+      *b = replace_type_references(b, &buffer_reg, "ArrayBuffer");
+    });
     // header.push_str("import buffer from '@ohos.buffer';\n");
 
     let info = format!(
@@ -145,32 +775,48 @@ fn process_type_def(
       "ArrayBuffer".bold().red()
     );
 
-    println!("{}", info);
+    eprintln!("{}", info);
   }
 
+  let mut imports = Vec::new();
   let abort_reg = Regex::new(r"\bAbortSignal\b").unwrap();
-  if abort_reg.is_match(&dts) {
-    has_import = true;
-    header.push_str(super::abort_tmp::ABORT_TS);
+  if blocks
+    .iter()
+    .any(|(_, b)| has_type_reference(b, &abort_reg))
+  {
+    match options.polyfill_mode(PolyfillName::AbortSignal) {
+      PolyfillMode::Inline => {
+        has_import = true;
+        header.push_str(super::abort_tmp::ABORT_TS);
+        top_level_names.push(String::from("AbortSignal"));
 
-    let info = format!(
-      "\nTips: You're currently using {}, which isn't supported by Harmony.
+        let info = format!(
+          "\nTips: You're currently using {}, which isn't supported by Harmony.
       You could consider using {} as an alternative.
       For more detail info: https://github.com/ohos-rs/abort-controller",
-      "AbortController".bold().red(),
-      "@ohos-rs/abort-controller".bold().red()
-    );
+          "AbortController".bold().red(),
+          "@ohos-rs/abort-controller".bold().red()
+        );
 
-    println!("{}", info);
+        eprintln!("{}", info);
+      }
+      PolyfillMode::Import(module) => {
+        imports.push((PolyfillName::AbortSignal.type_name().to_string(), module))
+      }
+      PolyfillMode::Off => {}
+    }
   }
 
   if has_import {
     header.push_str("\n\n");
   }
 
-  if dts.contains("ExternalObject<") {
-    header.push_str(
-      r#"
+  if blocks.iter().any(|(_, b)| b.contains("ExternalObject<")) {
+    match options.polyfill_mode(PolyfillName::ExternalObject) {
+      PolyfillMode::Inline => {
+        top_level_names.push(String::from("ExternalObject"));
+        header.push_str(
+          r#"
 export class ExternalObject<T> {
   readonly '': {
     readonly '': unique symbol
@@ -178,144 +824,1153 @@ export class ExternalObject<T> {
   }
 }
 "#,
-    );
+        );
+      }
+      PolyfillMode::Import(module) => {
+        imports.push((PolyfillName::ExternalObject.type_name().to_string(), module))
+      }
+      PolyfillMode::Off => {}
+    }
   }
 
-  dts.insert_str(0, header.as_str());
+  for external in &options.external_types {
+    let reg = Regex::new(&format!(r"\b{}\b", regex::escape(&external.name))).unwrap();
+    if blocks.iter().any(|(_, b)| has_type_reference(b, &reg)) {
+      imports.push((external.name.clone(), external.module.clone()));
+    }
+  }
 
-  (dts, exports)
+  Ok(TypeDefOutput {
+    imports,
+    header,
+    top_level_doc,
+    blocks,
+    top_level_names,
+    exports,
+  })
 }
 
-// Helper function to preprocess type definitions
-fn preprocess_type_def(defs: Vec<TypeDefLine>) -> HashMap<String, Vec<TypeDefLine>> {
-  let mut namespace_grouped: HashMap<String, Vec<TypeDefLine>> = HashMap::new();
-  let mut class_defs: HashMap<String, (String, TypeDefLine)> = HashMap::new();
-
-  for def in defs {
-    let namespace = def
-      .js_mod
-      .clone()
-      .unwrap_or_else(|| TOP_LEVEL_NAMESPACE.to_string());
-    namespace_grouped
-      .entry(namespace.clone())
-      .or_insert_with(Vec::new);
-
-    let group = namespace_grouped.get_mut(&namespace).unwrap();
+const JS_RESERVED_WORDS: [&str; 46] = [
+  "await",
+  "break",
+  "case",
+  "catch",
+  "class",
+  "const",
+  "continue",
+  "debugger",
+  "default",
+  "delete",
+  "do",
+  "else",
+  "enum",
+  "export",
+  "extends",
+  "false",
+  "finally",
+  "for",
+  "function",
+  "if",
+  "import",
+  "in",
+  "instanceof",
+  "new",
+  "null",
+  "return",
+  "super",
+  "switch",
+  "this",
+  "throw",
+  "true",
+  "try",
+  "typeof",
+  "var",
+  "void",
+  "while",
+  "with",
+  "yield",
+  "let",
+  "static",
+  "implements",
+  "interface",
+  "package",
+  "private",
+  "protected",
+  "public",
+];
 
-    match def.kind {
-      TypeDefKind::Struct => {
-        class_defs.insert(def.name.clone(), (namespace, def));
-      }
-      TypeDefKind::Impl => {
-        if let Some(class_def) = class_defs.get_mut(&def.name) {
-          if !class_def.1.def.is_empty() {
-            class_def.1.def += "\n";
-          }
-          class_def.1.def += &def.def;
-        }
-      }
-      _ => {
-        group.push(def);
-      }
-    }
+// Check the name with the IdentifierName grammar of ECMAScript
+fn is_identifier_name(name: &str) -> bool {
+  let mut chars = name.chars();
+  let Some(first) = chars.next() else {
+    return false;
+  };
+  if !(first.is_alphabetic() || first == '$' || first == '_') {
+    return false;
   }
+  chars.all(|c| c.is_alphanumeric() || matches!(c, '$' | '_' | '\u{200C}' | '\u{200D}'))
+}
 
-  class_defs.iter().for_each(|(_, (n, t))| {
-    let group = namespace_grouped.get_mut(n).unwrap();
-    group.push(t.clone());
-  });
+// IdentifierName which isn't a reserved word
+fn is_valid_js_identifier(name: &str) -> bool {
+  is_identifier_name(name) && !JS_RESERVED_WORDS.contains(&name)
+}
 
-  namespace_grouped
+// Enum member is a property name, so the reserved words are fine,
+// but TypeScript rejects the numeric names, and `__proto__` would replace the prototype of the enum object
+fn is_valid_enum_member(name: &str) -> bool {
+  let quoted = name.len() >= 2
+    && ["'", "\""]
+      .iter()
+      .any(|q| name.starts_with(q) && name.ends_with(q));
+  let unquoted = if quoted {
+    &name[1..name.len() - 1]
+  } else {
+    name
+  };
+  (quoted || is_identifier_name(name))
+    && unquoted.parse::<f64>().is_err()
+    && unquoted != "__proto__"
 }
 
-fn export_declare(ambient: bool) -> String {
-  if ambient {
-    return String::from("export");
+// Names of the enum members, from the structured members or the raw def `A = 0,\nB = 1`
+fn enum_member_names(line: &TypeDefLine) -> Vec<String> {
+  if !line.members.is_empty() {
+    return line.members.iter().map(|m| m.name.clone()).collect();
   }
+  line
+    .def
+    .split(",\n")
+    .filter_map(|m| {
+      let m = m.rsplit_once("*/").map_or(m, |(_, m)| m);
+      let name = m.split_once('=').map_or(m, |(name, _)| name).trim();
+      Some(name.to_string()).filter(|n| !n.is_empty())
+    })
+    .collect()
+}
 
-  return String::from("export declare");
+// The variants are rendered as enum members, which have different rules with the exported names
+fn validate_enum_members(
+  grouped_defs: &HashMap<String, Vec<TypeDefLine>>,
+  strict: bool,
+) -> anyhow::Result<()> {
+  let mut invalid = grouped_defs
+    .iter()
+    .flat_map(|(namespace, defs)| defs.iter().map(move |def| (namespace, def)))
+    .filter(|(_, def)| matches!(def.kind, TypeDefKind::Enum | TypeDefKind::StringEnum))
+    .flat_map(|(namespace, def)| {
+      enum_member_names(def)
+        .into_iter()
+        .filter(|m| !is_valid_enum_member(m))
+        .map(move |m| {
+          let mut origin = format!("`{}` of enum `{}`", m, def.name);
+          if namespace != TOP_LEVEL_NAMESPACE {
+            origin += &format!(" in namespace `{}`", namespace);
+          }
+          origin
+        })
+    })
+    .collect::<Vec<_>>();
+  if invalid.is_empty() {
+    return Ok(());
+  }
+  invalid.sort();
+
+  let info = format!(
+    "The following enum members are not valid in TypeScript, please rename them with js_name:\n  {}",
+    invalid.join("\n  ")
+  );
+  if strict {
+    return Err(Error::msg(info));
+  }
+  eprintln!("{}: {}", "Warning".bold().yellow(), info);
+  Ok(())
 }
 
-// Helper function to format the string with the correct indentation
-fn pretty_print(line: &TypeDefLine, const_enum: bool, indent: usize, ambient: bool) -> String {
-  let mut s = line.js_doc.clone().unwrap_or_default();
-  match line.kind {
-    TypeDefKind::Interface => {
-      s += &format!("export interface {} {{\n{}\n}}", line.name, line.def);
-    }
-    TypeDefKind::Enum => {
-      let enum_name = if const_enum { "const enum" } else { "enum" };
-      s += &format!(
-        "{} {} {} {{\n{}\n}}",
-        export_declare(ambient),
-        enum_name,
-        line.name,
-        line.def
-      );
-    }
-    TypeDefKind::Type => {
-      s += &format!("export type {} = \n{}", line.name, line.def);
+// Validate all the exported names and namespaces
+fn validate_identifiers(
+  grouped_defs: &HashMap<String, Vec<TypeDefLine>>,
+  strict: bool,
+) -> anyhow::Result<()> {
+  let mut invalid = Vec::new();
+  for (namespace, defs) in grouped_defs {
+    if namespace != TOP_LEVEL_NAMESPACE && !is_valid_js_identifier(namespace) {
+      invalid.push(format!("namespace `{}`", namespace));
     }
-    TypeDefKind::StringEnum => match const_enum {
-      true => {
-        s += &format!(
-          "{} const enum {} {{\n{}\n}}",
-          export_declare(ambient),
-          line.name,
-          line.def
-        );
+    for def in defs {
+      let mut origin = format!("{:?} `{}`", def.kind, def.name);
+      if let Some(original_name) = &def.original_name {
+        origin += &format!(" (from `{}`)", original_name);
       }
-      false => {
-        let def = line.def.split('=').collect::<Vec<&str>>()[1]
-          .split(',')
-          .map(|s| s.trim())
-          .collect::<Vec<&str>>()
-          .join(" | ");
-        s += &format!("export type {} = {};", line.name, def);
+      if namespace != TOP_LEVEL_NAMESPACE {
+        origin += &format!(" in namespace `{}`", namespace);
       }
-    },
-    TypeDefKind::Struct => {
-      s += &format!(
-        "{} class {} {{\n{}\n}}",
-        export_declare(ambient),
-        line.name,
-        line.def
-      );
-      if let Some(original_name) = &line.original_name {
-        if original_name != &line.name {
-          s += &format!("\nexport type {} = {}", original_name, line.name);
-        }
+      if let Some(alias) = def.alias.as_ref().filter(|a| !is_valid_js_identifier(a)) {
+        invalid.push(format!("alias `{}` of {}", alias, origin));
+      }
+      if !is_valid_js_identifier(&def.name) {
+        invalid.push(origin);
       }
-    }
-    TypeDefKind::Fn => {
-      s += &format!("{} {}", export_declare(ambient), line.def);
-    }
-    _ => {
-      s += &line.def;
     }
   }
+  if invalid.is_empty() {
+    return Ok(());
+  }
+  invalid.sort();
 
-  correct_string_indent(&s, indent)
+  let info = format!(
+    "The following names are not valid JavaScript identifiers, please rename them with js_name:\n  {}",
+    invalid.join("\n  ")
+  );
+  if strict {
+    return Err(Error::msg(info));
+  }
+  eprintln!("{}: {}", "Warning".bold().yellow(), info);
+  Ok(())
 }
 
-fn correct_string_indent(src: &str, indent: usize) -> String {
-  let mut result = String::new();
-  let mut bracket_depth = 0;
-  for line in src.lines() {
-    let line = line.trim();
-    if line.is_empty() {
-      result.push('\n');
-      continue;
+// The alias is registered beside the export, so it can't take the name of another one
+fn check_alias_conflicts(grouped_defs: &HashMap<String, Vec<TypeDefLine>>) -> anyhow::Result<()> {
+  let mut conflicts = Vec::new();
+  for (namespace, defs) in grouped_defs {
+    for def in defs {
+      let Some(alias) = &def.alias else {
+        continue;
+      };
+      let taken_by = defs.iter().find(|d| {
+        d.name == *alias
+          || (!std::ptr::eq(*d, def) && d.alias.as_ref() == Some(alias))
+          || d.original_name.as_ref() == Some(alias)
+      });
+      let Some(other) = taken_by else {
+        continue;
+      };
+      let mut info = format!(
+        "alias `{}` of {:?} `{}` conflicts with {:?} `{}`",
+        alias, def.kind, def.name, other.kind, other.name
+      );
+      if namespace != TOP_LEVEL_NAMESPACE {
+        info += &format!(" in namespace `{}`", namespace);
+      }
+      conflicts.push(info);
     }
-
-    let is_in_multiline_comment = line.starts_with('*');
+  }
+  if conflicts.is_empty() {
+    return Ok(());
+  }
+  conflicts.sort();
+  Err(Error::msg(format!(
+    "The following aliases are the same as other exports, please rename them:\n  {}",
+    conflicts.join("\n  ")
+  )))
+}
+
+// `file:line` of the rust source, or the rust name
+fn def_origin(def: &TypeDefLine) -> String {
+  match def
+    .source
+    .as_ref()
+    .and_then(|s| Some((s.file.as_ref()?, s.line)))
+  {
+    Some((file, Some(line))) => format!("{}:{}", file, line),
+    Some((file, None)) => file.clone(),
+    None => format!("`{}`", def.original_name.as_ref().unwrap_or(&def.name)),
+  }
+}
+
+// A top level declaration with the same name as a namespace will be merged by TypeScript
+fn check_namespace_shadowing(
+  grouped_defs: &HashMap<String, Vec<TypeDefLine>>,
+  strict: bool,
+) -> anyhow::Result<()> {
+  let Some(top_level) = grouped_defs.get(TOP_LEVEL_NAMESPACE) else {
+    return Ok(());
+  };
+  let mut shadowed = Vec::new();
+  for def in top_level {
+    let Some(namespace_defs) = grouped_defs.get(&def.name) else {
+      continue;
+    };
+    let namespace_origin = namespace_defs
+      .first()
+      .map(|d| format!(" (defined at {})", def_origin(d)))
+      .unwrap_or_default();
+    shadowed.push(format!(
+      "{:?} `{}` (defined at {}) and namespace `{}`{}",
+      def.kind,
+      def.name,
+      def_origin(def),
+      def.name,
+      namespace_origin
+    ));
+  }
+  if shadowed.is_empty() {
+    return Ok(());
+  }
+  shadowed.sort();
+
+  let info = format!(
+    "The following top level declarations have the same name as namespaces, they will be merged in TypeScript, please rename them with js_name:\n  {}",
+    shadowed.join("\n  ")
+  );
+  if strict {
+    return Err(Error::msg(info));
+  }
+  eprintln!("{}: {}", "Warning".bold().yellow(), info);
+  Ok(())
+}
+
+// TypeScript built in types and the polyfills emitted by ohrs
+const KNOWN_TYPES: &[&str] = &[
+  "any",
+  "unknown",
+  "never",
+  "void",
+  "null",
+  "undefined",
+  "object",
+  "string",
+  "number",
+  "boolean",
+  "bigint",
+  "symbol",
+  "true",
+  "false",
+  "this",
+  "unique",
+  "infer",
+  "typeof",
+  "keyof",
+  "readonly",
+  "new",
+  "get",
+  "set",
+  "is",
+  "asserts",
+  "in",
+  "Array",
+  "ReadonlyArray",
+  "Promise",
+  "PromiseLike",
+  "Awaited",
+  "Record",
+  "Partial",
+  "Required",
+  "Readonly",
+  "Pick",
+  "Omit",
+  "Exclude",
+  "Extract",
+  "NonNullable",
+  "ReturnType",
+  "Parameters",
+  "InstanceType",
+  "Map",
+  "Set",
+  "WeakMap",
+  "WeakSet",
+  "ReadonlyMap",
+  "ReadonlySet",
+  "Date",
+  "RegExp",
+  "Error",
+  "TypeError",
+  "RangeError",
+  "Function",
+  "Object",
+  "String",
+  "Number",
+  "Boolean",
+  "Symbol",
+  "BigInt",
+  "ArrayBuffer",
+  "SharedArrayBuffer",
+  "DataView",
+  "Int8Array",
+  "Uint8Array",
+  "Uint8ClampedArray",
+  "Int16Array",
+  "Uint16Array",
+  "Int32Array",
+  "Uint32Array",
+  "Float32Array",
+  "Float64Array",
+  "BigInt64Array",
+  "BigUint64Array",
+  "Iterator",
+  "Iterable",
+  "IterableIterator",
+  "AsyncIterator",
+  "AsyncIterable",
+  "AsyncIterableIterator",
+  "Generator",
+  "AsyncGenerator",
+  "globalThis",
+  "Buffer",
+  "ExternalObject",
+  "AbortSignal",
+];
+
+// Generic parameters declared by `name<T, U extends X>`
+fn generic_params(src: &str) -> Vec<String> {
+  let generic_reg = Regex::new(r"[\w$]\s*<([^<>()]*)>\s*[({=]").unwrap();
+  generic_reg
+    .captures_iter(src)
+    .flat_map(|caps| {
+      split_generic_args(&caps[1])
+        .into_iter()
+        .filter_map(|p| p.split_whitespace().next().map(|p| p.to_string()))
+        .collect::<Vec<_>>()
+    })
+    .collect()
+}
+
+// Every referenced type should be declared in the output, built in, or a polyfill
+fn check_closed_world(
+  grouped_defs: &HashMap<String, Vec<TypeDefLine>>,
+  global_defs: &[TypeDefLine],
+  const_enum: bool,
+  external_types: &[ExternalType],
+) -> anyhow::Result<()> {
+  // a bare name resolves in its own namespace or the top level, `ns.X` resolves in `ns`
+  let declared_in = |defs: &mut dyn Iterator<Item = &TypeDefLine>| {
+    let mut names = std::collections::HashSet::new();
+    for def in defs {
+      names.insert(def.name.clone());
+      names.extend(def.original_name.clone());
+      names.extend(def.alias.clone());
+    }
+    names
+  };
+  let mut scopes = grouped_defs
+    .iter()
+    .filter(|(n, _)| *n != TOP_LEVEL_NAMESPACE)
+    .map(|(n, defs)| (n.as_str(), declared_in(&mut defs.iter())))
+    .collect::<HashMap<_, _>>();
+  let mut top_level = declared_in(
+    &mut grouped_defs
+      .get(TOP_LEVEL_NAMESPACE)
+      .into_iter()
+      .flatten()
+      .chain(global_defs),
+  );
+  top_level.extend(scopes.keys().map(|n| n.to_string()));
+  top_level.extend(external_types.iter().map(|e| e.name.clone()));
+  scopes.insert(TOP_LEVEL_NAMESPACE, top_level);
+
+  let ident_reg = Regex::new(r"[A-Za-z_$][\w$]*").unwrap();
+  let member_reg = Regex::new(r"^\.([A-Za-z_$][\w$]*)").unwrap();
+  let either_reg = Regex::new(r"^Either\d*$").unwrap();
+  let mut dangling = BTreeMap::<String, Vec<String>>::new();
+  // members of enum are not types
+  let type_defs = grouped_defs
+    .iter()
+    .flat_map(|(n, defs)| defs.iter().map(move |d| (n.as_str(), d)))
+    .chain(global_defs.iter().map(|d| (TOP_LEVEL_NAMESPACE, d)))
+    .filter(|(_, d)| !matches!(d.kind, TypeDefKind::Enum | TypeDefKind::StringEnum));
+  for (namespace, def) in type_defs {
+    let src = pretty_print(def, const_enum, 0, false);
+    let generics = generic_params(&src);
+    for m in type_references(&src, &ident_reg) {
+      let name = m.as_str();
+      let rest = src[m.end()..].trim_start();
+      let before = src[..m.start()].trim_end();
+      // property and parameter names, the member of namespace, or value of `typeof`
+      if rest.starts_with(':')
+        || rest.starts_with("?:")
+        || src[..m.start()].ends_with('.')
+        || before.ends_with("typeof")
+      {
+        continue;
+      }
+      let member = member_reg
+        .captures(&src[m.end()..])
+        .map(|c| c[1].to_string());
+      let missing = match (scopes.get(name), member) {
+        (Some(members), Some(member)) if name != TOP_LEVEL_NAMESPACE => {
+          (!members.contains(&member)).then(|| format!("{}.{}", name, member))
+        }
+        _ => {
+          let found = KNOWN_TYPES.contains(&name)
+            || either_reg.is_match(name)
+            || generics.iter().any(|g| g == name)
+            || scopes[namespace].contains(name)
+            || scopes[TOP_LEVEL_NAMESPACE].contains(name);
+          (!found).then(|| name.to_string())
+        }
+      };
+      let Some(missing) = missing else {
+        continue;
+      };
+      let referrers = dangling.entry(missing).or_default();
+      if !referrers.contains(&def.name) {
+        referrers.push(def.name.clone());
+      }
+    }
+  }
+  if dangling.is_empty() {
+    return Ok(());
+  }
+  let info = dangling
+    .iter()
+    .map(|(name, referrers)| format!("`{}` referenced by {}", name, referrers.join(", ")))
+    .collect::<Vec<_>>()
+    .join("\n  ");
+  Err(Error::msg(format!(
+    "The following types are not declared in .d.ts file, please check whether they are marked with #[napi]:\n  {}",
+    info
+  )))
+}
+
+// Find the index of matched `>` for the `<` at start, `=>` is ignored
+fn find_closing_angle(s: &str, start: usize) -> Option<usize> {
+  let bytes = s.as_bytes();
+  let mut depth = 0;
+  for i in start..bytes.len() {
+    match bytes[i] {
+      b'<' => depth += 1,
+      b'>' if i > 0 && bytes[i - 1] == b'=' => {}
+      b'>' => {
+        depth -= 1;
+        if depth == 0 {
+          return Some(i);
+        }
+      }
+      _ => {}
+    }
+  }
+  None
+}
+
+// Split generic arguments by top level comma
+fn split_generic_args(s: &str) -> Vec<&str> {
+  let bytes = s.as_bytes();
+  let mut args = Vec::new();
+  let mut depth = 0i32;
+  let mut prev = 0;
+  for i in 0..bytes.len() {
+    match bytes[i] {
+      b'<' | b'(' | b'[' | b'{' => depth += 1,
+      b'>' if i > 0 && bytes[i - 1] == b'=' => {}
+      b'>' | b')' | b']' | b'}' => depth -= 1,
+      b',' if depth == 0 => {
+        args.push(s[prev..i].trim());
+        prev = i + 1;
+      }
+      _ => {}
+    }
+  }
+  args.push(s[prev..].trim());
+  args
+}
+
+// Ranges of string literals and comments, nothing should be rewritten in them
+fn literal_and_comment_ranges(s: &str) -> Vec<(usize, usize)> {
+  let bytes = s.as_bytes();
+  let mut ranges = Vec::new();
+  let mut i = 0;
+  while i < bytes.len() {
+    let start = i;
+    let end = match bytes[i] {
+      b'/' if bytes.get(i + 1) == Some(&b'/') => s[i..].find('\n').map(|p| i + p),
+      b'/' if bytes.get(i + 1) == Some(&b'*') => s[i + 2..].find("*/").map(|p| i + p + 4),
+      quote @ (b'\'' | b'"' | b'`') => {
+        let mut j = i + 1;
+        while j < bytes.len() && bytes[j] != quote {
+          // skip the escaped char
+          j += if bytes[j] == b'\\' { 2 } else { 1 };
+        }
+        Some((j + 1).min(bytes.len()))
+      }
+      _ => {
+        i += 1;
+        continue;
+      }
+    };
+    i = end.unwrap_or(bytes.len());
+    ranges.push((start, i));
+  }
+  ranges
+}
+
+// Whether the identifier at `start..end` is used as a type, property and parameter names are excluded
+fn is_type_position(s: &str, start: usize, end: usize) -> bool {
+  const PUNCTUATIONS: [&str; 10] = [":", "<", ",", "|", "&", "=>", "(", "[", "=", "?"];
+  const KEYWORDS: [&str; 5] = ["extends", "implements", "keyof", "readonly", "typeof"];
+  let before = s[..start].trim_end();
+  let after = s[end..].trim_start();
+  let keyword = KEYWORDS.iter().any(|k| {
+    before.ends_with(k)
+      && !before[..before.len() - k.len()]
+        .ends_with(|c: char| c.is_alphanumeric() || c == '_' || c == '$')
+  });
+  if keyword {
+    return true;
+  }
+  let Some(punctuation) = PUNCTUATIONS.iter().find(|p| before.ends_with(**p)) else {
+    return false;
+  };
+  // `{ a: string, Buffer: number }` and `(Buffer: number)` are names
+  let is_name = after.starts_with(':') || after.starts_with("?:") || after.starts_with('(');
+  !(is_name && matches!(*punctuation, "," | "(" | "<" | "|" | "&"))
+}
+
+// Matches of the regex in type positions
+fn type_references<'a>(s: &'a str, reg: &'a Regex) -> impl Iterator<Item = regex::Match<'a>> {
+  let skipped = literal_and_comment_ranges(s);
+  reg.find_iter(s).filter(move |m| {
+    !skipped
+      .iter()
+      .any(|(start, end)| m.start() >= *start && m.start() < *end)
+      && is_type_position(s, m.start(), m.end())
+  })
+}
+
+fn has_type_reference(s: &str, reg: &Regex) -> bool {
+  type_references(s, reg).next().is_some()
+}
+
+// Replace the type references only, string literals, comments and names are untouched
+fn replace_type_references(s: &str, reg: &Regex, replacement: &str) -> String {
+  let mut ret = String::with_capacity(s.len());
+  let mut last = 0;
+  for m in type_references(s, reg) {
+    ret.push_str(&s[last..m.start()]);
+    ret.push_str(replacement);
+    last = m.end();
+  }
+  ret.push_str(&s[last..]);
+  ret
+}
+
+// Whether the whole type is wrapped by `(...)`
+fn is_wrapped_by_paren(s: &str) -> bool {
+  let mut depth = 0;
+  for (i, c) in s.char_indices() {
+    match c {
+      '(' => depth += 1,
+      ')' => {
+        depth -= 1;
+        if depth == 0 {
+          return i == s.len() - 1;
+        }
+      }
+      _ if depth == 0 => return false,
+      _ => {}
+    }
+  }
+  false
+}
+
+// Rewrite `Either<A, B>` and `EitherN<...>` to `A | B`
+fn normalize_either(dts: &str) -> String {
+  let either_reg = Regex::new(r"\bEither\d*<").unwrap();
+  // `Either<` in doc comments and string literals is kept
+  let skipped = literal_and_comment_ranges(dts);
+  let mut ret = String::with_capacity(dts.len());
+  let mut last = 0;
+  let mut start = 0;
+  while let Some(m) = either_reg.find_at(dts, start) {
+    start = m.end();
+    if skipped
+      .iter()
+      .any(|(s, e)| m.start() >= *s && m.start() < *e)
+    {
+      continue;
+    }
+    let open = m.end() - 1;
+    let Some(close) = find_closing_angle(dts, open) else {
+      continue;
+    };
+    let arms = split_generic_args(&dts[open + 1..close])
+      .into_iter()
+      .map(|arm| {
+        let arm = normalize_either(arm);
+        // function type should be wrapped, or it will swallow the other arms
+        if arm.contains("=>") && !is_wrapped_by_paren(&arm) {
+          format!("({})", arm)
+        } else {
+          arm
+        }
+      })
+      .collect::<Vec<_>>()
+      .join(" | ");
+
+    ret.push_str(&dts[last..m.start()]);
+    if dts[close + 1..].starts_with('[') {
+      ret.push_str(&format!("({})", arms));
+    } else {
+      ret.push_str(&arms);
+    }
+    last = close + 1;
+    start = close + 1;
+  }
+  ret.push_str(&dts[last..]);
+  ret
+}
+
+// Helper function to preprocess type definitions
+// classes are placed after other declarations by default, or kept in the sorted position with `struct_first` disabled
+fn preprocess_type_def(
+  defs: Vec<TypeDefLine>,
+  struct_first: bool,
+) -> HashMap<String, Vec<TypeDefLine>> {
+  let mut namespace_grouped: HashMap<String, Vec<TypeDefLine>> = HashMap::new();
+  let mut class_defs: Vec<(String, TypeDefLine)> = Vec::new();
+
+  // impl may be placed before its struct when they're sorted by name
+  let mut impl_defs: HashMap<String, Vec<String>> = HashMap::new();
+  for def in defs.iter().filter(|d| d.kind == TypeDefKind::Impl) {
+    impl_defs
+      .entry(def.name.clone())
+      .or_default()
+      .push(def.def.clone());
+  }
+
+  for mut def in defs {
+    let namespace = def
+      .js_mod
+      .clone()
+      .unwrap_or_else(|| TOP_LEVEL_NAMESPACE.to_string());
+    let group = namespace_grouped.entry(namespace.clone()).or_default();
+
+    match def.kind {
+      TypeDefKind::Struct => {
+        for impl_def in impl_defs.remove(&def.name).unwrap_or_default() {
+          if !def.def.is_empty() {
+            def.def += "\n";
+          }
+          def.def += &impl_def;
+        }
+        if struct_first {
+          class_defs.push((namespace, def));
+        } else {
+          group.push(def);
+        }
+      }
+      TypeDefKind::Impl => {}
+      _ => {
+        group.push(def);
+      }
+    }
+  }
+
+  class_defs.sort_by(|a, b| a.1.name.cmp(&b.1.name));
+  class_defs.into_iter().for_each(|(n, t)| {
+    namespace_grouped.entry(n).or_default().push(t);
+  });
+
+  namespace_grouped
+}
+
+// Take the module docs out of the grouped defs, keyed by namespace
+fn take_module_docs(
+  grouped_defs: &mut HashMap<String, Vec<TypeDefLine>>,
+) -> HashMap<String, String> {
+  let mut module_docs = HashMap::new();
+  grouped_defs.retain(|namespace, defs| {
+    defs.retain(|def| {
+      if def.kind != TypeDefKind::Mod {
+        return true;
+      }
+      if let Some(js_doc) = def.js_doc.as_ref().filter(|d| !d.is_empty()) {
+        module_docs.insert(namespace.clone(), js_doc.clone());
+      }
+      false
+    });
+    // namespace with doc only should not be emitted
+    !defs.is_empty() || namespace == TOP_LEVEL_NAMESPACE
+  });
+  module_docs
+}
+
+// The kinds which are registered as exports at runtime
+fn is_runtime_export(kind: &TypeDefKind) -> bool {
+  matches!(
+    kind,
+    TypeDefKind::Const
+      | TypeDefKind::Enum
+      | TypeDefKind::Fn
+      | TypeDefKind::Struct
+      | TypeDefKind::StringEnum
+  )
+}
+
+// Move the only runtime export of a namespace to top level, and prefix the namespace to its name.
+// Only the declaration is changed, the type only members are kept in the namespace
+fn flatten_singleton_namespace(grouped_defs: &mut HashMap<String, Vec<TypeDefLine>>) {
+  let mut singletons = grouped_defs
+    .iter()
+    .filter(|(namespace, defs)| {
+      namespace.as_str() != TOP_LEVEL_NAMESPACE
+        && defs.iter().filter(|d| is_runtime_export(&d.kind)).count() == 1
+    })
+    .map(|(namespace, _)| namespace.clone())
+    .collect::<Vec<_>>();
+  singletons.sort();
+
+  let mut renamed = Vec::new();
+  for namespace in singletons {
+    let Some(defs) = grouped_defs.get_mut(&namespace) else {
+      continue;
+    };
+    let Some(index) = defs.iter().position(|d| is_runtime_export(&d.kind)) else {
+      continue;
+    };
+    let mut def = defs.remove(index);
+    let name = format!("{}_{}", namespace, def.name);
+    // the kept members are still in the namespace
+    for kept in defs.iter() {
+      let bare = Regex::new(&format!(r"(^|[^.\w$]){}\b", regex::escape(&kept.name))).unwrap();
+      def.def = bare
+        .replace_all(&def.def, format!("${{1}}{}.{}", namespace, kept.name))
+        .to_string();
+    }
+    def.def = match def.kind {
+      TypeDefKind::Fn => def.def.replacen(
+        &format!("function {}", def.name),
+        &format!("function {}", name),
+        1,
+      ),
+      TypeDefKind::Const => def.def.replacen(
+        &format!("const {}:", def.name),
+        &format!("const {}:", name),
+        1,
+      ),
+      // struct and enum are printed with the name
+      _ => def.def,
+    };
+    def.alias = def.alias.map(|a| format!("{}_{}", namespace, a));
+    renamed.push((namespace.clone(), def.name.clone(), name.clone()));
+    def.name = name;
+    def.original_name = None;
+    def.js_mod = None;
+    if defs.is_empty() {
+      grouped_defs.remove(&namespace);
+    }
+    grouped_defs
+      .entry(TOP_LEVEL_NAMESPACE.to_string())
+      .or_default()
+      .push(def);
+  }
+
+  // `ns.Foo` everywhere, and `Foo` in the kept members of `ns` refer to the hoisted one
+  for (namespace, old, new) in renamed {
+    let qualified = Regex::new(&format!(
+      r"\b{}\.{}\b",
+      regex::escape(&namespace),
+      regex::escape(&old)
+    ))
+    .unwrap();
+    let bare = Regex::new(&format!(r"(^|[^.\w$]){}\b", regex::escape(&old))).unwrap();
+    for (ns, defs) in grouped_defs.iter_mut() {
+      for def in defs.iter_mut().filter(|d| d.name != new) {
+        def.def = replace_type_references(&def.def, &qualified, &new);
+        if ns == &namespace {
+          def.def = bare
+            .replace_all(&def.def, format!("${{1}}{}", new))
+            .to_string();
+        }
+      }
+    }
+  }
+}
+
+// `field?: T` => `field?: T | undefined`, parameters of methods and constructor are not changed
+// because `exactOptionalPropertyTypes` only applies to properties
+fn with_explicit_undefined(def: &str) -> String {
+  let property_reg =
+    Regex::new(r#"^(\s*(?:readonly\s+)?(?:[\w$]+|'[^']*'|"[^"]*")\?:\s*)(.+?)(;?)$"#).unwrap();
+  let undefined_reg = Regex::new(r"(^|\|)\s*undefined\s*($|\|)").unwrap();
+  def
+    .split('\n')
+    .map(|l| match property_reg.captures(l) {
+      Some(caps) if !undefined_reg.is_match(&caps[2]) => {
+        let ty = &caps[2];
+        // function type should be wrapped
+        let ty = if ty.contains("=>") {
+          format!("({})", ty)
+        } else {
+          ty.to_string()
+        };
+        format!("{}{} | undefined{}", &caps[1], ty, &caps[3])
+      }
+      _ => l.to_string(),
+    })
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+// The declarations marked as global are moved out of their namespace
+fn take_global_defs(grouped_defs: &mut HashMap<String, Vec<TypeDefLine>>) -> Vec<TypeDefLine> {
+  let mut global_defs = Vec::new();
+  for defs in grouped_defs.values_mut() {
+    let (global, rest) = defs.drain(..).partition::<Vec<_>, _>(|d| d.global);
+    *defs = rest;
+    global_defs.extend(global);
+  }
+  grouped_defs.retain(|n, defs| n == TOP_LEVEL_NAMESPACE || !defs.is_empty());
+  global_defs.sort_by(|a, b| a.name.cmp(&b.name));
+  global_defs
+}
+
+// Declarations in `declare global` can't be exported, only the lines at the given indent are changed
+fn strip_export(src: &str, indent: usize) -> String {
+  let prefix = format!("{}export ", " ".repeat(indent));
+  src
+    .lines()
+    .map(|l| match l.strip_prefix(&prefix) {
+      Some(rest) => format!("{}{}", " ".repeat(indent), rest),
+      None => l.to_string(),
+    })
+    .collect::<Vec<_>>()
+    .join("\n")
+    + "\n"
+}
+
+fn export_declare(ambient: bool) -> String {
+  if ambient {
+    return String::from("export");
+  }
+
+  return String::from("export declare");
+}
+
+// Append `@throws` tag to the js doc
+fn with_throws_tag(js_doc: Option<String>) -> String {
+  const TAG: &str = " * @throws {Error} if the native call fails\n";
+  let doc = js_doc.unwrap_or_default();
+  let Some(inner) = doc
+    .trim_end()
+    .strip_prefix("/**")
+    .and_then(|d| d.strip_suffix("*/"))
+  else {
+    return format!("/**\n{} */\n", TAG);
+  };
+  if inner.contains('\n') {
+    format!("/**{}{} */\n", inner.trim_end_matches(' '), TAG)
+  } else {
+    format!("/**\n *{}\n{} */\n", inner.trim_end(), TAG)
+  }
+}
+
+// Helper function to format the string with the correct indentation
+fn pretty_print(line: &TypeDefLine, const_enum: bool, indent: usize, ambient: bool) -> String {
+  let mut s = line.js_doc.clone().unwrap_or_default();
+  match line.kind {
+    TypeDefKind::Interface => {
+      s += &format!(
+        "export interface {} {{\n{}\n}}",
+        line.name,
+        object_members(line)
+      );
+    }
+    // `enum X {}` is valid in TypeScript, don't leave an empty line in body
+    TypeDefKind::Enum | TypeDefKind::StringEnum if is_empty_enum(line) => {
+      let enum_name = if const_enum { "const enum" } else { "enum" };
+      if line.kind == TypeDefKind::StringEnum && !const_enum {
+        s += &format!("export type {} = never;", line.name);
+      } else {
+        s += &format!(
+          "{} {} {} {{}}",
+          export_declare(ambient),
+          enum_name,
+          line.name
+        );
+      }
+    }
+    TypeDefKind::Enum => {
+      let enum_name = if const_enum { "const enum" } else { "enum" };
+      s += &format!(
+        "{} {} {} {{\n{}\n}}",
+        export_declare(ambient),
+        enum_name,
+        line.name,
+        line.def
+      );
+    }
+    TypeDefKind::Type => {
+      s += &format!("export type {} = \n{}", line.name, line.def);
+    }
+    TypeDefKind::TypeAlias => {
+      s += &format!("export type {} = {}", line.name, line.def);
+    }
+    TypeDefKind::StringEnum => match const_enum {
+      true => {
+        s += &format!(
+          "{} const enum {} {{\n{}\n}}",
+          export_declare(ambient),
+          line.name,
+          enum_members_def(line)
+        );
+      }
+      false => {
+        s += &format!("export type {} = {};", line.name, string_enum_union(line));
+      }
+    },
+    TypeDefKind::Struct => {
+      s += &format!(
+        "{} class {} {{\n{}\n}}",
+        export_declare(ambient),
+        line.name,
+        members_with_index_signature(line)
+      );
+      if let Some(original_name) = &line.original_name {
+        if original_name != &line.name {
+          s += &format!("\nexport type {} = {}", original_name, line.name);
+        }
+      }
+    }
+    TypeDefKind::Fn => {
+      s += &format!("{} {}", export_declare(ambient), line.def);
+    }
+    // `export const NAME: Type`, the wide type is replaced with the literal type
+    TypeDefKind::Const if line.literal.is_some() => {
+      let literal = line.literal.as_deref().unwrap_or_default();
+      match line.def.split_once(": ") {
+        Some((head, _)) => s += &format!("{}: {}", head, literal),
+        None => s += &line.def,
+      }
+    }
+    _ => {
+      s += &line.def;
+    }
+  }
+  if let Some(alias) = alias_declaration(line, const_enum, ambient) {
+    s += "\n";
+    s += &alias;
+  }
+
+  correct_string_indent(&s, indent)
+}
+
+// The renamed declaration is still exported with the deprecated old name
+fn alias_declaration(line: &TypeDefLine, const_enum: bool, ambient: bool) -> Option<String> {
+  let alias = line.alias.as_ref()?;
+  let mut s = format!("/** @deprecated Use `{}` instead. */\n", line.name);
+  let value = format!(
+    "{} const {}: typeof {}",
+    export_declare(ambient),
+    alias,
+    line.name
+  );
+  let ty = format!("export type {} = {}", alias, line.name);
+  match line.kind {
+    TypeDefKind::Fn | TypeDefKind::Const => s += &value,
+    // const enum has no runtime object in TypeScript
+    TypeDefKind::Enum | TypeDefKind::StringEnum if const_enum => s += &ty,
+    TypeDefKind::Struct | TypeDefKind::Enum => s += &format!("{}\n{}", value, ty),
+    _ => s += &ty,
+  }
+  Some(s)
+}
+
+// The enum without any variant
+fn is_empty_enum(line: &TypeDefLine) -> bool {
+  line.members.is_empty() && line.def.trim().is_empty()
+}
+
+// Index signature is placed before all members of interface or class
+fn members_with_index_signature(line: &TypeDefLine) -> String {
+  match &line.index_signature {
+    Some(index_signature) if line.def.is_empty() => index_signature.clone(),
+    Some(index_signature) => format!("{}\n{}", index_signature, line.def),
+    None => line.def.clone(),
+  }
+}
+
+// Plain object can't be constructed, drop the constructor from its members
+fn object_members(line: &TypeDefLine) -> String {
+  members_with_index_signature(line)
+    .lines()
+    .filter(|l| !l.trim_start().starts_with("constructor("))
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+// Render enum body from structured members, fallback to the raw def
+fn enum_members_def(line: &TypeDefLine) -> String {
+  if line.members.is_empty() {
+    return line.def.clone();
+  }
+  line
+    .members
+    .iter()
+    .map(|m| {
+      format!(
+        "{}{} = {}",
+        m.js_doc.as_deref().unwrap_or_default(),
+        m.name,
+        m.value
+      )
+    })
+    .collect::<Vec<_>>()
+    .join(",\n")
+}
+
+// Render string enum as union of literals, members' doc will be kept
+fn string_enum_union(line: &TypeDefLine) -> String {
+  let members = if line.members.is_empty() {
+    // def is `A = 'a',\n B = 'b'`, and doc may be placed before each member
+    line
+      .def
+      .split(",\n")
+      .filter_map(|m| {
+        let m = m.rsplit_once("*/").map_or(m, |(_, m)| m);
+        m.split_once('=').map(|(name, value)| TypeDefMember {
+          name: name.trim().to_string(),
+          value: value.trim().to_string(),
+          js_doc: None,
+        })
+      })
+      .collect::<Vec<_>>()
+  } else {
+    line.members.clone()
+  };
+
+  if members
+    .iter()
+    .all(|m| m.js_doc.as_deref().unwrap_or_default().is_empty())
+  {
+    return members
+      .iter()
+      .map(|m| m.value.as_str())
+      .collect::<Vec<_>>()
+      .join(" | ");
+  }
+
+  members.iter().fold(String::new(), |mut union, m| {
+    union.push('\n');
+    union.push_str(m.js_doc.as_deref().unwrap_or_default());
+    union.push_str("| ");
+    union.push_str(&m.value);
+    union
+  })
+}
+
+// Malformed def with unbalanced brackets should not produce a huge indent
+const MAX_BRACKET_DEPTH: usize = 32;
+
+fn correct_string_indent(src: &str, indent: usize) -> String {
+  // the indent of every line is at most `indent + MAX_BRACKET_DEPTH * 2`
+  let line_count = src.lines().count();
+  let mut result = String::with_capacity(src.len() + line_count * (indent + 4));
+  let mut bracket_depth = 0;
+  for line in src.lines() {
+    let line = line.trim();
+    if line.is_empty() {
+      result.push('\n');
+      continue;
+    }
+
+    let is_in_multiline_comment = line.starts_with('*');
     let is_closing_bracket = line.ends_with('}');
     let is_opening_bracket = line.ends_with('{');
     let is_type_declearation = line.ends_with('=');
     let is_type_variable = line.starts_with('|');
 
     let right_indent = if (is_opening_bracket || is_type_declearation) && !is_in_multiline_comment {
-      bracket_depth += 1;
+      bracket_depth = (bracket_depth + 1).min(MAX_BRACKET_DEPTH);
       indent + (bracket_depth - 1) * 2
     } else {
       if is_closing_bracket && bracket_depth > 0 && !is_in_multiline_comment && !is_type_variable {
@@ -324,36 +1979,1508 @@ fn correct_string_indent(src: &str, indent: usize) -> String {
       indent + bracket_depth * 2
     };
 
-    let indented_line = if is_in_multiline_comment {
-      format!("{} {}", " ".repeat(right_indent + 1), line)
+    // ` * doc` is aligned with `/**`
+    let right_indent = if is_in_multiline_comment {
+      right_indent + 2
     } else {
-      format!("{}{}", " ".repeat(right_indent), line)
+      right_indent
     };
-
-    result.push_str(&indented_line);
+    result.extend(std::iter::repeat_n(' ', right_indent));
+    result.push_str(line);
     result.push('\n');
   }
 
   result
 }
-pub fn generate_d_ts_file(ctx: &Context) -> anyhow::Result<()> {
-  let tmp_file = env::var("TYPE_DEF_TMP_PATH")
-    .map_err(|_e| Error::msg("Failed to get the TYPE_DEF_TMP_PATH environment variable"))?;
-  if !Path::new(tmp_file.as_str()).is_file() {
-    return Ok(());
+
+// One name per line, or a json array in one line
+fn format_exports(exports: &[String], format: ExportsFormat) -> String {
+  match format {
+    ExportsFormat::Lines => exports.iter().map(|e| format!("{}\n", e)).collect(),
+    ExportsFormat::Json => serde_json::to_string(exports).unwrap_or_default() + "\n",
   }
-  let (dts, _exports) = process_type_def(&tmp_file, true, "");
-  let dest_file_path = ctx.dist.join("index.d.ts");
+}
 
-  let extra_header = ctx
+/// print the export names of the native module instead of writing the .d.ts file
+pub fn print_exports(ctx: &Context, format: ExportsFormat) -> anyhow::Result<()> {
+  let tmp_file = env::var("TYPE_DEF_TMP_PATH")
+    .map_err(|_e| Error::msg("Failed to get the TYPE_DEF_TMP_PATH environment variable"))?;
+  let exports = if Path::new(tmp_file.as_str()).is_file() {
+    process_type_def(&tmp_file, true, "", &ctx.dts_options)?.1
+  } else {
+    vec![]
+  };
+  print!("{}", format_exports(&exports, format));
+  Ok(())
+}
+
+/// return the path of generated .d.ts file
+pub fn generate_d_ts_file(ctx: &Context) -> anyhow::Result<Option<PathBuf>> {
+  let tmp_file = env::var("TYPE_DEF_TMP_PATH")
+    .map_err(|_e| Error::msg("Failed to get the TYPE_DEF_TMP_PATH environment variable"))?;
+  if !Path::new(tmp_file.as_str()).is_file() {
+    return Ok(None);
+  }
+  let (dts, files, exports) = if ctx.dts_options.split {
+    process_type_def_split(&tmp_file, true, "", &ctx.dts_options)?
+  } else {
+    let (dts, exports) = process_type_def(&tmp_file, true, "", &ctx.dts_options)?;
+    (dts, vec![], exports)
+  };
+  let dest_file_path = ctx
+    .dts_options
+    .out
+    .clone()
+    .unwrap_or(ctx.dist.join("index.d.ts"));
+  if let Some(parent) = dest_file_path.parent() {
+    create_dist_dir!(parent);
+  }
+
+  let extra_header = ctx
     .template
     .as_ref()
     .map(|t| t.header.as_ref())
     .and_then(|h| h.map(|s| s.as_str()))
     .unwrap_or("");
 
-  let write_content = format!("{}{}{}", DEFAULT_TYPE_DEF_HEADER, extra_header, dts);
+  write_dts_bundle(ctx, &dest_file_path, extra_header, &dts, &files)?;
+
+  if ctx.dts_options.export_equals.is_some() {
+    let library = ctx
+      .package
+      .as_ref()
+      .map(|p| super::cdylib_target(p).map_or(p.name.clone(), |t| t.name.clone()))
+      .unwrap_or_default()
+      .replace('-', "_");
+    let entry = export_equals_entry(&format!("lib{}.so", library), &exports);
+    let js_file_path = ctx
+      .dts_options
+      .js_out
+      .clone()
+      .unwrap_or(dest_file_path.with_file_name("index.js"));
+    if let Some(parent) = js_file_path.parent() {
+      create_dist_dir!(parent);
+    }
+    write_atomic(&js_file_path, entry.as_bytes())?;
+    println!("Create {} succeed.", js_file_path.display());
+    if ctx.dts_options.js_out.is_some() {
+      update_package_field(ctx, "main", &js_file_path)?;
+    }
+  }
+
+  if let Some(dir) = &ctx.dts_options.types_package {
+    create_dist_dir!(dir);
+    write_dts_bundle(ctx, &dir.join("index.d.ts"), extra_header, &dts, &files)?;
+    let package_json = types_package_json(ctx, &files);
+    create_project_file!(package_json, dir.join("package.json"), "package.json");
+  }
+
+  if ctx.dts_options.emit_map {
+    let map_file_path = dest_file_path.with_file_name(format!(
+      "{}.map.json",
+      dest_file_path
+        .file_name()
+        .map(|f| f.to_string_lossy().to_string())
+        .unwrap_or_default()
+    ));
+    let map = dts_map(&tmp_file, &ctx.dts_options);
+    create_project_file!(map, map_file_path, "index.d.ts.map.json");
+  }
+
+  if ctx.dts_options.out.is_some() {
+    update_package_field(ctx, "types", &dest_file_path)?;
+  }
+  Ok(Some(dest_file_path))
+}
+
+// The hashes of written .d.ts files are kept in the cache dir
+fn dts_hash_file(ctx: &Context) -> Option<PathBuf> {
+  Some(ctx.cache_dir.join(DTS_HASH)).filter(|_| !ctx.cache_dir.as_os_str().is_empty())
+}
+
+// The options which change the written content are hashed too
+fn dts_content_hash(ctx: &Context, content: &str) -> String {
+  let options = &ctx.dts_options;
+  let mut hasher = Sha256::new();
+  hasher.update(format!(
+    "lint_ignore={};dual={};transform={};formatter={}\n",
+    options.lint_ignore.name(),
+    options.dual,
+    options.transform.as_deref().unwrap_or_default(),
+    options.formatter.as_deref().unwrap_or_default()
+  ));
+  hasher.update(content);
+  format!("{:x}", hasher.finalize())
+}
+
+// Write the index .d.ts file, and every namespace into `types/<namespace>.d.ts` for split mode
+// a file is only rewritten when its hash changes, so the mtime of unchanged files is kept for watchers
+fn write_dts_bundle(
+  ctx: &Context,
+  dest: &Path,
+  extra_header: &str,
+  dts: &str,
+  files: &[(String, String)],
+) -> anyhow::Result<()> {
+  let hash_file = dts_hash_file(ctx);
+  let mut hashes: HashMap<String, String> = hash_file
+    .as_ref()
+    .and_then(|f| fs::read_to_string(f).ok())
+    .and_then(|c| serde_json::from_str(&c).ok())
+    .unwrap_or_default();
+  let types_dir = dest
+    .parent()
+    .filter(|_| ctx.dts_options.split)
+    .map(|p| p.join("types"));
+
+  if let Some(types_dir) = &types_dir {
+    // remove the files of the namespaces which don't exist anymore,
+    // only the ones written before are recorded in the hashes, others may be written by user
+    let current = files
+      .iter()
+      .map(|(n, _)| types_dir.join(format!("{}.d.ts", n)).display().to_string())
+      .collect::<HashSet<_>>();
+    let stale = hashes
+      .keys()
+      .filter(|k| Path::new(k).parent() == Some(types_dir.as_path()) && !current.contains(*k))
+      .cloned()
+      .collect::<Vec<_>>();
+    for key in stale {
+      let path = PathBuf::from(&key);
+      for file in [esm_dts_path(&path), path] {
+        if file.is_file() {
+          fs::remove_file(&file)?;
+        }
+      }
+      hashes.remove(&key);
+    }
+  }
+
+  let mut write = |path: &Path, content: String| -> anyhow::Result<()> {
+    let key = path.display().to_string();
+    let hash = dts_content_hash(ctx, &content);
+    let unchanged = hashes.get(&key) == Some(&hash)
+      && path.is_file()
+      && (!ctx.dts_options.dual || esm_dts_path(path).is_file());
+    if !unchanged {
+      write_dts_file(ctx, path, content)?;
+      hashes.insert(key, hash);
+    }
+    Ok(())
+  };
+
+  write(dest, format!("{}{}", extra_header, dts))?;
+
+  if let Some(types_dir) = &types_dir {
+    create_dist_dir!(types_dir);
+    for (namespace, content) in files {
+      write(
+        &types_dir.join(format!("{}.d.ts", namespace)),
+        format!("{}{}", extra_header, content),
+      )?;
+    }
+  }
+
+  if hash_file.is_some() {
+    write_cache(&ctx.cache_dir, DTS_HASH, &serde_json::to_value(&hashes)?)?;
+  }
+  Ok(())
+}
+
+// `package.json` of the types package, the name is `<crate>-types`
+fn types_package_json(ctx: &Context, files: &[(String, String)]) -> String {
+  let (name, version) = ctx
+    .package
+    .as_ref()
+    .map(|p| (format!("{}-types", p.name), p.version.to_string()))
+    .unwrap_or((String::from("types"), String::from("0.0.0")));
+  let mut package_files = vec![String::from("index.d.ts")];
+  if ctx.dts_options.dual {
+    package_files.push(String::from("index.d.mts"));
+  }
+  if ctx.dts_options.split && !files.is_empty() {
+    package_files.push(String::from("types"));
+  }
+  let package = serde_json::json!({
+    "name": name,
+    "version": version,
+    "types": "index.d.ts",
+    "files": package_files,
+  });
+  serde_json::to_string_pretty(&package).unwrap_or_default() + "\n"
+}
+
+// `src/a/mod.rs` -> `a`, `src/a/b.rs` -> `a::b`, the inline modules can't be resolved
+fn module_path_of(krate: &str, file: &str) -> Option<String> {
+  let path = file.strip_prefix("src/")?.strip_suffix(".rs")?;
+  let path = path.strip_suffix("/mod").unwrap_or(path);
+  let krate = krate.replace('-', "_");
+  if path == "lib" || path == "main" {
+    return Some(krate);
+  }
+  Some(format!("{}::{}", krate, path.replace('/', "::")))
+}
+
+// Map every declaration to the rust source, namespaced declaration is keyed by `namespace.name`
+fn dts_map(intermediate_type_file: &str, options: &DtsOptions) -> String {
+  let defs = read_intermediate_type_file(intermediate_type_file, !options.no_struct_first);
+  let mut grouped_defs = preprocess_type_def(defs, !options.no_struct_first);
+  take_module_docs(&mut grouped_defs);
+  if options.flatten_singletons {
+    flatten_singleton_namespace(&mut grouped_defs);
+  }
+
+  let mut declarations = BTreeMap::new();
+  for (namespace, defs) in grouped_defs {
+    for def in defs {
+      let Some(mut source) = def.source else {
+        continue;
+      };
+      source.module = source
+        .file
+        .as_deref()
+        .and_then(|f| module_path_of(&source.krate, f));
+      let name = if namespace == TOP_LEVEL_NAMESPACE {
+        def.name
+      } else {
+        format!("{}.{}", namespace, def.name)
+      };
+      declarations.insert(name, source);
+    }
+  }
+  let map = serde_json::json!({
+    "version": 1,
+    "declarations": declarations,
+  });
+  serde_json::to_string_pretty(&map).unwrap_or_default()
+}
+
+// Write the .d.ts file, and the .d.mts file with ESM import style for dual mode
+fn write_dts_file(ctx: &Context, path: &Path, content: String) -> anyhow::Result<()> {
+  let header = type_def_header(ctx.dts_options.lint_ignore);
+  let mut write_content = format!("{}{}", header, content);
+  if let Some(command) = &ctx.dts_options.transform {
+    write_content = transform_dts(command, write_content)?;
+  }
+  if let Some(command) = &ctx.dts_options.formatter {
+    write_content = format_dts(command, write_content);
+  }
+  create_project_file!(write_content, path, "index.d.ts");
+
+  if ctx.dts_options.dual {
+    let mut esm_content = format!("{}{}", header, esm_preamble(&content));
+    if let Some(command) = &ctx.dts_options.transform {
+      esm_content = transform_dts(command, esm_content)?;
+    }
+    if let Some(command) = &ctx.dts_options.formatter {
+      esm_content = format_dts(command, esm_content);
+    }
+    let esm_file_path = esm_dts_path(path);
+    create_project_file!(esm_content, esm_file_path, "index.d.mts");
+  }
+  Ok(())
+}
+
+// `index.d.ts` -> `index.d.mts`
+fn esm_dts_path(dts: &Path) -> PathBuf {
+  let file_name = dts
+    .file_name()
+    .map(|f| f.to_string_lossy().to_string())
+    .unwrap_or_default();
+  let stem = file_name
+    .strip_suffix(".d.ts")
+    .or(file_name.strip_suffix(".ts"))
+    .unwrap_or(&file_name);
+  dts.with_file_name(format!("{}.d.mts", stem))
+}
+
+// Convert the references and relative imports in header to ESM style
+fn esm_preamble(header: &str) -> String {
+  let reference_reg =
+    Regex::new(r#"(///\s*<reference\s+path\s*=\s*["'][^"']*)\.d\.ts(["'])"#).unwrap();
+  let import_reg =
+    Regex::new(r#"(\bfrom\s+|\bimport\s*\(\s*|\bimport\s+)(["'])(\.{1,2}/[^"']*?)(\.js)?(["'])"#)
+      .unwrap();
+  let header = reference_reg.replace_all(header, "$1.d.mts$2");
+  import_reg
+    .replace_all(&header, |caps: &regex::Captures| {
+      format!("{}{}{}.mjs{}", &caps[1], &caps[2], &caps[3], &caps[5])
+    })
+    .to_string()
+}
+
+/// point the field of package, `types` or `main`, to the custom output file
+fn update_package_field(ctx: &Context, field: &str, file: &Path) -> anyhow::Result<()> {
+  let package = ctx.pwd.join("package");
+  // dist will be copied to `package/libs` by artifact
+  let value = if let Ok(p) = file.strip_prefix(&package) {
+    p.to_path_buf()
+  } else if let Ok(p) = file.strip_prefix(&ctx.dist) {
+    Path::new("libs").join(p)
+  } else {
+    eprintln!(
+      "{}: {} is outside of package and dist folder, the {} field of package can't be updated.",
+      "Warning".bold().yellow(),
+      file.display(),
+      field
+    );
+    return Ok(());
+  };
+  let value = value.to_string_lossy().replace('\\', "/");
 
-  create_project_file!(write_content, dest_file_path, "index.d.ts");
+  for name in ["oh-package.json5", "package.json"] {
+    let path = package.join(name);
+    if !path.is_file() {
+      continue;
+    }
+    let content = std::fs::read_to_string(&path)?;
+    let Some(range) = top_level_string_field(&content, field) else {
+      continue;
+    };
+    let old = &content[range.start + 1..range.end - 1];
+    // the ArkTS entry of HAR package is kept, the JS entry is loaded by path
+    if field == "main" && (old.ends_with(".ets") || old.ends_with(".ts")) {
+      eprintln!(
+        "{}: the main field of package/{} is the ArkTS entry {}, it isn't updated.",
+        "Warning".bold().yellow(),
+        name,
+        old
+      );
+      continue;
+    }
+    if old != value {
+      let new_content = format!(
+        "{}{}{}",
+        &content[..range.start],
+        serde_json::to_string(&value)?,
+        &content[range.end..]
+      );
+      write_atomic(&path, new_content.as_bytes())?;
+      println!("Update {} field of package/{} to {}.", field, name, value);
+    }
+  }
   Ok(())
 }
+
+/// the range of the quoted string value of a top level field in json or json5,
+/// the nested objects, strings and comments are skipped
+fn top_level_string_field(content: &str, field: &str) -> Option<std::ops::Range<usize>> {
+  let bytes = content.as_bytes();
+  let string_end = |start: usize| {
+    let mut i = start + 1;
+    while i < bytes.len() {
+      match bytes[i] {
+        b'\\' => i += 1,
+        c if c == bytes[start] => return i + 1,
+        _ => {}
+      }
+      i += 1;
+    }
+    bytes.len()
+  };
+
+  let mut depth = 0;
+  // the top level key whose value isn't read yet
+  let mut key: Option<&str> = None;
+  let mut i = 0;
+  while i < bytes.len() {
+    match bytes[i] {
+      b'/' if bytes.get(i + 1) == Some(&b'/') => {
+        i = content[i..].find('\n').map_or(bytes.len(), |p| i + p);
+        continue;
+      }
+      b'/' if bytes.get(i + 1) == Some(&b'*') => {
+        i = content[i + 2..]
+          .find("*/")
+          .map_or(bytes.len(), |p| i + p + 4);
+        continue;
+      }
+      b'"' | b'\'' => {
+        let end = string_end(i);
+        if depth == 1 {
+          match key.take() {
+            Some(k) if k == field => return Some(i..end),
+            Some(_) => {}
+            None => key = content.get(i + 1..end - 1),
+          }
+        }
+        i = end;
+        continue;
+      }
+      b'{' | b'[' => {
+        if depth == 1 {
+          key = None;
+        }
+        depth += 1;
+      }
+      b'}' | b']' => depth -= 1,
+      b',' if depth == 1 => key = None,
+      c if depth == 1 && key.is_none() && (c.is_ascii_alphabetic() || c == b'_' || c == b'$') => {
+        let len = content[i..]
+          .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '$'))
+          .unwrap_or(content.len() - i);
+        key = Some(&content[i..i + len]);
+        i += len;
+        continue;
+      }
+      _ => {}
+    }
+    i += 1;
+  }
+  None
+}
+
+/// pipe the .d.ts content to the command's stdin and use its stdout as the result
+fn transform_dts(command: &str, content: String) -> anyhow::Result<String> {
+  pipe_dts("dts transform", command, content)
+}
+
+fn pipe_dts(tool: &str, command: &str, content: String) -> anyhow::Result<String> {
+  #[cfg(target_os = "windows")]
+  let mut cmd = Command::new("cmd");
+  #[cfg(target_os = "windows")]
+  cmd.args(["/C", command]);
+  #[cfg(not(target_os = "windows"))]
+  let mut cmd = Command::new("sh");
+  #[cfg(not(target_os = "windows"))]
+  cmd.args(["-c", command]);
+
+  let mut child = cmd
+    .stdin(Stdio::piped())
+    .stdout(Stdio::piped())
+    .spawn()
+    .map_err(|e| Error::msg(format!("Failed to run {} `{}`: {}", tool, command, e)))?;
+
+  // write in another thread, avoid blocking when the output is too large
+  let mut stdin = child
+    .stdin
+    .take()
+    .ok_or(Error::msg(format!("Failed to open stdin of {}.", tool)))?;
+  let writer = std::thread::spawn(move || stdin.write_all(content.as_bytes()));
+
+  let output = child.wait_with_output()?;
+  writer
+    .join()
+    .map_err(|_| Error::msg(format!("Failed to write .d.ts to {}.", tool)))??;
+
+  if !output.status.success() {
+    return Err(Error::msg(format!(
+      "{} `{}` failed with {}.",
+      tool, command, output.status
+    )));
+  }
+  String::from_utf8(output.stdout).map_err(|_| {
+    Error::msg(format!(
+      "The output of {} `{}` is not utf-8.",
+      tool, command
+    ))
+  })
+}
+
+/// format the .d.ts content with external formatter, keep the unformatted content if it fails
+fn format_dts(command: &str, content: String) -> String {
+  match pipe_dts("dts formatter", command, content.clone()) {
+    Ok(formatted) if !formatted.trim().is_empty() => formatted,
+    Ok(_) => {
+      eprintln!(
+        "{}: dts formatter `{}` has no output, the unformatted .d.ts is kept.",
+        "Warning".bold().yellow(),
+        command
+      );
+      content
+    }
+    Err(e) => {
+      eprintln!(
+        "{}: {} The unformatted .d.ts is kept.",
+        "Warning".bold().yellow(),
+        e
+      );
+      content
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::{
+    correct_string_indent, dts_map, esm_dts_path, esm_preamble, export_equals_entry,
+    format_exports, is_valid_enum_member, is_valid_js_identifier, normalize_either,
+    process_type_def, process_type_def_split, top_level_string_field, type_def_header,
+    types_package_json, update_package_field, write_dts_bundle, DtsOptions, ExportsFormat,
+    ExternalType, LintIgnore, NamespaceKeyword, OptionalStyle, Polyfill, MAX_BRACKET_DEPTH,
+  };
+  #[cfg(not(target_os = "windows"))]
+  use super::{format_dts, transform_dts};
+  use crate::build::Context;
+  use crate::util::{test_dir, test_file};
+  use std::fs;
+  use std::path::Path;
+
+  fn process_fixture_with_options(
+    name: &str,
+    lines: &[&str],
+    const_enum: bool,
+    options: &DtsOptions,
+  ) -> (String, Vec<String>) {
+    let (_dir, file) = test_file(&format!("{}.napi_type_def.tmp", name), &lines.join("\n"));
+    process_type_def(file.to_str().unwrap(), const_enum, "", options).unwrap()
+  }
+
+  fn process_fixture(name: &str, lines: &[&str], const_enum: bool) -> (String, Vec<String>) {
+    process_fixture_with_options(name, lines, const_enum, &DtsOptions::default())
+  }
+
+  #[test]
+  fn test_type_alias() {
+    let (dts, exports) = process_fixture(
+      "type-alias",
+      &[
+        r#"{"kind": "type_alias", "name": "Id", "js_doc": "/** identifier */\n", "def": "number", "original_name": "Id"}"#,
+        r#"{"kind": "type_alias", "name": "Name", "js_doc": "", "def": "string | undefined | null", "original_name": "Name", "js_mod": "user"}"#,
+      ],
+      true,
+    );
+    assert!(dts.contains("/** identifier */\nexport type Id = number\n"));
+    assert!(
+      dts.contains("export namespace user {\n  export type Name = string | undefined | null\n")
+    );
+    // only the namespace is registered at runtime
+    assert_eq!(exports, vec!["user"]);
+  }
+
+  const STRING_ENUM_FIXTURE: &str = r#"{"kind": "string_enum", "name": "Kind", "js_doc": "", "def": "/** first */\nA = 'a',\n B = 'b, c'", "original_name": "Kind", "members": [{"name": "A", "value": "'a'", "js_doc": "/** first */\n"}, {"name": "B", "value": "'b, c'", "js_doc": ""}]}"#;
+
+  #[test]
+  fn test_string_enum_union() {
+    let (dts, exports) = process_fixture("string-enum-union", &[STRING_ENUM_FIXTURE], false);
+    assert_eq!(
+      dts,
+      "export type Kind =\n  /** first */\n  | 'a'\n  | 'b, c';\n\n"
+    );
+    assert_eq!(exports, vec!["Kind"]);
+
+    let (dts, _) = process_fixture(
+      "string-enum-union-legacy",
+      &[r#"{"kind": "string_enum", "name": "Kind", "js_doc": "", "def": "A = 'a',\n B = 'b'"}"#],
+      false,
+    );
+    assert_eq!(dts, "export type Kind = 'a' | 'b';\n\n");
+  }
+
+  #[test]
+  fn test_string_enum_const() {
+    let (dts, _) = process_fixture("string-enum-const", &[STRING_ENUM_FIXTURE], true);
+    assert_eq!(
+      dts,
+      "export declare const enum Kind {\n  /** first */\n  A = 'a',\n  B = 'b, c'\n}\n\n"
+    );
+  }
+
+  #[test]
+  fn test_empty_enum() {
+    let lines = [
+      r#"{"kind": "enum", "name": "Empty", "js_doc": "", "def": ""}"#,
+      r#"{"kind": "string_enum", "name": "EmptyKind", "js_doc": "", "def": "", "members": []}"#,
+    ];
+    let (dts, exports) = process_fixture("empty-enum", &lines, false);
+    assert_eq!(
+      dts,
+      "export declare enum Empty {}\n\nexport type EmptyKind = never;\n\n"
+    );
+    assert_eq!(exports, vec!["Empty", "EmptyKind"]);
+
+    let (dts, _) = process_fixture("empty-enum-const", &lines, true);
+    assert_eq!(
+      dts,
+      "export declare const enum Empty {}\n\nexport declare const enum EmptyKind {}\n\n"
+    );
+  }
+
+  #[test]
+  fn test_export_equals() {
+    let lines = [
+      r#"{"kind": "fn", "name": "sum", "js_doc": "/** add two numbers */\n", "def": "function sum(a: number, b: number): number"}"#,
+      r#"{"kind": "struct", "name": "Counter", "original_name": "Counter", "js_doc": "", "def": "constructor()"}"#,
+      r#"{"kind": "fn", "name": "hello", "js_doc": "", "def": "function hello(): string", "js_mod": "utils"}"#,
+      r#"{"kind": "interface", "name": "Window", "js_doc": "", "def": "native: boolean", "global": true}"#,
+    ];
+    let options = DtsOptions {
+      export_equals: Some(String::from("Native")),
+      ..Default::default()
+    };
+    let (dts, exports) = process_fixture_with_options("export-equals", &lines, true, &options);
+    assert_eq!(
+      dts,
+      r#"declare namespace Native {
+  /** add two numbers */
+  export function sum(a: number, b: number): number
+
+  export class Counter {
+    constructor()
+  }
+
+  export namespace utils {
+    export function hello(): string
+
+  }
+}
+
+declare global {
+  interface Window {
+    native: boolean
+  }
+
+}
+
+export = Native
+"#
+    );
+    assert_eq!(exports, vec!["sum", "Counter", "utils"]);
+    assert_eq!(
+      export_equals_entry("libnative.so", &exports),
+      r#"const binding = require('libnative.so')
+
+module.exports = {
+  sum: binding.sum,
+  Counter: binding.Counter,
+  utils: binding.utils,
+}
+"#
+    );
+
+    let options = DtsOptions {
+      export_equals: Some(String::from("native-module")),
+      ..Default::default()
+    };
+    let (_dir, file) = test_file("export-equals-invalid.napi_type_def.tmp", &lines.join("\n"));
+    assert!(process_type_def(file.to_str().unwrap(), true, "", &options).is_err());
+  }
+
+  #[test]
+  fn test_update_package_field() {
+    let content = r#"{
+  // main: "comment.js",
+  "dependencies": { "main": "nested.js" },
+  'name': "a,\"b",
+  main: 'index.ets',
+}"#;
+    let range = top_level_string_field(content, "main").unwrap();
+    assert_eq!(&content[range], "'index.ets'");
+    assert_eq!(top_level_string_field(content, "types"), None);
+    assert_eq!(
+      top_level_string_field(content, "name").map(|r| r.len()),
+      Some(7)
+    );
+
+    let dir = test_dir();
+    let pwd = dir.path().to_path_buf();
+    fs::create_dir_all(pwd.join("package")).unwrap();
+    fs::write(pwd.join("package/oh-package.json5"), content).unwrap();
+    fs::write(
+      pwd.join("package/package.json"),
+      "{\n  \"dependencies\": { \"main\": \"x.js\" },\n  \"main\": \"index.js\",\n  \"types\": \"index.d.ts\"\n}",
+    )
+    .unwrap();
+    let ctx = Context {
+      pwd: pwd.clone(),
+      dist: pwd.join("dist"),
+      ..Default::default()
+    };
+    update_package_field(&ctx, "main", &pwd.join("dist/entry/main.js")).unwrap();
+    update_package_field(&ctx, "types", &pwd.join("package/types/index.d.ts")).unwrap();
+    // the ArkTS entry is kept
+    assert_eq!(
+      fs::read_to_string(pwd.join("package/oh-package.json5")).unwrap(),
+      content
+    );
+    assert_eq!(
+      fs::read_to_string(pwd.join("package/package.json")).unwrap(),
+      "{\n  \"dependencies\": { \"main\": \"x.js\" },\n  \"main\": \"libs/entry/main.js\",\n  \"types\": \"types/index.d.ts\"\n}"
+    );
+  }
+
+  #[test]
+  fn test_polyfill_modes() {
+    let lines = [
+      r#"{"kind": "fn", "name": "wrap", "js_doc": "", "def": "function wrap(signal?: AbortSignal | undefined | null): ExternalObject<number>"}"#,
+    ];
+    let abort_inline = "export class AbortSignal {";
+    let abort_import = "import type { AbortSignal } from '@ohos-rs/abort-controller'\n";
+    let external_inline = "export class ExternalObject<T> {";
+    let external_import = "import type { ExternalObject } from '@scope/types'\n";
+
+    for abort in ["inline", "import", "off"] {
+      for external in ["inline", "import:@scope/types", "off"] {
+        let options = DtsOptions {
+          polyfills: vec![
+            format!("abort-signal={}", abort).parse().unwrap(),
+            format!("external-object={}", external).parse().unwrap(),
+          ],
+          ..Default::default()
+        };
+        let (dts, _) = process_fixture_with_options(
+          &format!(
+            "polyfill-{}-{}",
+            abort,
+            external.replace([':', '@', '/'], "-")
+          ),
+          &lines,
+          true,
+          &options,
+        );
+        let case = format!("abort-signal={} external-object={}", abort, external);
+        assert!(dts.contains(
+          "export declare function wrap(signal?: AbortSignal | undefined | null): ExternalObject<number>"
+        ));
+        assert_eq!(dts.contains(abort_inline), abort == "inline", "{}", case);
+        assert_eq!(dts.contains(abort_import), abort == "import", "{}", case);
+        assert_eq!(
+          dts.contains(external_inline),
+          external == "inline",
+          "{}",
+          case
+        );
+        assert_eq!(
+          dts.contains(external_import),
+          external != "inline" && external != "off",
+          "{}",
+          case
+        );
+      }
+    }
+
+    assert_eq!(
+      "abort-signal=import".parse::<Polyfill>().unwrap().name(),
+      "abort-signal=import:@ohos-rs/abort-controller"
+    );
+    assert!("external-object=import".parse::<Polyfill>().is_err());
+    assert!("buffer=off".parse::<Polyfill>().is_err());
+    assert!("abort-signal=skip".parse::<Polyfill>().is_err());
+    assert!("abort-signal".parse::<Polyfill>().is_err());
+  }
+
+  #[test]
+  fn test_literal_const() {
+    let lines = [
+      r#"{"kind": "const", "name": "CONFIG", "original_name": "CONFIG", "js_doc": "", "def": "export const CONFIG: Config", "literal": "{ readonly version: 1; readonly name: \"x\" }"}"#,
+      r#"{"kind": "const", "name": "DEFAULT_COST", "original_name": "DEFAULT_COST", "js_doc": "", "def": "export const DEFAULT_COST: number"}"#,
+    ];
+    let (dts, _) = process_fixture("literal-const", &lines, true);
+    assert!(dts.contains("export const CONFIG: { readonly version: 1; readonly name: \"x\" }\n"));
+    assert!(dts.contains("export const DEFAULT_COST: number\n"));
+  }
+
+  #[test]
+  fn test_external_types() {
+    let lines = [
+      r#"{"kind": "interface", "name": "Base", "js_doc": "", "def": "id: number"}"#,
+      r#"{"kind": "fn", "name": "extend", "js_doc": "", "def": "function extend(base: Base): Base"}"#,
+      r#"{"kind": "fn", "name": "plain", "js_doc": "", "def": "function plain(): number"}"#,
+    ];
+    let options = DtsOptions {
+      external_types: vec![
+        "Base=@scope/base".parse().unwrap(),
+        "Unused=@scope/unused".parse().unwrap(),
+      ],
+      closed_world: true,
+      ..Default::default()
+    };
+    let (dts, exports) = process_fixture_with_options("external-types", &lines, true, &options);
+    assert!(dts.contains("import type { Base } from '@scope/base'\n"));
+    assert!(dts.contains("export declare function extend(base: Base): Base"));
+    assert!(!dts.contains("interface Base"));
+    assert!(!dts.contains("@scope/unused"));
+    assert!(!exports.contains(&String::from("Base")));
+
+    assert_eq!(
+      "Base = @scope/base".parse::<ExternalType>().unwrap().name(),
+      "Base=@scope/base"
+    );
+    assert!("Base".parse::<ExternalType>().is_err());
+    assert!("Base=".parse::<ExternalType>().is_err());
+    assert!("1Base=@scope/base".parse::<ExternalType>().is_err());
+  }
+
+  #[test]
+  fn test_print_exports() {
+    let lines = [
+      r#"{"kind": "fn", "name": "sum", "js_doc": "", "def": "function sum(a: number, b: number): number"}"#,
+      r#"{"kind": "struct", "name": "Counter", "original_name": "Counter", "js_doc": "", "def": "constructor()"}"#,
+      r#"{"kind": "interface", "name": "Options", "js_doc": "", "def": "id: number"}"#,
+      r#"{"kind": "fn", "name": "hello", "js_doc": "", "def": "function hello(): string", "js_mod": "utils"}"#,
+    ];
+    let (_, exports) = process_fixture("print-exports", &lines, true);
+    assert_eq!(
+      format_exports(&exports, ExportsFormat::Lines),
+      "sum\nCounter\nutils\n"
+    );
+    assert_eq!(
+      format_exports(&exports, ExportsFormat::Json),
+      "[\"sum\",\"Counter\",\"utils\"]\n"
+    );
+    assert!("yaml".parse::<ExportsFormat>().is_err());
+  }
+
+  #[test]
+  fn test_tuple_types() {
+    let lines = [
+      r#"{"kind": "fn", "name": "sumTuples", "js_doc": "", "def": "function sumTuples(pair: [number, string], nested: Array<[[number, boolean], string | undefined | null]>, maybe?: [number, number] | undefined | null): number"}"#,
+    ];
+    let (dts, exports) = process_fixture("tuple-types", &lines, true);
+    assert_eq!(
+      dts,
+      "export declare function sumTuples(pair: [number, string], nested: Array<[[number, boolean], string | undefined | null]>, maybe?: [number, number] | undefined | null): number\n\n"
+    );
+    assert_eq!(exports, vec!["sumTuples"]);
+  }
+
+  #[test]
+  fn test_optional_style() {
+    let lines = [
+      r#"{"kind": "interface", "name": "Config", "js_doc": "", "def": "name?: string\nreadonly onDone?: (err: Error) => void\nlevel?: number | undefined\ncount: number"}"#,
+      r#"{"kind": "struct", "name": "Task", "js_doc": "", "def": "label?: string\nconstructor(label?: string)\nrun(timeout?: number): void"}"#,
+    ];
+    let options = DtsOptions {
+      optional_style: OptionalStyle::Undefined,
+      ..Default::default()
+    };
+    let (dts, _) = process_fixture_with_options("optional-undefined", &lines, true, &options);
+    assert!(dts.contains("  name?: string | undefined\n"));
+    assert!(dts.contains("  readonly onDone?: ((err: Error) => void) | undefined\n"));
+    assert!(dts.contains("  level?: number | undefined\n"));
+    assert!(dts.contains("  count: number\n"));
+    assert!(dts.contains("  label?: string | undefined\n"));
+    assert!(dts.contains("  constructor(label?: string)\n"));
+    assert!(dts.contains("  run(timeout?: number): void\n"));
+
+    let (dts, _) = process_fixture("optional-omit", &lines, true);
+    assert!(dts.contains("  name?: string\n"));
+  }
+
+  #[test]
+  fn test_namespace_keyword() {
+    let lines = [
+      r#"{"kind": "fn", "name": "plus", "js_doc": "", "def": "function plus(a: number, b: number): number", "js_mod": "utils"}"#,
+      r#"{"kind": "const", "name": "ALIGNMENT", "js_doc": "", "def": "export const ALIGNMENT: number", "js_mod": "utils"}"#,
+    ];
+    let options = DtsOptions {
+      namespace_keyword: NamespaceKeyword::Module,
+      ..Default::default()
+    };
+    let (dts, exports) = process_fixture_with_options("namespace-keyword", &lines, true, &options);
+    let (default_dts, default_exports) = process_fixture("namespace-keyword-default", &lines, true);
+    assert!(dts.contains("export module utils {\n"));
+    assert!(dts.contains("  export function plus(a: number, b: number): number\n"));
+    assert!(default_dts.contains("export namespace utils {\n"));
+    assert_eq!(
+      dts.replace("export module", "export namespace"),
+      default_dts
+    );
+    assert_eq!(exports, default_exports);
+  }
+
+  #[test]
+  fn test_unbalanced_brackets_indent() {
+    let src = "a {\n".repeat(10_000);
+    let out = correct_string_indent(&src, 2);
+    assert_eq!(out.lines().count(), 10_000);
+    let max_indent = out
+      .lines()
+      .map(|l| l.len() - l.trim_start().len())
+      .max()
+      .unwrap();
+    assert_eq!(max_indent, 2 + (MAX_BRACKET_DEPTH - 1) * 2);
+    assert!(out.len() <= src.len() + 10_000 * (2 + MAX_BRACKET_DEPTH * 2));
+
+    // closing brackets still work after the cap
+    let out = correct_string_indent(&format!("{}}}\n}}\n", "a {\n".repeat(100)), 0);
+    assert!(out.ends_with(&format!("\n{}}}\n{}}}\n", " ".repeat(62), " ".repeat(60))));
+  }
+
+  #[test]
+  fn test_flatten_singletons() {
+    let lines = [
+      r#"{"kind": "fn", "name": "plus", "js_doc": "", "def": "function plus(a: number, b: number): number", "js_mod": "single"}"#,
+      r#"{"kind": "fn", "name": "plus", "js_doc": "", "def": "function plus(a: number, b: number): number", "js_mod": "multi"}"#,
+      r#"{"kind": "const", "name": "ALIGNMENT", "js_doc": "", "def": "export const ALIGNMENT: number", "js_mod": "multi"}"#,
+      r#"{"kind": "struct", "name": "Animal", "js_doc": "", "def": "info: Info", "original_name": "Animal", "js_mod": "zoo", "alias": "Pet"}"#,
+      r#"{"kind": "interface", "name": "Info", "js_doc": "", "def": "owner: Animal", "js_mod": "zoo"}"#,
+      r#"{"kind": "enum", "name": "Kind", "js_doc": "", "def": "A = 0", "original_name": "Kind", "js_mod": "kinds"}"#,
+      r#"{"kind": "fn", "name": "create", "js_doc": "", "def": "function create(): zoo.Animal"}"#,
+    ];
+    let options = DtsOptions {
+      flatten_singletons: true,
+      ..Default::default()
+    };
+    let (dts, exports) = process_fixture_with_options("flatten", &lines, true, &options);
+    assert!(dts.contains("export declare function single_plus(a: number, b: number): number\n"));
+    assert!(!dts.contains("export namespace single"));
+    assert!(dts.contains("export namespace multi {\n"));
+    assert!(dts.contains("  export function plus(a: number, b: number): number\n"));
+    assert!(dts.contains("export declare class zoo_Animal {\n  info: zoo.Info\n}\n"));
+    assert!(dts.contains("export declare const zoo_Pet: typeof zoo_Animal"));
+    // the interface has nothing at runtime, so it's kept in the namespace
+    assert!(
+      dts.contains("export namespace zoo {\n  export interface Info {\n    owner: zoo_Animal\n")
+    );
+    assert!(dts.contains("export declare const enum kinds_Kind {\n"));
+    assert!(dts.contains("export declare function create(): zoo_Animal\n"));
+    assert_eq!(
+      exports,
+      vec![
+        "create",
+        "kinds_Kind",
+        "single_plus",
+        "zoo_Animal",
+        "zoo_Pet",
+        "multi",
+        "zoo"
+      ]
+    );
+
+    let (dts, exports) = process_fixture("flatten-disabled", &lines, true);
+    assert!(dts.contains("export namespace single {\n"));
+    assert_eq!(exports, vec!["create", "kinds", "multi", "single", "zoo"]);
+  }
+
+  #[test]
+  fn test_module_doc() {
+    let lines = [
+      r#"{"kind": "mod", "name": "utils", "js_doc": "/**\n * Helpers for testing\n */\n", "def": "", "js_mod": "utils"}"#,
+      r#"{"kind": "fn", "name": "plus", "js_doc": "", "def": "function plus(a: number, b: number): number", "js_mod": "utils"}"#,
+      r#"{"kind": "mod", "name": "index", "js_doc": "/** The root module */\n", "def": ""}"#,
+      r#"{"kind": "fn", "name": "minus", "js_doc": "", "def": "function minus(a: number, b: number): number"}"#,
+    ];
+    let (dts, exports) = process_fixture("module-doc", &lines, true);
+    assert!(dts.starts_with("/** The root module */\n\nexport declare function minus("));
+    assert!(dts.contains("/**\n * Helpers for testing\n */\nexport namespace utils {\n"));
+    assert_eq!(exports, vec!["minus", "utils"]);
+  }
+
+  #[cfg(not(target_os = "windows"))]
+  #[test]
+  fn test_transform_dts() {
+    let dts = String::from("export declare function plus(): number\n");
+    assert_eq!(
+      transform_dts("sed 's/plus/add/'", dts.clone()).unwrap(),
+      "export declare function add(): number\n"
+    );
+    assert!(transform_dts("exit 1", dts).is_err());
+  }
+
+  #[cfg(not(target_os = "windows"))]
+  #[test]
+  fn test_format_dts() {
+    let dts = String::from("export declare function plus():number\n");
+    assert_eq!(
+      format_dts("sed 's/):/): /'", dts.clone()),
+      "export declare function plus(): number\n"
+    );
+    // fallback to the unformatted content
+    assert_eq!(format_dts("ohrs-formatter-not-found", dts.clone()), dts);
+    assert_eq!(format_dts("exit 1", dts.clone()), dts);
+  }
+
+  #[test]
+  fn test_namespace_order() {
+    let lines = [
+      r#"{"kind": "fn", "name": "a", "js_doc": "", "def": "function a(): void", "js_mod": "alpha"}"#,
+      r#"{"kind": "fn", "name": "b", "js_doc": "", "def": "function b(): void", "js_mod": "beta"}"#,
+      r#"{"kind": "fn", "name": "c", "js_doc": "", "def": "function c(): void", "js_mod": "core"}"#,
+      r#"{"kind": "fn", "name": "d", "js_doc": "", "def": "function d(): void", "js_mod": "delta"}"#,
+      r#"{"kind": "fn", "name": "top", "js_doc": "", "def": "function top(): void"}"#,
+    ];
+    let options = DtsOptions {
+      namespace_order: vec!["core".to_owned(), "delta".to_owned()],
+      ..Default::default()
+    };
+    let (_, exports) = process_fixture_with_options("namespace-order", &lines, true, &options);
+    assert_eq!(exports, vec!["top", "core", "delta", "alpha", "beta"]);
+
+    // the top level one is still the first without order, before the uppercase namespaces
+    let lines = [
+      r#"{"kind": "fn", "name": "a", "js_doc": "", "def": "function a(): void", "js_mod": "Alpha"}"#,
+      r#"{"kind": "fn", "name": "top", "js_doc": "", "def": "function top(): void"}"#,
+      r#"{"kind": "fn", "name": "b", "js_doc": "", "def": "function b(): void", "js_mod": "beta"}"#,
+    ];
+    let (dts, exports) = process_fixture("namespace-default-order", &lines, true);
+    assert_eq!(exports, vec!["top", "Alpha", "beta"]);
+    assert!(dts.find("function top").unwrap() < dts.find("namespace Alpha").unwrap());
+  }
+
+  #[test]
+  fn test_normalize_either() {
+    assert_eq!(
+      normalize_either("Either<string, number>"),
+      "string | number"
+    );
+    assert_eq!(
+      normalize_either("Either3<Array<string>, Record<string, Either<number, boolean>>, null>"),
+      "Array<string> | Record<string, number | boolean> | null"
+    );
+    assert_eq!(
+      normalize_either("Either<(arg: number) => void, string>[]"),
+      "(((arg: number) => void) | string)[]"
+    );
+    assert_eq!(
+      normalize_either("Either<(a: Either<string, number>) => void, Promise<number>>"),
+      "((a: string | number) => void) | Promise<number>"
+    );
+    assert_eq!(
+      normalize_either("/** returns `Either<A, B>` */\nfunction f(x: 'Either<'): Either<A, B>"),
+      "/** returns `Either<A, B>` */\nfunction f(x: 'Either<'): A | B"
+    );
+    // the unclosed one is kept, and the rest are still normalized
+    assert_eq!(
+      normalize_either("type A = Either<string\ntype B = Either<string, number>"),
+      "type A = Either<string\ntype B = string | number"
+    );
+
+    let lines = [
+      r#"{"kind": "fn", "name": "plus", "js_doc": "", "def": "function plus(input: Either<Array<string>, Either3<number, boolean, Map<string, number>>>): number"}"#,
+    ];
+    let (dts, _) = process_fixture("either", &lines, true);
+    assert!(dts.contains(
+      "export declare function plus(input: Array<string> | number | boolean | Map<string, number>): number"
+    ));
+  }
+
+  #[test]
+  fn test_validate_identifiers() {
+    assert!(is_valid_js_identifier("plus"));
+    assert!(is_valid_js_identifier("$_value1"));
+    assert!(is_valid_js_identifier("名字"));
+    assert!(!is_valid_js_identifier("1plus"));
+    assert!(!is_valid_js_identifier("my-fn"));
+    assert!(!is_valid_js_identifier("delete"));
+    assert!(!is_valid_js_identifier(""));
+
+    let lines = [
+      r#"{"kind": "fn", "name": "1plus", "js_doc": "", "def": "function 1plus(): void", "js_mod": "utils"}"#,
+      r#"{"kind": "fn", "name": "minus", "js_doc": "", "def": "function minus(): void", "js_mod": "utils", "alias": "my-minus"}"#,
+    ];
+    let (_dir, file) = test_file("invalid-identifier.napi_type_def.tmp", &lines.join("\n"));
+    let options = DtsOptions {
+      strict: true,
+      ..Default::default()
+    };
+    let err = process_type_def(file.to_str().unwrap(), true, "", &options).unwrap_err();
+    assert!(err.to_string().contains("Fn `1plus` in namespace `utils`"));
+    assert!(err
+      .to_string()
+      .contains("alias `my-minus` of Fn `minus` in namespace `utils`"));
+    assert!(process_type_def(file.to_str().unwrap(), true, "", &DtsOptions::default()).is_ok());
+  }
+
+  #[test]
+  fn test_validate_enum_members() {
+    assert!(is_valid_enum_member("delete"));
+    assert!(is_valid_enum_member("'my-value'"));
+    assert!(!is_valid_enum_member("my-value"));
+    assert!(!is_valid_enum_member("1st"));
+    assert!(!is_valid_enum_member("'1'"));
+    assert!(!is_valid_enum_member("__proto__"));
+
+    let lines = [
+      r#"{"kind": "enum", "name": "Kind", "js_doc": "", "def": "/** the first */\nDefault = 0,\n__proto__ = 1", "js_mod": "utils"}"#,
+      r#"{"kind": "enum", "name": "Level", "js_doc": "", "def": "Low = 0,\nHigh = 1"}"#,
+    ];
+    let (_dir, file) = test_file("invalid-enum-member.napi_type_def.tmp", &lines.join("\n"));
+    let options = DtsOptions {
+      strict: true,
+      ..Default::default()
+    };
+    let err = process_type_def(file.to_str().unwrap(), true, "", &options)
+      .unwrap_err()
+      .to_string();
+    assert!(err.contains("`__proto__` of enum `Kind` in namespace `utils`"));
+    assert!(!err.contains("Default"));
+    assert!(!err.contains("Level"));
+    assert!(process_type_def(file.to_str().unwrap(), true, "", &DtsOptions::default()).is_ok());
+  }
+
+  #[test]
+  fn test_esm_preamble() {
+    let header = r#"/// <reference path="./global.d.ts" />
+import { Foo } from './foo'
+import type { Bar } from "../bar.js"
+import { Baz } from '@ohos/baz'
+"#;
+    assert_eq!(
+      esm_preamble(header),
+      r#"/// <reference path="./global.d.mts" />
+import { Foo } from './foo.mjs'
+import type { Bar } from "../bar.mjs"
+import { Baz } from '@ohos/baz'
+"#
+    );
+    assert_eq!(
+      esm_dts_path(Path::new("dist/index.d.ts")),
+      Path::new("dist/index.d.mts")
+    );
+  }
+
+  #[test]
+  fn test_split_dts() {
+    let lines = [
+      r#"{"kind": "struct", "name": "Animal", "js_doc": "", "def": "name: string"}"#,
+      r#"{"kind": "fn", "name": "create", "js_doc": "", "def": "function create(): Animal", "js_mod": "zoo"}"#,
+      r#"{"kind": "fn", "name": "plus", "js_doc": "", "def": "function plus(a: number, b: number): number", "js_mod": "utils"}"#,
+    ];
+    let (_dir, file) = test_file("split.napi_type_def.tmp", &lines.join("\n"));
+    let (index, files, exports) =
+      process_type_def_split(file.to_str().unwrap(), true, "", &DtsOptions::default()).unwrap();
+
+    assert!(index.starts_with("export declare class Animal {"));
+    assert!(index.ends_with("\nexport * from './types/utils'\nexport * from './types/zoo'\n"));
+    assert_eq!(exports, vec!["Animal", "utils", "zoo"]);
+    assert_eq!(
+      files.iter().map(|(n, _)| n.as_str()).collect::<Vec<_>>(),
+      vec!["utils", "zoo"]
+    );
+    assert!(files[0].1.starts_with("export namespace utils {\n"));
+    assert!(files[1]
+      .1
+      .starts_with("import { Animal } from '../index'\n\nexport namespace zoo {\n"));
+  }
+
+  #[test]
+  fn test_incremental_split_dts() {
+    let temp = test_dir();
+    let dir = temp.path();
+    let mut ctx = Context::default();
+    ctx.dts_options.split = true;
+    ctx.cache_dir = dir.join(".ohrs");
+    let index = dir.join("index.d.ts");
+    let types = dir.join("types");
+    let files = |utils: &str| {
+      vec![
+        (String::from("utils"), utils.to_string()),
+        (
+          String::from("zoo"),
+          String::from("export namespace zoo {}\n"),
+        ),
+      ]
+    };
+    let barrel = "export * from './types/utils'\nexport * from './types/zoo'\n";
+
+    write_dts_bundle(
+      &ctx,
+      &index,
+      "",
+      barrel,
+      &files("export namespace utils {}\n"),
+    )
+    .unwrap();
+    assert!(index.is_file() && types.join("utils.d.ts").is_file());
+    // the unchanged files keep the marker as they aren't rewritten
+    let marker = "// not rewritten\n";
+    fs::write(types.join("zoo.d.ts"), marker).unwrap();
+    fs::write(types.join("custom.d.ts"), marker).unwrap();
+
+    // only the changed namespace is rewritten
+    write_dts_bundle(
+      &ctx,
+      &index,
+      "",
+      barrel,
+      &files("export namespace utils {\n  export const a: number\n}\n"),
+    )
+    .unwrap();
+    assert!(fs::read_to_string(types.join("utils.d.ts"))
+      .unwrap()
+      .contains("export const a"));
+    assert_eq!(fs::read_to_string(types.join("zoo.d.ts")).unwrap(), marker);
+
+    // the removed namespace is cleaned, the files of user are kept
+    write_dts_bundle(
+      &ctx,
+      &index,
+      "",
+      "export * from './types/zoo'\n",
+      &files("")[1..],
+    )
+    .unwrap();
+    assert!(!types.join("utils.d.ts").exists());
+    assert_eq!(fs::read_to_string(types.join("zoo.d.ts")).unwrap(), marker);
+    assert_eq!(
+      fs::read_to_string(types.join("custom.d.ts")).unwrap(),
+      marker
+    );
+    assert!(fs::read_to_string(&index).unwrap().contains("types/zoo"));
+  }
+
+  #[test]
+  fn test_annotate_throws() {
+    let lines = [
+      r#"{"kind": "fn", "name": "readFile", "js_doc": "/** read file */\n", "def": "function readFile(path: string): string", "throws": true}"#,
+      r#"{"kind": "fn", "name": "plus", "js_doc": "", "def": "function plus(a: number, b: number): number"}"#,
+      r#"{"kind": "fn", "name": "parse", "js_doc": "/**\n * parse\n * input\n */\n", "def": "function parse(input: string): number", "throws": true}"#,
+    ];
+    let options = DtsOptions {
+      annotate_throws: true,
+      ..Default::default()
+    };
+    let (dts, _) = process_fixture_with_options("throws", &lines, true, &options);
+    assert!(dts.contains(
+      "/**\n  * read file\n  * @throws {Error} if the native call fails\n  */\nexport declare function readFile("
+    ));
+    assert!(dts.contains(
+      "/**\n  * parse\n  * input\n  * @throws {Error} if the native call fails\n  */\nexport declare function parse("
+    ));
+    assert!(dts.contains("\nexport declare function plus("));
+    assert_eq!(dts.matches("@throws").count(), 2);
+
+    let (dts, _) = process_fixture("throws-disabled", &lines, true);
+    assert!(!dts.contains("@throws"));
+  }
+
+  #[test]
+  fn test_unknown_kind() {
+    let lines = [
+      r#"{"kind": "custom_kind", "name": "Custom", "js_doc": "", "def": "export type Custom = string"}"#,
+      r#"{"kind": "another_kind", "name": "Empty", "js_doc": "", "def": ""}"#,
+      r#"{"kind": "fn", "name": "plus", "js_doc": "", "def": "function plus(a: number, b: number): number"}"#,
+    ];
+    let (dts, exports) = process_fixture("unknown-kind", &lines, true);
+    assert_eq!(
+      dts,
+      "export type Custom = string\n\nexport declare function plus(a: number, b: number): number\n\n"
+    );
+    assert_eq!(exports, vec!["plus"]);
+  }
+
+  #[test]
+  fn test_index_signature() {
+    let lines = [
+      r#"{"kind": "interface", "name": "Options", "js_doc": "", "def": "name: string\nage?: number", "index_signature": "[key: string]: unknown"}"#,
+      r#"{"kind": "interface", "name": "Extra", "js_doc": "", "def": "", "js_mod": "ns", "index_signature": "[key: string]: number"}"#,
+    ];
+    let (dts, _) = process_fixture("index-signature", &lines, true);
+    assert!(dts.contains(
+      "export interface Options {\n  [key: string]: unknown\n  name: string\n  age?: number\n}\n"
+    ));
+    assert!(dts.contains("  export interface Extra {\n    [key: string]: number\n  }\n"));
+  }
+
+  #[test]
+  fn test_dts_map() {
+    let lines = [
+      r#"{"kind": "fn", "name": "plus", "js_doc": "", "def": "function plus(): number", "source": {"crate": "my-crate", "file": "src/math/mod.rs", "line": 3}}"#,
+      r#"{"kind": "struct", "name": "Foo", "js_doc": "", "def": "", "js_mod": "ns", "source": {"crate": "my-crate", "file": "src/lib.rs", "line": 10}}"#,
+      r#"{"kind": "fn", "name": "old", "js_doc": "", "def": "function old(): void", "source": {"crate": "my-crate"}}"#,
+      r#"{"kind": "fn", "name": "noSource", "js_doc": "", "def": "function noSource(): void"}"#,
+    ];
+    let (_dir, file) = test_file("dts-map.napi_type_def.tmp", &lines.join("\n"));
+    let map = dts_map(file.to_str().unwrap(), &DtsOptions::default());
+
+    let map: serde_json::Value = serde_json::from_str(&map).unwrap();
+    assert_eq!(
+      map["declarations"],
+      serde_json::json!({
+        "plus": {"crate": "my-crate", "module": "my_crate::math", "file": "src/math/mod.rs", "line": 3},
+        "ns.Foo": {"crate": "my-crate", "module": "my_crate", "file": "src/lib.rs", "line": 10},
+        "old": {"crate": "my-crate"},
+      })
+    );
+  }
+
+  #[test]
+  fn test_global_declarations() {
+    let lines = [
+      r#"{"kind": "fn", "name": "plus", "js_doc": "", "def": "function plus(a: number, b: number): number"}"#,
+      r#"{"kind": "fn", "name": "nativeLog", "js_doc": "/** log from native */\n", "def": "function nativeLog(msg: string): void", "global": true}"#,
+      r#"{"kind": "interface", "name": "NativeGlobal", "js_doc": "", "def": "version: string", "js_mod": "ns", "global": true}"#,
+    ];
+    let (dts, exports) = process_fixture("global", &lines, true);
+    assert!(dts.contains("export declare function plus(a: number, b: number): number\n"));
+    assert!(dts.contains(
+      "declare global {\n  interface NativeGlobal {\n    version: string\n  }\n\n  /** log from native */\n  function nativeLog(msg: string): void\n\n}\n"
+    ));
+    // the namespace only contains global declarations is removed
+    assert!(!dts.contains("namespace ns"));
+    assert_eq!(exports, vec!["plus"]);
+  }
+
+  #[test]
+  fn test_no_struct_first() {
+    let lines = [
+      r#"{"kind": "interface", "name": "AnimalOptions", "js_doc": "", "def": "name: string"}"#,
+      r#"{"kind": "impl", "name": "Animal", "js_doc": "", "def": "get name(): string"}"#,
+      r#"{"kind": "struct", "name": "Animal", "js_doc": "", "def": ""}"#,
+      r#"{"kind": "fn", "name": "createAnimal", "js_doc": "", "def": "function createAnimal(options: AnimalOptions): Animal"}"#,
+    ];
+    let (dts, _) = process_fixture("struct-first", &lines, true);
+    let class = dts
+      .find("export declare class Animal {\n  get name(): string\n}")
+      .unwrap();
+    assert!(dts.find("export interface AnimalOptions").unwrap() < class);
+    assert!(dts.find("export declare function createAnimal").unwrap() < class);
+
+    let options = DtsOptions {
+      no_struct_first: true,
+      ..Default::default()
+    };
+    let (dts, _) = process_fixture_with_options("no-struct-first", &lines, true, &options);
+    let class = dts
+      .find("export declare class Animal {\n  get name(): string\n}")
+      .unwrap();
+    assert!(class < dts.find("export interface AnimalOptions").unwrap());
+    assert!(
+      dts.find("export interface AnimalOptions").unwrap()
+        < dts.find("export declare function createAnimal").unwrap()
+    );
+  }
+
+  #[test]
+  fn test_namespace_shadowing() {
+    let lines = [
+      r#"{"kind": "const", "name": "xxh3", "js_doc": "", "def": "export const xxh3: number", "source": {"crate": "napi", "file": "src/lib.rs", "line": 3}}"#,
+      r#"{"kind": "fn", "name": "hash", "js_doc": "", "def": "function hash(): number", "js_mod": "xxh3", "source": {"crate": "napi", "file": "src/js_mod.rs", "line": 8}}"#,
+    ];
+    let (_dir, file) = test_file("namespace-shadowing.napi_type_def.tmp", &lines.join("\n"));
+    let options = DtsOptions {
+      strict: true,
+      ..Default::default()
+    };
+    let err = process_type_def(file.to_str().unwrap(), true, "", &options).unwrap_err();
+    assert!(err.to_string().contains(
+      "Const `xxh3` (defined at src/lib.rs:3) and namespace `xxh3` (defined at src/js_mod.rs:8)"
+    ));
+    // only warning without strict
+    assert!(process_type_def(file.to_str().unwrap(), true, "", &DtsOptions::default()).is_ok());
+  }
+
+  #[test]
+  fn test_object_and_class() {
+    let lines = [
+      r#"{"kind": "interface", "name": "Point", "js_doc": "", "def": "x: number\ny: number\nconstructor(x: number, y: number)", "original_name": "Point"}"#,
+      r#"{"kind": "struct", "name": "Animal", "js_doc": "", "def": "name: string\nconstructor(name: string)", "original_name": "Animal"}"#,
+    ];
+    let (dts, exports) = process_fixture("object-and-class", &lines, true);
+    assert!(dts.contains("export interface Point {\n  x: number\n  y: number\n}\n"));
+    assert!(dts
+      .contains("export declare class Animal {\n  name: string\n  constructor(name: string)\n}\n"));
+    // interface is type only, no runtime export
+    assert_eq!(exports, vec!["Animal"]);
+  }
+
+  #[test]
+  fn test_buffer_in_type_positions() {
+    let lines = [
+      r#"{"kind": "fn", "name": "readFile", "js_doc": "/** Read the file into a Buffer */\n", "def": "function readFile(path: string, encoding?: 'Buffer' | 'utf8'): Promise<Buffer>"}"#,
+      r#"{"kind": "interface", "name": "Options", "js_doc": "", "def": "/** default is \"Buffer\" */\nmode: string\nBuffer?: Array<Buffer | null>\ndata: Buffer"}"#,
+      r#"{"kind": "type_alias", "name": "Data", "js_doc": "", "def": "Buffer | string"}"#,
+    ];
+    let (dts, _) = process_fixture("buffer-type-positions", &lines, true);
+    assert!(dts.contains(
+      "/** Read the file into a Buffer */\nexport declare function readFile(path: string, encoding?: 'Buffer' | 'utf8'): Promise<ArrayBuffer>\n"
+    ));
+    assert!(dts.contains(
+      "  /** default is \"Buffer\" */\n  mode: string\n  Buffer?: Array<ArrayBuffer | null>\n  data: ArrayBuffer\n"
+    ));
+    assert!(dts.contains("export type Data = ArrayBuffer | string\n"));
+  }
+
+  #[test]
+  fn test_types_package_json() {
+    let mut ctx = Context::default();
+    ctx.dts_options.split = true;
+    let files = vec![(String::from("ns"), String::new())];
+    let package: serde_json::Value =
+      serde_json::from_str(&types_package_json(&ctx, &files)).unwrap();
+    assert_eq!(
+      package,
+      serde_json::json!({
+        "name": "types",
+        "version": "0.0.0",
+        "types": "index.d.ts",
+        "files": ["index.d.ts", "types"],
+      })
+    );
+  }
+
+  #[test]
+  fn test_lint_ignore_header() {
+    assert_eq!(
+      type_def_header(LintIgnore::default()),
+      "/* auto-generated by OHOS-RS */\n/* eslint-disable */\n\n"
+    );
+    let biome: LintIgnore = "Biome".parse().unwrap();
+    assert_eq!(
+      type_def_header(biome),
+      "/* auto-generated by OHOS-RS */\n/* biome-ignore-all lint: auto-generated */\n\n"
+    );
+    assert_eq!(
+      type_def_header("none".parse().unwrap()),
+      "/* auto-generated by OHOS-RS */\n\n"
+    );
+    assert!("tslint".parse::<LintIgnore>().is_err());
+  }
+
+  #[test]
+  fn test_async_iterable_return_type() {
+    let lines = [
+      r#"{"kind": "fn", "name": "passThroughStream", "js_doc": "", "def": "function passThroughStream(stream: AsyncIterable<number>): AsyncIterableIterator<number>"}"#,
+      r#"{"kind": "fn", "name": "readChunks", "js_doc": "", "def": "function readChunks(): AsyncGenerator<Buffer, void, unknown>", "js_mod": "fs"}"#,
+    ];
+    let (dts, _) = process_fixture("async-iterable", &lines, true);
+    assert!(dts.contains(
+      "export declare function passThroughStream(stream: AsyncIterable<number>): AsyncIterableIterator<number>\n"
+    ));
+    assert!(
+      dts.contains("  export function readChunks(): AsyncGenerator<ArrayBuffer, void, unknown>\n")
+    );
+  }
+
+  #[test]
+  fn test_alias() {
+    let lines = [
+      r#"{"kind": "fn", "name": "sum", "js_doc": "", "def": "function sum(a: number, b: number): number", "alias": "plus"}"#,
+      r#"{"kind": "struct", "name": "Animal", "js_doc": "", "def": "name: string", "original_name": "Animal", "alias": "Pet"}"#,
+      r#"{"kind": "enum", "name": "Kind", "js_doc": "", "def": "A = 0", "original_name": "Kind", "alias": "OldKind"}"#,
+      r#"{"kind": "fn", "name": "walk", "js_doc": "", "def": "function walk(): void", "js_mod": "ns", "alias": "run"}"#,
+    ];
+    let (dts, exports) = process_fixture("alias", &lines, true);
+    assert!(dts.contains("export declare function sum(a: number, b: number): number\n/** @deprecated Use `sum` instead. */\nexport declare const plus: typeof sum\n"));
+    assert!(dts.contains("/** @deprecated Use `Animal` instead. */\nexport declare const Pet: typeof Animal\nexport type Pet = Animal\n"));
+    // const enum can't be referred with typeof
+    assert!(dts.contains("/** @deprecated Use `Kind` instead. */\nexport type OldKind = Kind\n"));
+    assert!(dts.contains("  export function walk(): void\n  /** @deprecated Use `walk` instead. */\n  export const run: typeof walk\n"));
+    assert_eq!(
+      exports,
+      vec!["Kind", "OldKind", "sum", "plus", "Animal", "Pet", "ns"]
+    );
+
+    let lines = [
+      r#"{"kind": "fn", "name": "sum", "js_doc": "", "def": "function sum(a: number, b: number): number", "alias": "plus"}"#,
+      r#"{"kind": "fn", "name": "plus", "js_doc": "", "def": "function plus(a: number, b: number): number"}"#,
+    ];
+    let (_dir, file) = test_file("alias-conflict.napi_type_def.tmp", &lines.join("\n"));
+    let err =
+      process_type_def(file.to_str().unwrap(), true, "", &DtsOptions::default()).unwrap_err();
+    assert!(err
+      .to_string()
+      .ends_with(":\n  alias `plus` of Fn `sum` conflicts with Fn `plus`"));
+  }
+
+  #[test]
+  fn test_closed_world() {
+    let lines = [
+      r#"{"kind": "fn", "name": "load", "js_doc": "", "def": "function load(config: Config, cb: (err: Error | null, value: Item) => void): Promise<Array<Payload>>"}"#,
+      r#"{"kind": "fn", "name": "identity", "js_doc": "", "def": "function identity<T>(value: T): Either<T, Buffer>"}"#,
+      r#"{"kind": "interface", "name": "Config", "js_doc": "", "def": "path: string\nitem?: ns.Item\nother?: ns.Missing\n[key: string]: unknown"}"#,
+      r#"{"kind": "struct", "name": "Item", "js_doc": "", "def": "kind: Kind", "js_mod": "ns"}"#,
+      r#"{"kind": "struct", "name": "Wrapper", "js_doc": "", "def": "item: Item\nconfig: Config", "js_mod": "ns"}"#,
+    ];
+    let (_dir, file) = test_file("closed-world.napi_type_def.tmp", &lines.join("\n"));
+    let options = DtsOptions {
+      closed_world: true,
+      ..Default::default()
+    };
+    let err = process_type_def(file.to_str().unwrap(), true, "", &options).unwrap_err();
+    assert!(err
+      .to_string()
+      .ends_with(":\n  `Item` referenced by load\n  `Kind` referenced by Item\n  `Payload` referenced by load\n  `ns.Missing` referenced by Config"));
+  }
+}
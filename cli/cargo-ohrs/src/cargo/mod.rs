@@ -1,16 +1,17 @@
+use crate::util::{resolve_ndk, resolve_sysroot, Arch, NdkToolchain};
 use anyhow::Error;
-use std::env;
-
-use crate::util::Arch;
 
 mod run;
 
 pub fn cargo(args: crate::CargoArgs) -> anyhow::Result<()> {
-  let ndk = env::var("OHOS_NDK_HOME").map_err(|_| {
-    Error::msg(
-      "Failed to get the OHOS_NDK_HOME environment variable, please make sure you have set it.",
-    )
-  })?;
+  if args.args.is_empty() {
+    return Err(Error::msg(
+      "Missing cargo command, e.g. `ohrs cargo run --example foo`.",
+    ));
+  }
+  let ndk = resolve_ndk(None)?;
+  let toolchain = NdkToolchain::probe(&ndk)?;
+  let sysroot = resolve_sysroot(None, &ndk)?;
   let (command, rest_args) = args.args.split_at(1);
   let target_arch = args.arch.unwrap_or(vec![Arch::ARM64]);
 
@@ -27,7 +28,7 @@ pub fn cargo(args: crate::CargoArgs) -> anyhow::Result<()> {
       all_args.extend([&t, &rt]);
       all_args.extend(rest_args);
 
-      run::run(arch, ndk.clone(), all_args)?;
+      run::run(arch, &toolchain, &sysroot, args.runner.as_deref(), all_args)?;
       Ok(())
     })
     .collect::<anyhow::Result<Vec<_>>>()?;
@@ -0,0 +1,64 @@
+use crate::util::resolve_cache_dir;
+use anyhow::Error;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+/// remove the cache dir, return whether it existed
+fn clean_cache(root: &Path, cache_dir: Option<&str>) -> anyhow::Result<bool> {
+  let dir = resolve_cache_dir(root, cache_dir);
+  if !dir.exists() {
+    return Ok(false);
+  }
+  if !dir.is_dir() {
+    return Err(Error::msg(format!(
+      "{} is not a folder, please check --cache-dir.",
+      dir.display()
+    )));
+  }
+  fs::remove_dir_all(&dir)?;
+  Ok(true)
+}
+
+pub fn clean(args: crate::CleanArgs) -> anyhow::Result<()> {
+  if !args.cache {
+    return Err(Error::msg(
+      "Nothing to clean, please use --cache to remove the cache dir.",
+    ));
+  }
+  let pwd = env::current_dir()?;
+  let dir = resolve_cache_dir(&pwd, args.cache_dir.as_deref());
+  if clean_cache(&pwd, args.cache_dir.as_deref())? {
+    println!("Remove {}.", dir.display());
+  } else {
+    println!("{} is not existed, nothing to clean.", dir.display());
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod test {
+  use super::clean_cache;
+  use crate::util::{test_dir, write_cache, NDK_LOCK};
+  use serde_json::json;
+  use std::fs;
+
+  #[test]
+  fn test_clean_cache() {
+    let temp = test_dir();
+    let root = temp.path().to_path_buf();
+    write_cache(
+      &root.join(".ohrs"),
+      NDK_LOCK,
+      &json!({ "version": "5.0.0" }),
+    )
+    .unwrap();
+
+    assert!(clean_cache(&root, None).unwrap());
+    assert!(!root.join(".ohrs").exists());
+    assert!(!clean_cache(&root, None).unwrap());
+
+    fs::write(root.join("cache"), "").unwrap();
+    assert!(clean_cache(&root, Some("cache")).is_err());
+  }
+}
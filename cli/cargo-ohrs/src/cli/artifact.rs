@@ -1,5 +1,7 @@
 use bpaf::{construct, long, Parser};
 
+use crate::util::Arch;
+
 pub fn cli_artifact() -> impl Parser<crate::Options> {
   let dist = long("dist")
     .short('d')
@@ -13,6 +15,35 @@ pub fn cli_artifact() -> impl Parser<crate::Options> {
     .help(".har file product name.")
     .fallback(String::from("package"));
 
-  let artifact_parser = construct!(crate::ArtifactArgs { name, dist });
+  let artifact_name = long("artifact-name")
+    .help("Template of .har file name, {name}, {version}, {abi} and {sha} are replaced with the crate name, crate version, architectures and git short commit, e.g. {name}-{version}-{abi}. It overrides --name.")
+    .argument::<String>("TEMPLATE")
+    .optional();
+
+  let artifact_split = long("artifact-split")
+    .help("How the architectures are packed, fat puts all of them into one .har file, per-abi generates one .har file for every architecture, which should be named with {abi} in --artifact-name, {name}-{abi} is used by default.")
+    .argument::<crate::artifact::ArtifactSplit>("SPLIT")
+    .fallback(crate::artifact::ArtifactSplit::Fat);
+
+  let arch = long("arch")
+    .short('a')
+    .help("The architectures should be contained in package, all architectures are required with --check by default, and all built ones are packed otherwise.")
+    .argument::<Arch>("ARCH")
+    .some("Please provide at least one architecture")
+    .optional();
+
+  let check = long("check")
+    .help("Validate the built libraries, version and types file of package without generating .har file.")
+    .switch()
+    .fallback(false);
+
+  let artifact_parser = construct!(crate::ArtifactArgs {
+    name,
+    artifact_name,
+    artifact_split,
+    dist,
+    arch,
+    check
+  });
   construct!(crate::Options::Artifact(artifact_parser))
 }
@@ -3,25 +3,236 @@ use bpaf::{construct, long, positional, Parser};
 use crate::util::Arch;
 
 pub fn cli_build() -> impl Parser<crate::Options> {
+  let init_parser = build_args();
+  construct!(crate::Options::Build(init_parser))
+}
+
+/// `--NAME` or `--NAME=BOOL`, it's none if not given so the manifest can set it
+fn toggle(name: &'static str, help: &'static str) -> impl Parser<Option<bool>> {
+  let value = long(name).argument::<bool>("BOOL").hide();
+  let on = long(name).help(help).req_flag(true);
+  construct!([value, on]).optional()
+}
+
+/// shared with release command
+pub fn build_args() -> impl Parser<crate::BuildArgs> {
   let dist = long("dist")
     .argument::<String>("DIST")
     .help("The path of the final build product is set to `dist` by default.")
-    .fallback(String::from("dist"));
+    .optional();
 
   let release = long("release")
     .help("Build with release mode.")
     .switch()
     .fallback(false);
 
+  let profile = long("profile")
+    .help("Build with the given cargo profile, can't be used with --release.")
+    .argument::<String>("PROFILE")
+    .optional();
+
   let arch = long("arch")
       .short('a')
-      .help("The target build products support arm64/aarch, arm/arm32, and x86_64/x64 architectures, with all builds enabled by default.")
+      .help("The target build products support arm64/aarch, arm/arm32, and x86_64/x64 architectures or their target triples, with all builds enabled by default.")
       .argument::<Arch>("ARCH")
       .some("Please provide at least one architecture")
-      .optional()
-      .fallback(Some([Arch::ARM64,Arch::ARM32,Arch::X86_64].to_vec()));
+      .optional();
+
+  let copy_static = toggle(
+    "static",
+    "Copy the static link library to the final output directory, will be set to false by default.",
+  );
 
-  let copy_static = long("static").help("Copy the static link library to the final output directory, will be set to false by default.").switch().fallback(false);
+  let strict = toggle(
+    "strict",
+    "Treat the project check warnings as errors, will be set to false by default.",
+  );
+
+  let ndk = long("ndk")
+    .help("The ndk version or path used for current build. Version will be resolved in OHOS_NDK_ROOT, and will use OHOS_NDK_HOME by default.")
+    .argument::<String>("NDK")
+    .optional();
+
+  let sysroot = long("sysroot")
+    .help("The sysroot path used for current build. Will use OHOS_SYSROOT or the sysroot in ndk by default.")
+    .argument::<String>("SYSROOT")
+    .optional();
+
+  let locked_ndk = long("locked-ndk")
+    .help("Verify the ndk version is the same as the version in ndk-lock.json of cache dir, ohrs-ndk.lock in project root is still read if it's missing.")
+    .switch()
+    .fallback(false);
+
+  let update_ndk_lock = long("update-ndk-lock")
+    .help("Write the current ndk version to ndk-lock.json of cache dir.")
+    .switch()
+    .fallback(false);
+
+  let print_config = long("print-config")
+    .help("Print the effective configuration and exit without building.")
+    .switch()
+    .fallback(false);
+
+  let bundle_lib = long("bundle-lib")
+    .help("Extra shared library which will be copied to the final output directory, can be used multiple times.")
+    .argument::<String>("LIB")
+    .many();
+
+  let flatten_singletons = toggle(
+    "flatten-singletons",
+    "Hoist the only member of a namespace to top level in .d.ts file, its name will be prefixed with the namespace.",
+  );
+
+  let dts_transform = long("dts-transform")
+    .help("Pipe the generated .d.ts content to the command's stdin, and write its stdout as the final .d.ts file.")
+    .argument::<String>("COMMAND")
+    .optional();
+
+  let namespace_order = long("namespace-order")
+    .help("The order of namespaces in .d.ts file, split with comma. The unlisted namespaces will be sorted alphabetically after them.")
+    .argument::<String>("NAMESPACES")
+    .optional();
+
+  let dts_out = long("dts-out")
+    .help("The path of generated .d.ts file, default is index.d.ts in dist folder. The types field in package will be updated.")
+    .argument::<String>("PATH")
+    .optional();
+
+  let js_out = long("js-out")
+    .help("The path of generated JS entry with --export-equals, default is index.js beside the .d.ts file. The main field in package will be updated.")
+    .argument::<String>("PATH")
+    .optional();
+
+  let dual = toggle(
+    "dual",
+    "Emit index.d.mts for ESM alongside index.d.ts, only the header is different.",
+  );
+
+  let split_dts = toggle(
+    "split-dts",
+    "Emit a .d.ts file for every namespace into types folder, and index.d.ts will re-export them.",
+  );
+
+  let annotate_throws = toggle(
+    "annotate-throws",
+    "Add @throws to the doc of functions which return Result in .d.ts file.",
+  );
+
+  let content_hash = long("content-hash")
+    .help("Name the intermediate type def file by the hash of build inputs instead of the manifest path, so it's the same in different checkout paths.")
+    .switch()
+    .fallback(false);
+
+  let emit_metadata = long("emit-metadata")
+    .help("Write the cache key computed from the type defs, tool version and .d.ts flags, and the outputs to the json file.")
+    .argument::<String>("PATH")
+    .optional();
+
+  let emit_dts_map = long("emit-dts-map")
+    .help("Emit index.d.ts.map.json alongside the .d.ts file, which maps every declaration to its rust source.")
+    .switch()
+    .fallback(false);
+
+  let no_dts = toggle(
+    "no-dts",
+    "Skip generating .d.ts file, only build the native libraries.",
+  );
+
+  let verbose = long("verbose")
+    .short('v')
+    .help("Print the full compiler diagnostics, only the summary of errors and warnings is printed by default.")
+    .switch()
+    .fallback(false);
+
+  let emit_diagnostics = long("emit-diagnostics")
+    .help("Write the compiler errors and warnings of all targets to the json file.")
+    .argument::<String>("PATH")
+    .optional();
+
+  let no_struct_first = toggle(
+    "no-struct-first",
+    "Sort classes by name with other declarations in .d.ts file, instead of placing them after others.",
+  );
+
+  let types_package = long("types-package")
+    .help("Also emit the .d.ts files and a types only package.json into the folder, which can be published separately.")
+    .argument::<String>("DIR")
+    .optional();
+
+  let lint_ignore = long("lint-ignore")
+    .help("The lint directive in the header of .d.ts file, support eslint, biome, oxlint and none, default is eslint.")
+    .argument::<crate::build::LintIgnore>("LINT")
+    .optional();
+
+  let dts_formatter = long("dts-formatter")
+    .help("Format the generated .d.ts content with the command, such as prettier or dprint, which reads stdin and writes stdout. The unformatted content is kept if it fails.")
+    .argument::<String>("COMMAND")
+    .optional();
+
+  let dts_closed_world = toggle(
+    "dts-closed-world",
+    "Fail the build if the generated .d.ts references types which are neither declared in it nor built in.",
+  );
+
+  let since = long("since")
+    .help("Only build the crates changed since the git ref, all cdylib members are checked in workspace root.")
+    .argument::<String>("REF")
+    .optional();
+
+  let optional_style = long("optional-style")
+    .help("How the optional properties are declared in .d.ts file, `omit` emits `field?: T` by default, `undefined` emits `field?: T | undefined` which is also valid with `exactOptionalPropertyTypes`.")
+    .argument::<crate::build::OptionalStyle>("STYLE")
+    .optional();
+
+  let namespace_keyword = long("namespace-keyword")
+    .help("The keyword of grouped declarations in .d.ts file, support namespace and module, default is namespace.")
+    .argument::<crate::build::NamespaceKeyword>("KEYWORD")
+    .optional();
+
+  let manifest_metadata = long("manifest-metadata")
+    .help("Read the build settings from [package.metadata.ohrs] in Cargo.toml, the keys are the same as the flags. The flags take precedence over the manifest, pass false to turn off a switch, e.g. --strict=false.")
+    .switch()
+    .fallback(false);
+
+  let export_equals = long("export-equals")
+    .help("Wrap the declarations into `declare namespace NAME` with `export = NAME` for the legacy module resolution, and emit index.js with `module.exports` alongside the .d.ts file. Can't be used with --dual or --split-dts.")
+    .argument::<String>("NAME")
+    .optional();
+
+  let polyfill = long("polyfill")
+    .help("How the polyfill is provided in .d.ts file when it's referenced, NAME is abort-signal or external-object, MODE is inline by default, import[:MODULE] to import it from the module, or off if the consumer provides it. Can be used multiple times.")
+    .argument::<crate::build::Polyfill>("NAME=MODE")
+    .many();
+
+  let external_type = long("external-type")
+    .help("Import the type from the module in .d.ts file instead of declaring it, e.g. Base=@scope/base. The local declaration of the type is dropped. Can be used multiple times.")
+    .argument::<crate::build::ExternalType>("NAME=MODULE")
+    .many();
+
+  let allow_dir = long("allow-dir")
+    .help("Allow the output paths in the directory besides the workspace root, can be used multiple times.")
+    .argument::<String>("DIR")
+    .many();
+
+  let allow_outside = long("allow-outside")
+    .help("Allow the output paths outside of the workspace root, such as --dist and --dts-out.")
+    .switch()
+    .fallback(false);
+
+  let print_exports = long("print-exports")
+    .help("Print the export names of the native module and exit without writing the .d.ts file and libraries, FORMAT is lines or json. Only the first target is built.")
+    .argument::<crate::build::ExportsFormat>("FORMAT")
+    .optional();
+
+  let env_file = long("env-file")
+    .help("Load the KEY=VALUE lines of the file into environment before build, such as OHOS_NDK_HOME and RUSTFLAGS. The variables already set in environment win.")
+    .argument::<String>("PATH")
+    .optional();
+
+  let cache_dir = long("cache-dir")
+    .help("The folder of the ndk lock, .d.ts hashes and build metadata, .ohrs in project root by default.")
+    .argument::<String>("DIR")
+    .optional();
 
   let cargo_args = positional("CARGO_ARGS")
     .help("The custom parameters for cargo build in the current project.")
@@ -29,12 +240,50 @@ pub fn cli_build() -> impl Parser<crate::Options> {
     .many()
     .optional();
 
-  let init_parser = construct!(crate::BuildArgs {
+  construct!(crate::BuildArgs {
     dist,
     arch,
     release,
-    cargo_args,
-    copy_static
-  });
-  construct!(crate::Options::Build(init_parser))
+    profile,
+    copy_static,
+    strict,
+    ndk,
+    sysroot,
+    locked_ndk,
+    update_ndk_lock,
+    print_config,
+    bundle_lib,
+    flatten_singletons,
+    dts_transform,
+    namespace_order,
+    dts_out,
+    js_out,
+    dual,
+    split_dts,
+    annotate_throws,
+    content_hash,
+    emit_metadata,
+    emit_dts_map,
+    no_dts,
+    verbose,
+    emit_diagnostics,
+    no_struct_first,
+    types_package,
+    lint_ignore,
+    dts_formatter,
+    dts_closed_world,
+    since,
+    optional_style,
+    namespace_keyword,
+    manifest_metadata,
+    export_equals,
+    polyfill,
+    external_type,
+    allow_dir,
+    allow_outside,
+    print_exports,
+    env_file,
+    cache_dir,
+    cargo_args
+  })
 }
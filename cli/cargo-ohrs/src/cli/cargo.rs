@@ -1,4 +1,4 @@
-use bpaf::{construct, long, positional, Parser};
+use bpaf::{any, construct, long, Parser};
 
 use crate::util::Arch;
 
@@ -11,10 +11,16 @@ pub fn cli_cargo() -> impl Parser<crate::Options> {
   .optional()
   .fallback(Some([Arch::ARM64].to_vec()));
 
-  let args = positional("CARGO_ARGS")
+  let runner = long("runner")
+    .help("The runner for `cargo run` and `cargo test`, such as a script which pushes the binary to device with hdc and runs it.")
+    .argument::<String>("COMMAND")
+    .optional();
+
+  // the flags of cargo command are accepted as is, like `ohrs cargo run --example foo`
+  let args = any::<String, _, _>("CARGO_ARGS", Some)
     .help("Provide the ohpm environment for executing other cargo commands.")
     .many();
 
-  let cargo_parser = construct!(crate::CargoArgs { arch, args });
+  let cargo_parser = construct!(crate::CargoArgs { arch, runner, args });
   construct!(crate::Options::Cargo(cargo_parser))
 }
@@ -0,0 +1,16 @@
+use bpaf::{construct, long, Parser};
+
+pub fn cli_clean() -> impl Parser<crate::Options> {
+  let cache = long("cache")
+    .help("Remove the cache dir, including the ndk lock, .d.ts hashes and build metadata.")
+    .switch()
+    .fallback(false);
+
+  let cache_dir = long("cache-dir")
+    .help("The cache dir used by build, .ohrs in current folder by default.")
+    .argument::<String>("DIR")
+    .optional();
+
+  let clean_parser = construct!(crate::CleanArgs { cache, cache_dir });
+  construct!(crate::Options::Clean(clean_parser))
+}
@@ -0,0 +1,9 @@
+use bpaf::{construct, positional, Parser};
+
+pub fn cli_diff() -> impl Parser<crate::Options> {
+  let old = positional::<String>("OLD").help("The old .har file.");
+  let new = positional::<String>("NEW").help("The new .har file.");
+
+  let diff_parser = construct!(crate::DiffArgs { old, new });
+  construct!(crate::Options::Diff(diff_parser))
+}
@@ -1,4 +1,4 @@
-use bpaf::{construct, positional, short, Parser};
+use bpaf::{construct, long, positional, short, Parser};
 
 pub fn cli_init() -> impl Parser<crate::Options> {
   let name = positional::<String>("name").help("project name");
@@ -9,6 +9,33 @@ pub fn cli_init() -> impl Parser<crate::Options> {
     .argument::<String>("PACKAGE_NAME")
     .optional();
 
-  let init_parser = construct!(crate::InitArgs { package_name, name });
+  let description = long("description")
+    .help("Description of ohpm package.")
+    .argument::<String>("DESCRIPTION")
+    .optional();
+
+  let author = long("author")
+    .help("Author of ohpm package. If not set, will use the user name of git")
+    .argument::<String>("AUTHOR")
+    .optional();
+
+  let namespace = long("namespace")
+    .help("Namespace of ohpm package, the package will be published as @namespace/package")
+    .argument::<String>("NAMESPACE")
+    .optional();
+
+  let yes = short('y')
+    .long("yes")
+    .help("Don't prompt for the package variables, use the flags or defaults. It's the default when stdin is not a terminal")
+    .switch();
+
+  let init_parser = construct!(crate::InitArgs {
+    package_name,
+    description,
+    author,
+    namespace,
+    yes,
+    name
+  });
   construct!(crate::Options::Init(init_parser))
 }
@@ -1,8 +1,11 @@
 mod artifact;
 mod build;
 mod cargo;
+mod clean;
+mod diff;
 mod init;
 mod publish;
+mod release;
 
 use bpaf::{construct, pure, Doc, OptionParser, Parser};
 use owo_colors::colors::CustomColor;
@@ -11,8 +14,11 @@ use owo_colors::OwoColorize;
 use artifact::cli_artifact;
 use build::cli_build;
 use cargo::cli_cargo;
+use clean::cli_clean;
+use diff::cli_diff;
 use init::cli_init;
 use publish::cli_publish;
+use release::cli_release;
 
 pub fn cli_run() -> OptionParser<crate::Options> {
   let init = cli_init()
@@ -36,12 +42,44 @@ pub fn cli_run() -> OptionParser<crate::Options> {
     .command("cargo")
     .help("Used to execute any cargo command and ensure it is under the ohpm environment.");
 
+  let diff = cli_diff()
+    .to_options()
+    .command("diff")
+    .help("Compare two .har files, including files, .d.ts and metadata.");
+
+  let clean = cli_clean()
+    .to_options()
+    .command("clean")
+    .help("Remove the state kept by ohrs, such as the cache dir.");
+
+  let release = cli_release()
+    .to_options()
+    .command("release")
+    .help("Build, generate .har file and publish in one command.");
+
   let doctor = pure(crate::Options::Doctor)
     .to_options()
     .command("doctor")
     .help("Verify if the development environment is fully set up.");
 
-  construct!([init, build, artifact, publish, cargo, doctor]).to_options()
+  let list_targets = pure(crate::Options::ListTargets)
+    .to_options()
+    .command("list-targets")
+    .help("List the supported OHOS targets and whether they are installed by rustup.");
+
+  construct!([
+    init,
+    build,
+    artifact,
+    publish,
+    cargo,
+    diff,
+    clean,
+    release,
+    doctor,
+    list_targets
+  ])
+  .to_options()
 }
 
 pub struct Info();
@@ -1,4 +1,4 @@
-use bpaf::{construct, positional, short, Parser};
+use bpaf::{construct, positional, pure, short, Parser};
 
 pub fn cli_publish() -> impl Parser<crate::Options> {
   let name = positional::<String>("name").help("project name");
@@ -9,6 +9,18 @@ pub fn cli_publish() -> impl Parser<crate::Options> {
     .argument::<String>("PACKAGE_NAME")
     .optional();
 
-  let init_parser = construct!(crate::InitArgs { package_name, name });
+  let description = pure(None);
+  let author = pure(None);
+  let namespace = pure(None);
+  let yes = pure(true);
+
+  let init_parser = construct!(crate::InitArgs {
+    package_name,
+    description,
+    author,
+    namespace,
+    yes,
+    name
+  });
   construct!(crate::Options::Init(init_parser))
 }
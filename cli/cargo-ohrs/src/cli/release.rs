@@ -0,0 +1,38 @@
+use bpaf::{construct, long, Parser};
+
+use super::build::build_args;
+
+pub fn cli_release() -> impl Parser<crate::Options> {
+  let name = long("name")
+    .short('n')
+    .argument("NAME")
+    .help(".har file product name.")
+    .fallback(String::from("package"));
+
+  let skip_publish = long("skip-publish")
+    .help("Stop after the .har file is generated.")
+    .switch()
+    .fallback(false);
+
+  let dry_run = long("dry-run")
+    .help("Print the phases which will be run without running them.")
+    .switch()
+    .fallback(false);
+
+  let sync_version = long("sync-version")
+    .help("Update the version in package/oh-package.json5 with the version of current crate before generating .har file.")
+    .switch()
+    .fallback(false);
+
+  // build args has positional cargo args, it must be the last one
+  let build = build_args();
+
+  let release_parser = construct!(crate::ReleaseArgs {
+    name,
+    skip_publish,
+    dry_run,
+    sync_version,
+    build
+  });
+  construct!(crate::Options::Release(release_parser))
+}
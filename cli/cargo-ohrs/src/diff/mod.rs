@@ -0,0 +1,135 @@
+use anyhow::Error;
+use flate2::read::GzDecoder;
+use owo_colors::OwoColorize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use text::unified_diff;
+
+mod text;
+
+const META_FILE: &str = "ohrs-meta.json";
+
+/// read all files in .har, key is the path in package
+fn read_har(har: &str) -> anyhow::Result<BTreeMap<String, Vec<u8>>> {
+  let path = Path::new(har);
+  if !path.is_file() {
+    return Err(Error::msg(format!("{} is not existed.", har)));
+  }
+  let mut archive = tar::Archive::new(GzDecoder::new(File::open(path)?));
+  let mut files = BTreeMap::new();
+  for entry in archive
+    .entries()
+    .map_err(|e| Error::msg(format!("Read {} failed: {}", har, e)))?
+  {
+    let mut entry = entry?;
+    if !entry.header().entry_type().is_file() {
+      continue;
+    }
+    let name = entry.path()?.to_string_lossy().to_string();
+    let mut content = Vec::new();
+    entry.read_to_end(&mut content)?;
+    files.insert(name, content);
+  }
+  Ok(files)
+}
+
+// flatten json to `a.b.c = value`
+fn flatten_json(prefix: &str, value: &Value, ret: &mut BTreeMap<String, String>) {
+  match value {
+    Value::Object(map) => map.iter().for_each(|(k, v)| {
+      let key = if prefix.is_empty() {
+        k.clone()
+      } else {
+        format!("{}.{}", prefix, k)
+      };
+      flatten_json(&key, v, ret);
+    }),
+    _ => {
+      ret.insert(prefix.to_string(), value.to_string());
+    }
+  }
+}
+
+fn read_meta(files: &BTreeMap<String, Vec<u8>>) -> BTreeMap<String, String> {
+  let mut ret = BTreeMap::new();
+  let meta = files
+    .iter()
+    .find(|(name, _)| Path::new(name).file_name().is_some_and(|f| f == META_FILE))
+    .and_then(|(_, content)| serde_json::from_slice::<Value>(content).ok());
+  if let Some(meta) = meta {
+    flatten_json("", &meta, &mut ret);
+  }
+  ret
+}
+
+fn print_title(title: &str) {
+  println!("\n{}", title.bold());
+}
+
+/// compare two .har files
+pub fn diff(args: crate::DiffArgs) -> anyhow::Result<()> {
+  let old = read_har(&args.old)?;
+  let new = read_har(&args.new)?;
+
+  print_title("Files:");
+  let names = old.keys().chain(new.keys()).collect::<BTreeSet<_>>();
+  let mut changed_dts = Vec::new();
+  for name in names {
+    match (old.get(name), new.get(name)) {
+      (Some(_), None) => println!("{}", format!("- {}", name).red()),
+      (None, Some(_)) => println!("{}", format!("+ {}", name).green()),
+      (Some(o), Some(n)) if o != n => {
+        println!("{}", format!("~ {}", name).yellow());
+        if name.ends_with(".d.ts") {
+          changed_dts.push(name);
+        }
+      }
+      _ => {}
+    }
+  }
+
+  print_title("Declarations:");
+  for name in changed_dts {
+    let o = String::from_utf8_lossy(&old[name]);
+    let n = String::from_utf8_lossy(&new[name]);
+    if let Some(d) = unified_diff(
+      &format!("{}/{}", &args.old, name),
+      &format!("{}/{}", &args.new, name),
+      &o,
+      &n,
+    ) {
+      d.lines().for_each(|l| match l.chars().next() {
+        Some('+') => println!("{}", l.green()),
+        Some('-') => println!("{}", l.red()),
+        Some('@') => println!("{}", l.cyan()),
+        _ => println!("{}", l),
+      });
+    }
+  }
+
+  print_title("Metadata:");
+  let old_meta = read_meta(&old);
+  let new_meta = read_meta(&new);
+  if old_meta.is_empty() && new_meta.is_empty() {
+    println!("No {} found.", META_FILE);
+  }
+  let keys = old_meta
+    .keys()
+    .chain(new_meta.keys())
+    .collect::<BTreeSet<_>>();
+  for key in keys {
+    match (old_meta.get(key), new_meta.get(key)) {
+      (Some(o), None) => println!("{}", format!("- {}: {}", key, o).red()),
+      (None, Some(n)) => println!("{}", format!("+ {}: {}", key, n).green()),
+      (Some(o), Some(n)) if o != n => {
+        println!("{}", format!("~ {}: {} -> {}", key, o, n).yellow())
+      }
+      _ => {}
+    }
+  }
+  Ok(())
+}
@@ -0,0 +1,259 @@
+const CONTEXT: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+  Equal(usize, usize),
+  Delete(usize),
+  Insert(usize),
+}
+
+// Compute line operations with Myers' algorithm, in linear space with middle snake
+fn diff_ops(old: &[&str], new: &[&str]) -> Vec<Op> {
+  let d_max = (old.len() + new.len()).div_ceil(2) + 1;
+  let mut vf = V::new(d_max);
+  let mut vb = V::new(d_max);
+  let mut ops = Vec::with_capacity(old.len().max(new.len()));
+  conquer(old, new, 0, 0, &mut vf, &mut vb, &mut ops);
+
+  // deleted lines go before inserted ones in each changed block
+  for block in ops.split_mut(|op| matches!(op, Op::Equal(..))) {
+    block.sort_by_key(|op| matches!(op, Op::Insert(_)));
+  }
+  ops
+}
+
+// furthest x of each diagonal k, which can be negative
+struct V {
+  offset: isize,
+  v: Vec<usize>,
+}
+
+impl V {
+  fn new(d_max: usize) -> Self {
+    V {
+      offset: d_max as isize,
+      v: vec![0; 2 * d_max + 1],
+    }
+  }
+}
+
+impl std::ops::Index<isize> for V {
+  type Output = usize;
+
+  fn index(&self, k: isize) -> &usize {
+    &self.v[(k + self.offset) as usize]
+  }
+}
+
+impl std::ops::IndexMut<isize> for V {
+  fn index_mut(&mut self, k: isize) -> &mut usize {
+    &mut self.v[(k + self.offset) as usize]
+  }
+}
+
+fn common_prefix(old: &[&str], new: &[&str]) -> usize {
+  old.iter().zip(new).take_while(|(a, b)| a == b).count()
+}
+
+fn common_suffix(old: &[&str], new: &[&str]) -> usize {
+  old
+    .iter()
+    .rev()
+    .zip(new.iter().rev())
+    .take_while(|(a, b)| a == b)
+    .count()
+}
+
+// `i0` and `j0` are the offsets of `old` and `new` in the whole text
+fn conquer(
+  old: &[&str],
+  new: &[&str],
+  i0: usize,
+  j0: usize,
+  vf: &mut V,
+  vb: &mut V,
+  ops: &mut Vec<Op>,
+) {
+  let prefix = common_prefix(old, new);
+  ops.extend((0..prefix).map(|k| Op::Equal(i0 + k, j0 + k)));
+  let (old, new) = (&old[prefix..], &new[prefix..]);
+  let (i0, j0) = (i0 + prefix, j0 + prefix);
+  let suffix = common_suffix(old, new);
+  let (old, new) = (&old[..old.len() - suffix], &new[..new.len() - suffix]);
+
+  match find_middle_snake(old, new, vf, vb) {
+    Some((x, y)) if !old.is_empty() && !new.is_empty() => {
+      conquer(&old[..x], &new[..y], i0, j0, vf, vb, ops);
+      conquer(&old[x..], &new[y..], i0 + x, j0 + y, vf, vb, ops);
+    }
+    _ => {
+      ops.extend((0..old.len()).map(|k| Op::Delete(i0 + k)));
+      ops.extend((0..new.len()).map(|k| Op::Insert(j0 + k)));
+    }
+  }
+
+  let (n, m) = (old.len(), new.len());
+  ops.extend((0..suffix).map(|k| Op::Equal(i0 + n + k, j0 + m + k)));
+}
+
+// find the start of the middle snake of shortest edit path, searching from both ends
+fn find_middle_snake(old: &[&str], new: &[&str], vf: &mut V, vb: &mut V) -> Option<(usize, usize)> {
+  let (n, m) = (old.len(), new.len());
+  if n == 0 || m == 0 {
+    return None;
+  }
+  let delta = n as isize - m as isize;
+  let odd = delta & 1 == 1;
+  let d_max = ((n + m).div_ceil(2) + 1) as isize;
+  vf[1] = 0;
+  vb[1] = 0;
+
+  for d in 0..d_max {
+    for k in (-d..=d).rev().step_by(2) {
+      let mut x = if k == -d || (k != d && vf[k - 1] < vf[k + 1]) {
+        vf[k + 1]
+      } else {
+        vf[k - 1] + 1
+      };
+      let y = (x as isize - k) as usize;
+      let (x0, y0) = (x, y);
+      if x < n && y < m {
+        x += common_prefix(&old[x..], &new[y..]);
+      }
+      vf[k] = x;
+      if odd && (k - delta).abs() < d && vf[k] + vb[-(k - delta)] >= n {
+        return Some((x0, y0));
+      }
+    }
+
+    for k in (-d..=d).rev().step_by(2) {
+      let mut x = if k == -d || (k != d && vb[k - 1] < vb[k + 1]) {
+        vb[k + 1]
+      } else {
+        vb[k - 1] + 1
+      };
+      let mut y = (x as isize - k) as usize;
+      if x < n && y < m {
+        let advance = common_suffix(&old[..n - x], &new[..m - y]);
+        x += advance;
+        y += advance;
+      }
+      vb[k] = x;
+      if !odd && (k - delta).abs() <= d && vb[k] + vf[-(k - delta)] >= n {
+        return Some((n - x, m - y));
+      }
+    }
+  }
+  None
+}
+
+/// Generate unified diff of two text, return `None` if they are the same
+pub fn unified_diff(old_name: &str, new_name: &str, old: &str, new: &str) -> Option<String> {
+  let old_lines = old.lines().collect::<Vec<_>>();
+  let new_lines = new.lines().collect::<Vec<_>>();
+  let ops = diff_ops(&old_lines, &new_lines);
+
+  let changed = ops
+    .iter()
+    .enumerate()
+    .filter(|(_, op)| !matches!(op, Op::Equal(..)))
+    .map(|(i, _)| i)
+    .collect::<Vec<_>>();
+  if changed.is_empty() {
+    return None;
+  }
+
+  // merge changes into hunks with context lines
+  let mut hunks: Vec<(usize, usize)> = Vec::new();
+  for i in changed {
+    let start = i.saturating_sub(CONTEXT);
+    let end = (i + CONTEXT + 1).min(ops.len());
+    match hunks.last_mut() {
+      Some(last) if start <= last.1 => last.1 = end,
+      _ => hunks.push((start, end)),
+    }
+  }
+
+  let mut ret = format!("--- {}\n+++ {}\n", old_name, new_name);
+  for (start, end) in hunks {
+    let hunk = &ops[start..end];
+    // line number of hunk start, it's the next line if the hunk has no line of that side
+    let old_start = ops[..start]
+      .iter()
+      .filter(|op| !matches!(op, Op::Insert(_)))
+      .count();
+    let new_start = ops[..start]
+      .iter()
+      .filter(|op| !matches!(op, Op::Delete(_)))
+      .count();
+    let old_len = hunk
+      .iter()
+      .filter(|op| !matches!(op, Op::Insert(_)))
+      .count();
+    let new_len = hunk
+      .iter()
+      .filter(|op| !matches!(op, Op::Delete(_)))
+      .count();
+
+    ret += &format!(
+      "@@ -{},{} +{},{} @@\n",
+      old_start + (old_len > 0) as usize,
+      old_len,
+      new_start + (new_len > 0) as usize,
+      new_len
+    );
+    for op in hunk {
+      match *op {
+        Op::Equal(i, _) => ret += &format!(" {}\n", old_lines[i]),
+        Op::Delete(i) => ret += &format!("-{}\n", old_lines[i]),
+        Op::Insert(j) => ret += &format!("+{}\n", new_lines[j]),
+      }
+    }
+  }
+  Some(ret)
+}
+
+#[cfg(test)]
+mod test {
+  use super::{diff_ops, unified_diff, Op};
+
+  #[test]
+  fn test_unified_diff() {
+    assert_eq!(unified_diff("a", "b", "x\ny\n", "x\ny\n"), None);
+
+    let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n";
+    let new = "1\n2\n3\n4\nfive\n6\n7\n8\n9\n10\n11\n";
+    assert_eq!(
+      unified_diff("a", "b", old, new).unwrap(),
+      "--- a\n+++ b\n@@ -2,9 +2,10 @@\n 2\n 3\n 4\n-5\n+five\n 6\n 7\n 8\n 9\n 10\n+11\n"
+    );
+  }
+
+  #[test]
+  fn test_diff_ops() {
+    let ops = diff_ops(&["a", "b", "c", "d"], &["b", "x", "d", "e"]);
+    assert_eq!(
+      ops,
+      [
+        Op::Delete(0),
+        Op::Equal(1, 0),
+        Op::Delete(2),
+        Op::Insert(1),
+        Op::Equal(3, 2),
+        Op::Insert(3)
+      ]
+    );
+
+    // large text with a few changes
+    let old = (0..50000).map(|i| i.to_string()).collect::<Vec<_>>();
+    let mut new = old.clone();
+    new[100] = "changed".to_string();
+    new.insert(30000, "inserted".to_string());
+    new.remove(45000);
+    let old = old.iter().map(String::as_str).collect::<Vec<_>>();
+    let new = new.iter().map(String::as_str).collect::<Vec<_>>();
+    let ops = diff_ops(&old, &new);
+    let changed = ops.iter().filter(|op| !matches!(op, Op::Equal(..))).count();
+    assert_eq!(changed, 4);
+  }
+}
@@ -5,7 +5,7 @@ use render_result::render;
 use semver::Version;
 use target::{resolve_rust_version, resolve_targets};
 
-use crate::util::Arch;
+use crate::util::SUPPORTED_TARGETS;
 mod render_result;
 mod target;
 
@@ -13,7 +13,7 @@ pub fn doctor() -> anyhow::Result<()> {
   let targets = resolve_targets()?;
   let origin_version = resolve_rust_version()?;
   let version = Version::parse(&origin_version)?;
-  let ndk = env::var("OHOS_NDK_HOME").unwrap_or_default();
+  let ndk = env::var_os("OHOS_NDK_HOME").unwrap_or_default();
 
   let msvc = Version::parse("1.78.0")?;
 
@@ -27,15 +27,57 @@ pub fn doctor() -> anyhow::Result<()> {
     "{}  Rust version should be >= 1.78.0.",
     render(version >= msvc)
   );
-  [Arch::ARM64, Arch::ARM32, Arch::X86_64]
+  SUPPORTED_TARGETS.iter().for_each(|t| {
+    println!(
+      "{}  Rustup target: {} should be installed.",
+      render(targets.iter().any(|i| i == t)),
+      t.green(),
+    );
+  });
+  Ok(())
+}
+
+// the supported targets which are not installed
+fn missing_targets(installed: &[String]) -> Vec<&'static str> {
+  SUPPORTED_TARGETS
     .iter()
-    .for_each(|arch| {
-      let t = arch.rust_target();
-      println!(
-        "{}  Rustup target: {} should be installed.",
-        render((&targets).contains(&t.to_owned())),
-        t.green(),
-      );
-    });
+    .filter(|t| !installed.iter().any(|i| i == *t))
+    .copied()
+    .collect()
+}
+
+/// print the installed and missing OHOS targets, it's informational and always succeeds
+pub fn list_targets() -> anyhow::Result<()> {
+  let installed = resolve_targets().unwrap_or_else(|e| {
+    println!("{}: {}", "Warning".bold().yellow(), e);
+    vec![]
+  });
+  SUPPORTED_TARGETS.iter().for_each(|t| {
+    println!("{}  {}", render(installed.iter().any(|i| i == t)), t);
+  });
+  let missing = missing_targets(&installed);
+  if !missing.is_empty() {
+    println!(
+      "\nInstall the missing targets with:\n  rustup target add {}",
+      missing.join(" ")
+    );
+  }
   Ok(())
 }
+
+#[cfg(test)]
+mod test {
+  use super::missing_targets;
+
+  #[test]
+  fn test_missing_targets() {
+    let installed = vec![
+      String::from("aarch64-unknown-linux-ohos"),
+      String::from("x86_64-unknown-linux-gnu"),
+    ];
+    assert_eq!(
+      missing_targets(&installed),
+      vec!["armv7-unknown-linux-ohos", "x86_64-unknown-linux-ohos"]
+    );
+  }
+}
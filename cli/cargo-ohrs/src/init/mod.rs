@@ -2,16 +2,20 @@ use crate::{create_dist_dir, create_project_file};
 
 mod config;
 mod package;
+mod prompt;
 mod tmp;
 
 use anyhow::Error;
 use config::get_git_config;
 use package::{CHANGELOG, LICENSE, MODULE_CONTENT, PKG, README};
+use prompt::{collect, validate_crate_name, InitVars};
+use std::io::{self, IsTerminal};
 use tmp::{BUILD_INIT, CARGO_TOML, GIT_IGNORE, LIB_CODE};
 
 pub fn init(arg: crate::InitArgs) -> anyhow::Result<()> {
   let pwd = std::env::current_dir().map_err(|_e| Error::msg("Can't get current work path"))?;
 
+  validate_crate_name(&arg.name)?;
   let target = pwd.join(&arg.name);
 
   if target.exists() == true {
@@ -21,6 +25,28 @@ pub fn init(arg: crate::InitArgs) -> anyhow::Result<()> {
     )));
   }
 
+  // prompt for the package variables in terminal, --yes and CI use the defaults
+  let interactive = !arg.yes && io::stdin().is_terminal();
+  let defaults = InitVars {
+    package: arg.package_name.clone().unwrap_or(arg.name.clone()),
+    description: arg.description.clone().unwrap_or_default(),
+    author: arg
+      .author
+      .clone()
+      .unwrap_or_else(|| get_git_config().author),
+    namespace: arg.namespace.clone(),
+  };
+  let with_package = interactive || arg.package_name.is_some() || arg.namespace.is_some();
+  // the package files are only created with package flags or in interactive mode
+  let vars = match (with_package, interactive) {
+    (false, _) => None,
+    (true, true) => {
+      let (mut stdin, mut stdout) = (io::stdin().lock(), io::stdout());
+      Some(collect(defaults, Some((&mut stdin, &mut stdout)))?)
+    }
+    (true, false) => Some(collect(defaults, None)?),
+  };
+
   create_dist_dir!(&target.join("src"));
 
   create_project_file!(LIB_CODE, &target.join("src").join("lib.rs"), "lib.rs");
@@ -30,28 +56,34 @@ pub fn init(arg: crate::InitArgs) -> anyhow::Result<()> {
   let config = CARGO_TOML.replace("entry", &arg.name.as_str());
   create_project_file!(config, &target.join("Cargo.toml"), "Cargo.toml");
 
-  if arg.package_name.is_some() {
-    let git_config = get_git_config();
-    let pkg = arg.package_name.unwrap_or(arg.name.clone());
+  if let Some(vars) = vars {
+    let pkg = vars.package_name();
     create_dist_dir!(&target.join("package").join("src").join("main"));
 
-    let readme = README.replace("@pkg", &pkg.as_str());
+    // the line of description is removed if it's empty
+    let readme = README
+      .replace(
+        "@description\n\n",
+        &match vars.description.is_empty() {
+          true => String::new(),
+          false => format!("{}\n\n", vars.description),
+        },
+      )
+      .replace("@pkg", &pkg.as_str());
     create_project_file!(
       readme,
       &target.join("package").join("README.md"),
       "package/README.md"
     );
 
-    let pkg_json5 = PKG
-      .replace("@author", &git_config.author.as_str())
-      .replace("@pkg", &pkg.as_str());
+    let pkg_json5 = render_pkg(&vars.author, &vars.description, &pkg);
     create_project_file!(
       pkg_json5,
       &target.join("package").join("oh-package.json5"),
       "package/oh-package.json5"
     );
 
-    let license = LICENSE.replace("@author", &git_config.author.as_str());
+    let license = LICENSE.replace("@author", &vars.author.as_str());
     create_project_file!(
       license,
       &target.join("package").join("LICENSE"),
@@ -71,7 +103,8 @@ pub fn init(arg: crate::InitArgs) -> anyhow::Result<()> {
       "package/CHANGELOG.md"
     );
 
-    let module = MODULE_CONTENT.replace("@pkg", &pkg.as_str());
+    // module name can't contain the namespace
+    let module = MODULE_CONTENT.replace("@pkg", &vars.package.as_str());
     create_project_file!(
       module,
       &target
@@ -84,3 +117,28 @@ pub fn init(arg: crate::InitArgs) -> anyhow::Result<()> {
   }
   Ok(())
 }
+
+// the values are escaped, the quotes and backslashes in them shouldn't break oh-package.json5
+fn render_pkg(author: &str, description: &str, pkg: &str) -> String {
+  let escape = |s: &str| {
+    let quoted = serde_json::to_string(s).unwrap_or_default();
+    quoted[1..quoted.len() - 1].to_string()
+  };
+  PKG
+    .replace("@author", &escape(author))
+    .replace("@description", &escape(description))
+    .replace("@pkg", pkg)
+}
+
+#[cfg(test)]
+mod test {
+  use super::render_pkg;
+
+  #[test]
+  fn test_render_pkg() {
+    let pkg = render_pkg(r#"Tom "T" Cat"#, "a\\b\nc", "@ohos-rs/hello");
+    assert!(pkg.contains(r#"author: "Tom \"T\" Cat","#));
+    assert!(pkg.contains(r#"description: "a\\b\nc","#));
+    assert!(pkg.contains(r#"name: "@ohos-rs/hello","#));
+  }
+}
@@ -25,7 +25,7 @@ pub const PKG: &str = r#"{
   license: "MIT",
   author: "@author",
   name: "@pkg",
-  description: "",
+  description: "@description",
   main: "index.ets",
   version: "0.0.1",
   types: "libs/index.d.ts",
@@ -34,6 +34,8 @@ pub const PKG: &str = r#"{
 
 pub const README: &str = r#"# `@pkg`
 
+@description
+
 ## Install
 
 use`ohpm` to install package.
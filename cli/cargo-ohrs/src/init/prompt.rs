@@ -0,0 +1,245 @@
+use anyhow::Error;
+use std::io::{BufRead, Write};
+
+// keywords and the names reserved by cargo
+const RESERVED_NAMES: &[&str] = &[
+  "abstract",
+  "as",
+  "async",
+  "await",
+  "become",
+  "box",
+  "break",
+  "const",
+  "continue",
+  "crate",
+  "do",
+  "dyn",
+  "else",
+  "enum",
+  "extern",
+  "false",
+  "final",
+  "fn",
+  "for",
+  "if",
+  "impl",
+  "in",
+  "let",
+  "loop",
+  "macro",
+  "match",
+  "mod",
+  "move",
+  "mut",
+  "override",
+  "priv",
+  "pub",
+  "ref",
+  "return",
+  "self",
+  "static",
+  "struct",
+  "super",
+  "trait",
+  "true",
+  "try",
+  "type",
+  "typeof",
+  "unsafe",
+  "unsized",
+  "use",
+  "virtual",
+  "where",
+  "while",
+  "yield",
+  "test",
+  "core",
+  "std",
+  "alloc",
+  "proc_macro",
+  "proc-macro",
+];
+
+/// the variables which are substituted into the scaffolded files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InitVars {
+  // ohpm package name without namespace
+  pub package: String,
+  pub description: String,
+  pub author: String,
+  // ohpm namespace, the package is published as `@namespace/package`
+  pub namespace: Option<String>,
+}
+
+impl InitVars {
+  /// full ohpm package name
+  pub fn package_name(&self) -> String {
+    match &self.namespace {
+      Some(ns) => format!("@{}/{}", ns, self.package),
+      None => self.package.clone(),
+    }
+  }
+}
+
+/// check the crate name with the rules of cargo
+pub fn validate_crate_name(name: &str) -> anyhow::Result<()> {
+  let invalid = |reason: &str| {
+    Err(Error::msg(format!(
+      "Invalid crate name `{}`: {}.",
+      name, reason
+    )))
+  };
+  let Some(first) = name.chars().next() else {
+    return invalid("the name can't be empty");
+  };
+  if first.is_ascii_digit() {
+    return invalid("the name can't start with a digit");
+  }
+  if let Some(c) = name
+    .chars()
+    .find(|c| !c.is_ascii_alphanumeric() && *c != '-' && *c != '_')
+  {
+    return invalid(&format!(
+      "`{}` is not allowed, only ASCII letters, digits, `-` and `_` are supported",
+      c
+    ));
+  }
+  if RESERVED_NAMES.contains(&name) {
+    return invalid("it's a Rust keyword or reserved by cargo");
+  }
+  if name.len() > 64 {
+    return invalid("the name can't be longer than 64 characters");
+  }
+  Ok(())
+}
+
+/// ohpm package name and namespace should be lowercase
+fn validate_ohpm_name(kind: &str, name: &str) -> anyhow::Result<()> {
+  let valid = name
+    .chars()
+    .next()
+    .is_some_and(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+    && name
+      .chars()
+      .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_' || c == '.');
+  if !valid {
+    return Err(Error::msg(format!(
+      "Invalid ohpm {} `{}`, only lowercase letters, digits, `-`, `_` and `.` are supported.",
+      kind, name
+    )));
+  }
+  Ok(())
+}
+
+fn prompt(
+  input: &mut impl BufRead,
+  output: &mut impl Write,
+  label: &str,
+  default: &str,
+) -> anyhow::Result<String> {
+  if default.is_empty() {
+    write!(output, "{}: ", label)?;
+  } else {
+    write!(output, "{} ({}): ", label, default)?;
+  }
+  output.flush()?;
+  let mut line = String::new();
+  input.read_line(&mut line)?;
+  let value = line.trim();
+  Ok(if value.is_empty() {
+    default.to_string()
+  } else {
+    value.to_string()
+  })
+}
+
+/// ask for the variables, the defaults are used as is without input
+/// all values are validated before any file is written
+pub fn collect(
+  defaults: InitVars,
+  input: Option<(&mut dyn BufRead, &mut dyn Write)>,
+) -> anyhow::Result<InitVars> {
+  let vars = match input {
+    Some((mut input, mut output)) => {
+      let package = prompt(
+        &mut input,
+        &mut output,
+        "ohpm package name",
+        &defaults.package,
+      )?;
+      let description = prompt(
+        &mut input,
+        &mut output,
+        "description",
+        &defaults.description,
+      )?;
+      let author = prompt(&mut input, &mut output, "author", &defaults.author)?;
+      let namespace = prompt(
+        &mut input,
+        &mut output,
+        "ohpm namespace, leave empty if not needed",
+        defaults.namespace.as_deref().unwrap_or_default(),
+      )?;
+      InitVars {
+        package,
+        description,
+        author,
+        // `@ohos-rs` and `ohos-rs` are the same
+        namespace: Some(namespace.trim_start_matches('@').to_string()).filter(|n| !n.is_empty()),
+      }
+    }
+    None => defaults,
+  };
+
+  validate_ohpm_name("package name", &vars.package)?;
+  if let Some(ns) = &vars.namespace {
+    validate_ohpm_name("namespace", ns)?;
+  }
+  Ok(vars)
+}
+
+#[cfg(test)]
+mod test {
+  use super::{collect, validate_crate_name, InitVars};
+  use std::io::Cursor;
+
+  fn defaults() -> InitVars {
+    InitVars {
+      package: String::from("hello"),
+      description: String::new(),
+      author: String::from("ohos"),
+      namespace: None,
+    }
+  }
+
+  #[test]
+  fn test_validate_crate_name() {
+    assert!(validate_crate_name("hello-world_2").is_ok());
+    assert!(validate_crate_name("").is_err());
+    assert!(validate_crate_name("2hello").is_err());
+    assert!(validate_crate_name("hello world").is_err());
+    assert!(validate_crate_name("fn").is_err());
+    assert!(validate_crate_name("std").is_err());
+  }
+
+  #[test]
+  fn test_collect_vars() {
+    assert_eq!(collect(defaults(), None).unwrap(), defaults());
+
+    let mut input = Cursor::new("\nA native module\n\n@ohos-rs\n");
+    let mut output = Vec::new();
+    let vars = collect(defaults(), Some((&mut input, &mut output))).unwrap();
+    assert_eq!(vars.package, "hello");
+    assert_eq!(vars.description, "A native module");
+    assert_eq!(vars.author, "ohos");
+    assert_eq!(vars.package_name(), "@ohos-rs/hello");
+    assert!(String::from_utf8(output)
+      .unwrap()
+      .starts_with("ohpm package name (hello): "));
+
+    let mut input = Cursor::new("Hello\n\n\n\n");
+    let mut output = Vec::new();
+    assert!(collect(defaults(), Some((&mut input, &mut output))).is_err());
+  }
+}
@@ -36,6 +36,7 @@ target/
 .DS_Store
 .idea/
 package/libs
+.ohrs/
 
 *.har
 
@@ -0,0 +1,169 @@
+use owo_colors::OwoColorize;
+use util::Arch;
+
+use crate::cli::cli_run;
+
+mod artifact;
+mod build;
+mod cargo;
+mod clean;
+mod cli;
+mod diff;
+mod doctor;
+mod init;
+mod publish;
+mod release;
+mod util;
+
+#[derive(Debug, Clone)]
+pub(crate) struct InitArgs {
+  package_name: Option<String>,
+  description: Option<String>,
+  author: Option<String>,
+  namespace: Option<String>,
+  yes: bool,
+  name: String,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct BuildArgs {
+  dist: Option<String>,
+  arch: Option<Vec<Arch>>,
+  release: bool,
+  profile: Option<String>,
+  copy_static: Option<bool>,
+  strict: Option<bool>,
+  ndk: Option<String>,
+  sysroot: Option<String>,
+  locked_ndk: bool,
+  update_ndk_lock: bool,
+  print_config: bool,
+  bundle_lib: Vec<String>,
+  flatten_singletons: Option<bool>,
+  dts_transform: Option<String>,
+  namespace_order: Option<String>,
+  dts_out: Option<String>,
+  js_out: Option<String>,
+  dual: Option<bool>,
+  split_dts: Option<bool>,
+  annotate_throws: Option<bool>,
+  content_hash: bool,
+  emit_metadata: Option<String>,
+  emit_dts_map: bool,
+  no_dts: Option<bool>,
+  verbose: bool,
+  emit_diagnostics: Option<String>,
+  no_struct_first: Option<bool>,
+  types_package: Option<String>,
+  lint_ignore: Option<build::LintIgnore>,
+  dts_formatter: Option<String>,
+  dts_closed_world: Option<bool>,
+  since: Option<String>,
+  optional_style: Option<build::OptionalStyle>,
+  namespace_keyword: Option<build::NamespaceKeyword>,
+  manifest_metadata: bool,
+  export_equals: Option<String>,
+  polyfill: Vec<build::Polyfill>,
+  external_type: Vec<build::ExternalType>,
+  allow_dir: Vec<String>,
+  allow_outside: bool,
+  print_exports: Option<build::ExportsFormat>,
+  env_file: Option<String>,
+  cache_dir: Option<String>,
+  cargo_args: Option<Vec<String>>,
+}
+
+impl BuildArgs {
+  /// `dist` if it's set by neither the flag nor the manifest
+  fn dist(&self) -> &str {
+    self.dist.as_deref().unwrap_or("dist")
+  }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ArtifactArgs {
+  dist: String,
+  name: String,
+  artifact_name: Option<String>,
+  artifact_split: artifact::ArtifactSplit,
+  arch: Option<Vec<Arch>>,
+  check: bool,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct CargoArgs {
+  arch: Option<Vec<Arch>>,
+  runner: Option<String>,
+  args: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ReleaseArgs {
+  name: String,
+  skip_publish: bool,
+  dry_run: bool,
+  sync_version: bool,
+  build: BuildArgs,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct CleanArgs {
+  cache: bool,
+  cache_dir: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct DiffArgs {
+  old: String,
+  new: String,
+}
+
+#[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
+pub(crate) enum Options {
+  Init(InitArgs),
+  Build(BuildArgs),
+  Artifact(ArtifactArgs),
+  Cargo(CargoArgs),
+  Diff(DiffArgs),
+  Clean(CleanArgs),
+  Release(ReleaseArgs),
+  #[allow(dead_code)]
+  Publish,
+  #[allow(dead_code)]
+  Doctor,
+  ListTargets,
+}
+
+/// entries for the benches, they are not stable api
+#[doc(hidden)]
+pub mod bench {
+  pub use crate::build::{process_type_def, DtsOptions};
+}
+
+pub fn run() {
+  util::install_cancel_handler();
+  let parser = cli_run()
+    .descr(cli::Info())
+    .version(env!("CARGO_PKG_VERSION"));
+
+  let ret = parser.fallback_to_usage().run();
+
+  let run_ret = match ret {
+    Options::Init(args) => init::init(args),
+    Options::Build(args) => build::build(args),
+    Options::Artifact(args) => artifact::artifact(args),
+    Options::Cargo(args) => cargo::cargo(args),
+    Options::Diff(args) => diff::diff(args),
+    Options::Clean(args) => clean::clean(args),
+    Options::Release(args) => release::release(args),
+    Options::Doctor => doctor::doctor(),
+    Options::ListTargets => doctor::list_targets(),
+    Options::Publish => publish::publish(),
+  };
+  if let Err(e) = run_ret {
+    util::run_cleanups();
+    eprintln!("{:?}", e.red());
+    std::process::exit(1);
+  }
+}
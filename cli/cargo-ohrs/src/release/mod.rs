@@ -0,0 +1,127 @@
+use anyhow::Error;
+use owo_colors::OwoColorize;
+use regex::Regex;
+use std::env;
+use std::fs;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+  Build,
+  SyncVersion,
+  Artifact,
+  Publish,
+}
+
+impl Phase {
+  fn name(&self) -> &'static str {
+    match self {
+      Phase::Build => "build",
+      Phase::SyncVersion => "sync-version",
+      Phase::Artifact => "artifact",
+      Phase::Publish => "publish",
+    }
+  }
+}
+
+fn phases(args: &crate::ReleaseArgs) -> Vec<Phase> {
+  let mut ret = vec![Phase::Build];
+  if args.sync_version {
+    ret.push(Phase::SyncVersion);
+  }
+  ret.push(Phase::Artifact);
+  if !args.skip_publish {
+    ret.push(Phase::Publish);
+  }
+  ret
+}
+
+/// replace the first version field, it should be the version of package
+fn replace_version(content: &str, version: &str) -> String {
+  let version_reg = Regex::new(r#"("?version"?\s*:\s*)"[^"]*""#).unwrap();
+  version_reg
+    .replace(content, |caps: &regex::Captures| {
+      format!("{}\"{}\"", &caps[1], version)
+    })
+    .to_string()
+}
+
+fn sync_version(version: &str) -> anyhow::Result<()> {
+  let path = env::current_dir()?.join("package").join("oh-package.json5");
+  if !path.is_file() {
+    return Err(Error::msg(format!("{:?} is not existed.", &path)));
+  }
+  let content = fs::read_to_string(&path)?;
+  let new_content = replace_version(&content, version);
+  if new_content != content {
+    fs::write(&path, new_content.as_bytes())?;
+    println!("Update version of package/oh-package.json5 to {}.", version);
+  }
+  Ok(())
+}
+
+/// build -> sync version -> artifact -> publish, stop at the first failed phase
+pub fn release(args: crate::ReleaseArgs) -> anyhow::Result<()> {
+  let phases = phases(&args);
+  if args.dry_run {
+    println!("Release phases:");
+    phases
+      .iter()
+      .enumerate()
+      .for_each(|(i, p)| println!("  {}. {}", i + 1, p.name()));
+    return Ok(());
+  }
+  if args.build.print_config {
+    return Err(Error::msg("--print-config can't be used with release."));
+  }
+  if args.build.print_exports.is_some() {
+    return Err(Error::msg("--print-exports can't be used with release."));
+  }
+  if args.build.since.is_some() {
+    return Err(Error::msg("--since can't be used with release."));
+  }
+
+  let mut version = None;
+  for phase in phases {
+    println!("{} {}", "Release".bold().green(), phase.name());
+    let ret = match phase {
+      Phase::Build => crate::build::build_package(args.build.clone()).map(|pkg| {
+        version = pkg.map(|p| p.version.to_string());
+      }),
+      Phase::SyncVersion => match &version {
+        Some(v) => sync_version(v),
+        None => Err(Error::msg("Can't get the version of current crate.")),
+      },
+      Phase::Artifact => crate::artifact::artifact(crate::ArtifactArgs {
+        dist: args.build.dist().to_string(),
+        name: args.name.clone(),
+        artifact_name: None,
+        artifact_split: crate::artifact::ArtifactSplit::Fat,
+        arch: args.build.arch.clone(),
+        check: false,
+      }),
+      Phase::Publish => crate::publish::publish(),
+    };
+    ret.map_err(|e| Error::msg(format!("Release stopped at {} phase: {}", phase.name(), e)))?;
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod test {
+  use super::replace_version;
+
+  #[test]
+  fn test_replace_version() {
+    let content = r#"{
+  name: "pkg",
+  "version": "0.1.0",
+  dependencies: {
+    "dep": "^1.0.0"
+  }
+}"#;
+    assert_eq!(
+      replace_version(content, "1.2.3"),
+      content.replace("0.1.0", "1.2.3")
+    );
+  }
+}
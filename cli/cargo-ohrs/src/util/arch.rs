@@ -1,3 +1,4 @@
+use crate::util::normalize_target;
 use std::str::FromStr;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -30,7 +31,16 @@ impl Arch {
     }
   }
 
-  pub fn rust_target(self) -> &'static str {
+  /// `e_machine` field in ELF header
+  pub fn elf_machine(self) -> u16 {
+    match self {
+      Arch::ARM64 => 0xB7,
+      Arch::ARM32 => 0x28,
+      Arch::X86_64 => 0x3E,
+    }
+  }
+
+  pub const fn rust_target(self) -> &'static str {
     match self {
       Arch::ARM64 => "aarch64-unknown-linux-ohos",
       Arch::ARM32 => "armv7-unknown-linux-ohos",
@@ -45,12 +55,10 @@ impl FromStr for Arch {
   where
     Self: Sized,
   {
-    let ret = s.to_lowercase();
-    match ret.as_ref() {
-      "aarch" | "arm64" => Ok(Arch::ARM64),
-      "arm" | "arm32" => Ok(Arch::ARM32),
-      "x86_64" | "x64" => Ok(Arch::X86_64),
-      _ => Err("Only supports aarch/arm64, arm/arm32, and x86_64/x64 architectures.".to_string()),
-    }
+    let target = normalize_target(s)?;
+    [Arch::ARM64, Arch::ARM32, Arch::X86_64]
+      .into_iter()
+      .find(|a| a.rust_target() == target)
+      .ok_or(format!("Unsupported target {}.", s))
   }
 }
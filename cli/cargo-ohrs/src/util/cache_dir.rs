@@ -0,0 +1,67 @@
+use crate::util::write_atomic;
+use serde::de::DeserializeOwned;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// the state of ohrs between builds is kept in `.ohrs` of the project root, it can be ignored by git
+/// all of them are small JSON files:
+/// - `ndk-lock.json`: `{ "version": "<ndk version>" }`, written by --update-ndk-lock and verified by --locked-ndk
+/// - `dts-hash.json`: `{ "<.d.ts path>": "<sha256>" }`, the .d.ts file isn't rewritten if its hash is the same
+/// - `metadata.json`: `{ "tool_version", "cache_key", "intermediate", "dts" }` of the last build, the same as --emit-metadata
+pub const CACHE_DIR: &str = ".ohrs";
+pub const NDK_LOCK: &str = "ndk-lock.json";
+pub const DTS_HASH: &str = "dts-hash.json";
+pub const METADATA: &str = "metadata.json";
+
+/// `.ohrs` in the project root, or --cache-dir which is relative to the project root
+pub fn resolve_cache_dir(root: &Path, cache_dir: Option<&str>) -> PathBuf {
+  root.join(cache_dir.unwrap_or(CACHE_DIR))
+}
+
+/// the missing or broken file is treated as empty
+pub fn read_cache<T: DeserializeOwned>(dir: &Path, name: &str) -> Option<T> {
+  let content = fs::read_to_string(dir.join(name)).ok()?;
+  serde_json::from_str(&content).ok()
+}
+
+pub fn write_cache(dir: &Path, name: &str, value: &serde_json::Value) -> anyhow::Result<()> {
+  fs::create_dir_all(dir)?;
+  write_atomic(
+    &dir.join(name),
+    (serde_json::to_string_pretty(value)? + "\n").as_bytes(),
+  )?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod test {
+  use super::{read_cache, resolve_cache_dir, write_cache, NDK_LOCK};
+  use crate::util::test_dir;
+  use serde_json::json;
+  use std::collections::HashMap;
+  use std::path::{Path, PathBuf};
+
+  #[test]
+  fn test_cache_dir() {
+    let root = Path::new("/ws/crate");
+    assert_eq!(
+      resolve_cache_dir(root, None),
+      PathBuf::from("/ws/crate/.ohrs")
+    );
+    assert_eq!(
+      resolve_cache_dir(root, Some("../cache")),
+      PathBuf::from("/ws/crate/../cache")
+    );
+    assert_eq!(
+      resolve_cache_dir(root, Some("/tmp/cache")),
+      PathBuf::from("/tmp/cache")
+    );
+
+    let temp = test_dir();
+    let dir = temp.path().join(".ohrs");
+    assert!(read_cache::<HashMap<String, String>>(&dir, NDK_LOCK).is_none());
+    write_cache(&dir, NDK_LOCK, &json!({ "version": "5.0.0.71" })).unwrap();
+    let lock = read_cache::<HashMap<String, String>>(&dir, NDK_LOCK).unwrap();
+    assert_eq!(lock["version"], "5.0.0.71");
+  }
+}
@@ -0,0 +1,121 @@
+use owo_colors::OwoColorize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// the partial outputs which are removed when cancelled
+static CLEANUP: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// remove the path if the command is cancelled before it's unregistered
+pub fn register_cleanup(path: &Path) {
+  if let Ok(mut paths) = CLEANUP.lock() {
+    paths.push(path.to_path_buf());
+  }
+}
+
+pub fn unregister_cleanup(path: &Path) {
+  if let Ok(mut paths) = CLEANUP.lock() {
+    if let Some(i) = paths.iter().rposition(|p| p == path) {
+      paths.remove(i);
+    }
+  }
+}
+
+/// `dir/.name.ohrs-tmp`, it's in the same dir so the rename is atomic
+pub fn temp_path(path: &Path) -> PathBuf {
+  let name = path
+    .file_name()
+    .map(|f| f.to_string_lossy().to_string())
+    .unwrap_or_default();
+  path.with_file_name(format!(".{}.ohrs-tmp", name))
+}
+
+/// write into the temp file and rename it to the path, so a half written file is impossible
+pub fn write_atomic(path: &Path, content: &[u8]) -> io::Result<()> {
+  let tmp = temp_path(path);
+  register_cleanup(&tmp);
+  let ret = fs::write(&tmp, content).and_then(|_| fs::rename(&tmp, path));
+  if ret.is_err() {
+    let _ = fs::remove_file(&tmp);
+  }
+  unregister_cleanup(&tmp);
+  ret
+}
+
+fn remove_paths(paths: &[PathBuf]) {
+  for path in paths {
+    if path.is_dir() {
+      let _ = fs::remove_dir_all(path);
+    } else if path.is_file() {
+      let _ = fs::remove_file(path);
+    }
+  }
+}
+
+/// remove the partial outputs which are still registered when the command failed
+pub fn run_cleanups() {
+  let mut paths = CLEANUP.lock().unwrap_or_else(|e| e.into_inner());
+  remove_paths(&paths);
+  paths.clear();
+}
+
+// the lock is held until exit, so no new partial output is registered after cleanup
+fn cancel() -> ! {
+  let paths = CLEANUP.lock().unwrap_or_else(|e| e.into_inner());
+  remove_paths(&paths);
+  eprintln!("\n{}", "Cancelled, the partial outputs are removed.".red());
+  std::process::exit(130)
+}
+
+/// handle SIGINT and SIGTERM in a dedicated thread, it should be called before any other thread is spawned
+/// the signals are blocked in the other threads, and the children reset the mask when spawned
+#[cfg(unix)]
+pub fn install_cancel_handler() {
+  // SAFETY: the set is initialized by sigemptyset before use
+  let set = unsafe {
+    let mut set = std::mem::zeroed::<libc::sigset_t>();
+    libc::sigemptyset(&mut set);
+    libc::sigaddset(&mut set, libc::SIGINT);
+    libc::sigaddset(&mut set, libc::SIGTERM);
+    if libc::pthread_sigmask(libc::SIG_BLOCK, &set, std::ptr::null_mut()) != 0 {
+      return;
+    }
+    set
+  };
+  std::thread::spawn(move || {
+    let mut signal = 0;
+    // SAFETY: the set is blocked in all threads, so it's only received here
+    if unsafe { libc::sigwait(&set, &mut signal) } == 0 {
+      cancel();
+    }
+  });
+}
+
+/// Ctrl-C terminates the process directly on the other platforms
+#[cfg(not(unix))]
+pub fn install_cancel_handler() {}
+
+#[cfg(test)]
+mod test {
+  use super::{temp_path, write_atomic, CLEANUP};
+  use crate::util::test_dir;
+  use std::fs;
+  use std::path::Path;
+
+  #[test]
+  fn test_write_atomic() {
+    let temp = test_dir();
+    let dir = temp.path().to_path_buf();
+    let path = dir.join("index.d.ts");
+    assert_eq!(temp_path(&path), dir.join(".index.d.ts.ohrs-tmp"));
+
+    fs::write(&path, "old").unwrap();
+    write_atomic(&path, b"export {}").unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "export {}");
+    assert!(!temp_path(&path).exists());
+    assert!(!CLEANUP.lock().unwrap().contains(&temp_path(&path)));
+
+    assert!(write_atomic(Path::new("/not/existed/index.d.ts"), b"").is_err());
+  }
+}
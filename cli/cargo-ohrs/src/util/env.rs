@@ -0,0 +1,88 @@
+use anyhow::Error;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+/// parse the dotenv style `KEY=VALUE` lines, the values are never put into the error
+pub fn parse_env_file(content: &str) -> anyhow::Result<Vec<(String, String)>> {
+  let mut vars = Vec::new();
+  for (i, line) in content.lines().enumerate() {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+      continue;
+    }
+    let line = line.strip_prefix("export ").unwrap_or(line);
+    let Some((key, value)) = line.split_once('=') else {
+      return Err(Error::msg(format!(
+        "Invalid line {} of env file, it should be KEY=VALUE.",
+        i + 1
+      )));
+    };
+    let key = key.trim();
+    let valid_key = key
+      .chars()
+      .next()
+      .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+      && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid_key {
+      return Err(Error::msg(format!(
+        "Invalid key `{}` at line {} of env file, only ASCII letters, digits and `_` are supported.",
+        key,
+        i + 1
+      )));
+    }
+    let value = value.trim();
+    let value = ['"', '\'']
+      .iter()
+      .find(|q| value.len() >= 2 && value.starts_with(**q) && value.ends_with(**q))
+      .map_or(value, |_| &value[1..value.len() - 1]);
+    vars.push((key.to_string(), value.to_string()));
+  }
+  Ok(vars)
+}
+
+/// load the env file into current process, the variables already set are kept
+/// return the keys which are loaded
+pub fn load_env_file(path: &Path) -> anyhow::Result<Vec<String>> {
+  let content = fs::read_to_string(path)
+    .map_err(|e| Error::msg(format!("Can't read env file {}: {}", path.display(), e)))?;
+  let vars =
+    parse_env_file(&content).map_err(|e| Error::msg(format!("{} ({})", e, path.display())))?;
+  let mut keys = Vec::new();
+  for (key, value) in vars {
+    if env::var_os(&key).is_some() {
+      continue;
+    }
+    env::set_var(&key, value);
+    keys.push(key);
+  }
+  Ok(keys)
+}
+
+#[cfg(test)]
+mod test {
+  use super::parse_env_file;
+
+  #[test]
+  fn test_parse_env_file() {
+    let vars = parse_env_file(
+      "# ndk\nOHOS_NDK_HOME=/opt/ohos-sdk/native\n\nexport OHPM_TOKEN = \"secret\"\nRUSTFLAGS='-C debuginfo=2'\nEMPTY=\n",
+    )
+    .unwrap();
+    assert_eq!(
+      vars,
+      vec![
+        ("OHOS_NDK_HOME".into(), "/opt/ohos-sdk/native".into()),
+        ("OHPM_TOKEN".into(), "secret".into()),
+        ("RUSTFLAGS".into(), "-C debuginfo=2".into()),
+        ("EMPTY".into(), String::new()),
+      ]
+    );
+
+    let err = parse_env_file("OHPM_TOKEN secret").unwrap_err().to_string();
+    assert!(err.contains("line 1"));
+    assert!(!err.contains("secret"));
+    assert!(parse_env_file("1KEY=value").is_err());
+    assert!(parse_env_file("MY-KEY=value").is_err());
+  }
+}
@@ -1,6 +1,24 @@
 mod arch;
+mod cache_dir;
+mod cancel;
+mod env;
+mod ndk;
+mod path;
+mod progress;
+mod target;
+#[cfg(test)]
+mod testing;
 
 pub use arch::*;
+pub use cache_dir::*;
+pub use cancel::*;
+pub use env::*;
+pub use ndk::*;
+pub use path::*;
+pub use progress::*;
+pub use target::*;
+#[cfg(test)]
+pub use testing::*;
 
 #[macro_export]
 macro_rules! create_dist_dir {
@@ -68,8 +86,11 @@ macro_rules! create_project_file {
       std::fs::remove_file(&$target).expect("Try to delete file failed.")
     }
 
+    // write into the temp file and rename it, so it won't be half written when cancelled
+    let tmp_path = $crate::util::temp_path(PathBuf::from(&$target).as_path());
+    $crate::util::register_cleanup(&tmp_path);
     let mut tmp_file =
-      std::fs::File::create(&$target).expect(format!("Create {} failed.", $name).as_str());
+      std::fs::File::create(&tmp_path).expect(format!("Create {} failed.", $name).as_str());
     // Windows don't need to set permissions
     // In another reason, we don't need to set permissions anymore, because we don't have any bash file.
     #[cfg(not(target_os = "windows"))]
@@ -81,6 +102,8 @@ macro_rules! create_project_file {
     tmp_file
       .write_all($strs.as_bytes())
       .expect(format!("Write {} failed", $name).as_str());
+    std::fs::rename(&tmp_path, &$target).expect(format!("Create {} failed.", $name).as_str());
+    $crate::util::unregister_cleanup(&tmp_path);
     println!("Create {} succeed.", $name);
   }};
 }
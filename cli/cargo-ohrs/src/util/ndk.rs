@@ -0,0 +1,354 @@
+use crate::util::{read_cache, write_cache, Arch, NDK_LOCK};
+use anyhow::Error;
+use serde::Deserialize;
+use serde_json::json;
+use std::env;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+// the probed ndk paths, tests running in parallel can filter their own ndk
+#[cfg(test)]
+pub(crate) static PROBED_NDKS: std::sync::Mutex<Vec<PathBuf>> = std::sync::Mutex::new(Vec::new());
+
+/// check the folder looks like an ndk, it should contain `native` folder
+fn is_ndk_dir(p: &Path) -> bool {
+  p.join("native").is_dir()
+}
+
+/// list all ndk versions under `OHOS_NDK_ROOT`
+fn list_ndk_versions(root: &Path) -> Vec<String> {
+  let mut versions = std::fs::read_dir(root)
+    .map(|dir| {
+      dir
+        .filter_map(|i| i.ok())
+        .filter(|i| is_ndk_dir(&i.path()))
+        .map(|i| i.file_name().to_string_lossy().to_string())
+        .collect::<Vec<_>>()
+    })
+    .unwrap_or_default();
+  versions.sort();
+  versions
+}
+
+/// resolve the ndk path
+/// 1. `--ndk` is a path, use it directly
+/// 2. `--ndk` is a version, resolve it in `OHOS_NDK_ROOT`
+/// 3. fallback to `OHOS_NDK_HOME`
+pub fn resolve_ndk(ndk: Option<&str>) -> anyhow::Result<PathBuf> {
+  let Some(ndk) = ndk else {
+    return env::var_os("OHOS_NDK_HOME")
+      .filter(|p| !p.is_empty())
+      .map(PathBuf::from)
+      .ok_or(Error::msg(
+        "Failed to get the OHOS_NDK_HOME environment variable, please make sure you have set it.",
+      ));
+  };
+
+  let ndk_path = PathBuf::from(ndk);
+  if ndk_path.is_dir() {
+    if !is_ndk_dir(&ndk_path) {
+      return Err(Error::msg(format!(
+        "{} is not a valid ndk folder, can't find native folder in it.",
+        ndk
+      )));
+    }
+    return Ok(ndk_path);
+  }
+
+  let root_path = env::var_os("OHOS_NDK_ROOT")
+    .map(PathBuf::from)
+    .ok_or(Error::msg(format!(
+    "{} is not a folder, please set OHOS_NDK_ROOT environment variable to resolve the ndk version.",
+    ndk
+  )))?;
+  let version_path = root_path.join(ndk);
+  if is_ndk_dir(&version_path) {
+    return Ok(version_path);
+  }
+
+  let versions = list_ndk_versions(&root_path);
+  Err(Error::msg(format!(
+    "Can't find ndk version {} in {}, available versions: [{}]",
+    ndk,
+    root_path.display(),
+    versions.join(", ")
+  )))
+}
+
+/// resolve the sysroot path
+/// 1. `--sysroot` flag
+/// 2. `OHOS_SYSROOT` environment variable
+/// 3. fallback to `{ndk}/native/sysroot`
+pub fn resolve_sysroot(sysroot: Option<&str>, ndk: &Path) -> anyhow::Result<PathBuf> {
+  let sysroot = sysroot
+    .map(PathBuf::from)
+    .or(env::var_os("OHOS_SYSROOT").map(PathBuf::from))
+    .unwrap_or(ndk.join("native").join("sysroot"));
+
+  for sub in ["usr/lib", "usr/include"] {
+    if !sysroot.join(sub).is_dir() {
+      return Err(Error::msg(format!(
+        "{} is not a valid sysroot, can't find {} folder in it.",
+        sysroot.display(),
+        sub
+      )));
+    }
+  }
+  Ok(sysroot)
+}
+
+/// detect ndk version from `native/oh-uni-package.json`
+pub fn detect_ndk_version(ndk: &Path) -> Option<String> {
+  let content = std::fs::read_to_string(ndk.join("native/oh-uni-package.json")).ok()?;
+  let value: serde_json::Value = serde_json::from_str(&content).ok()?;
+  value
+    .get("version")
+    .and_then(|v| v.as_str())
+    .map(String::from)
+}
+
+// the lock file in project root before the cache dir, it's still read if the cache dir has no lock
+pub const LEGACY_NDK_LOCK_FILE: &str = "ohrs-ndk.lock";
+
+#[derive(Deserialize)]
+struct NdkLock {
+  version: String,
+}
+
+/// read the locked ndk version from `ndk-lock.json` of cache dir, or `ohrs-ndk.lock` of project root
+pub fn read_ndk_lock(cache_dir: &Path, root: &Path) -> anyhow::Result<String> {
+  if let Some(lock) = read_cache::<NdkLock>(cache_dir, NDK_LOCK) {
+    return Ok(lock.version);
+  }
+  let lock = root.join(LEGACY_NDK_LOCK_FILE);
+  let content = std::fs::read_to_string(&lock).map_err(|_| {
+    Error::msg(format!(
+      "Failed to read {}, please run build with --update-ndk-lock first.",
+      cache_dir.join(NDK_LOCK).display()
+    ))
+  })?;
+  content
+    .lines()
+    .filter_map(|l| l.split_once('='))
+    .find(|(k, _)| k.trim() == "version")
+    .map(|(_, v)| v.trim().trim_matches('"').to_string())
+    .ok_or(Error::msg(format!(
+      "Can't find ndk version in {}.",
+      lock.display()
+    )))
+}
+
+/// write the ndk version to `ndk-lock.json` of cache dir
+pub fn write_ndk_lock(cache_dir: &Path, version: &str) -> anyhow::Result<()> {
+  write_cache(cache_dir, NDK_LOCK, &json!({ "version": version }))
+}
+
+/// llvm toolchain in ndk, it will be probed once for all targets
+#[derive(Debug, Clone, Default)]
+pub struct NdkToolchain {
+  pub bin: PathBuf,
+  pub lib: PathBuf,
+  pub cc: PathBuf,
+  pub cxx: PathBuf,
+  pub ar: PathBuf,
+  pub ranlib: PathBuf,
+  pub as_path: PathBuf,
+  pub ld: PathBuf,
+  pub strip: PathBuf,
+  pub objdump: PathBuf,
+  pub objcopy: PathBuf,
+  pub nm: PathBuf,
+}
+
+impl NdkToolchain {
+  /// resolve and validate the toolchain in `{ndk}/native/llvm`
+  pub fn probe(ndk: &Path) -> anyhow::Result<Self> {
+    #[cfg(test)]
+    PROBED_NDKS.lock().unwrap().push(ndk.to_path_buf());
+
+    let llvm = ndk.join("native").join("llvm");
+    let bin = llvm.join("bin");
+    let tool = |name: &str| bin.join(name);
+    let toolchain = NdkToolchain {
+      lib: llvm.join("lib"),
+      cc: tool("clang"),
+      cxx: tool("clang++"),
+      ar: tool("llvm-ar"),
+      ranlib: tool("llvm-ranlib"),
+      as_path: tool("llvm-as"),
+      ld: tool("ld.lld"),
+      strip: tool("llvm-strip"),
+      objdump: tool("llvm-objdump"),
+      objcopy: tool("llvm-objcopy"),
+      nm: tool("llvm-nm"),
+      bin,
+    };
+
+    for t in [&toolchain.cc, &toolchain.cxx, &toolchain.ar] {
+      let mut exe = t.clone().into_os_string();
+      exe.push(env::consts::EXE_SUFFIX);
+      if !Path::new(&exe).is_file() {
+        return Err(Error::msg(format!(
+          "Can't find {} in ndk {}, please check the ndk is complete.",
+          t.display(),
+          ndk.display()
+        )));
+      }
+    }
+    Ok(toolchain)
+  }
+
+  /// environment variables of the target toolchain for cargo and cc
+  pub fn target_envs(&self, arch: &Arch) -> Vec<(String, OsString)> {
+    [
+      (
+        format!("CARGO_TARGET_{}_LINKER", arch.rust_link_target()),
+        &self.cc,
+      ),
+      (String::from("TARGET_CC"), &self.cc),
+      (String::from("TARGET_CXX"), &self.cxx),
+      (String::from("TARGET_RANLIB"), &self.ranlib),
+      (String::from("TARGET_AR"), &self.ar),
+      (String::from("TARGET_AS"), &self.as_path),
+      (String::from("TARGET_LD"), &self.ld),
+      (String::from("TARGET_STRIP"), &self.strip),
+      (String::from("TARGET_OBJDUMP"), &self.objdump),
+      (String::from("TARGET_OBJCOPY"), &self.objcopy),
+      (String::from("TARGET_NM"), &self.nm),
+    ]
+    .into_iter()
+    .map(|(k, v)| (k, v.clone().into_os_string()))
+    .collect()
+  }
+
+  /// put the llvm bin folder in front of `PATH`
+  pub fn path_env(&self) -> anyhow::Result<OsString> {
+    let path = env::var_os("PATH").unwrap_or_default();
+    env::join_paths(std::iter::once(self.bin.clone()).chain(env::split_paths(&path)))
+      .map_err(|e| Error::msg(format!("Can't add {} to PATH: {}", self.bin.display(), e)))
+  }
+
+  /// all the envs for cargo to build the target, shared by `ohrs build` and `ohrs cargo`
+  pub fn cargo_envs(&self, arch: &Arch, sysroot: &Path) -> anyhow::Result<Vec<(String, OsString)>> {
+    let clang_flags = clang_flags(arch, sysroot)?;
+    // for bindgen, you may need to change to builtin clang or clang++ etc. You can set LIBCLANG_PATH and CLANG_PATH
+    let mut envs = self.target_envs(arch);
+    envs.extend([
+      (String::from("LIBCLANG_PATH"), self.lib.clone().into()),
+      (String::from("CLANG_PATH"), self.cxx.clone().into()),
+      (
+        format!("CXXSTDLIB_{}", arch.rust_link_target()),
+        "c++".into(),
+      ),
+      (
+        String::from("CARGO_ENCODED_RUSTFLAGS"),
+        encoded_rustflags(&clang_flags).into(),
+      ),
+      (String::from("PATH"), self.path_env()?),
+      // support opencv-rust
+      (
+        String::from("OPENCV_CLANG_ARGS"),
+        join_flags(&clang_flags).into(),
+      ),
+      // for some package deps on atomic
+      (String::from("DEP_ATOMIC"), "clang_rt.builtins".into()),
+    ]);
+    Ok(envs)
+  }
+}
+
+/// flags passed to clang as linker, every item is a single arg
+pub fn clang_flags(arch: &Arch, sysroot: &Path) -> anyhow::Result<Vec<String>> {
+  let sysroot = sysroot.to_str().ok_or(Error::msg(format!(
+    "The sysroot {} is not valid UTF-8, it can't be passed to linker.",
+    sysroot.display()
+  )))?;
+  let mut flags = vec![
+    String::from("-target"),
+    String::from(arch.c_target()),
+    format!("--sysroot={}", sysroot),
+    String::from("-D__MUSL__"),
+  ];
+  if arch.to_arch() == "armeabi-v7a" {
+    flags.extend(
+      [
+        "-march=armv7-a",
+        "-mfloat-abi=softfp",
+        "-mtune=generic-armv7-a",
+        "-mthumb",
+      ]
+      .map(String::from),
+    );
+  }
+  Ok(flags)
+}
+
+/// join flags with space for the tools which split args by whitespace, like `OPENCV_CLANG_ARGS`
+pub fn join_flags(flags: &[String]) -> String {
+  flags
+    .iter()
+    .map(|f| {
+      if f.contains(char::is_whitespace) {
+        format!("\"{}\"", f)
+      } else {
+        f.clone()
+      }
+    })
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+/// `CARGO_ENCODED_RUSTFLAGS` is separated by `0x1f`, so every link arg can contain spaces
+pub fn encoded_rustflags(clang_flags: &[String]) -> String {
+  let mut flags = clang_flags
+    .iter()
+    .map(|f| format!("-Clink-arg={}", f))
+    .collect::<Vec<_>>();
+  // respect the flags of current environment
+  if let Ok(args) = env::var("CARGO_RUSTFLAGS") {
+    flags.extend(args.split_whitespace().map(String::from));
+  } else if let Ok(args) = env::var("CARGO_ENCODED_RUSTFLAGS") {
+    flags.extend(
+      args
+        .split('\x1f')
+        .filter(|a| !a.is_empty())
+        .map(String::from),
+    );
+  }
+  flags.join("\x1f")
+}
+
+#[cfg(test)]
+mod test {
+  use super::{clang_flags, encoded_rustflags, join_flags, resolve_sysroot, NdkToolchain};
+  use crate::util::{test_dir, Arch};
+  use std::env;
+  use std::fs;
+
+  #[test]
+  fn test_ndk_path_with_space() {
+    let temp = test_dir();
+    let ndk = temp.path().join("ndk with space");
+    let bin = ndk.join("native/llvm/bin");
+    fs::create_dir_all(&bin).unwrap();
+    for t in ["clang", "clang++", "llvm-ar"] {
+      fs::write(bin.join(format!("{}{}", t, env::consts::EXE_SUFFIX)), "").unwrap();
+    }
+    for sub in ["usr/lib", "usr/include"] {
+      fs::create_dir_all(ndk.join("native/sysroot").join(sub)).unwrap();
+    }
+
+    let toolchain = NdkToolchain::probe(&ndk).unwrap();
+    assert!(env::split_paths(&toolchain.path_env().unwrap()).any(|p| p == bin));
+
+    let sysroot = resolve_sysroot(None, &ndk).unwrap();
+    let sysroot_flag = format!("--sysroot={}", sysroot.display());
+    let flags = clang_flags(&Arch::ARM64, &sysroot).unwrap();
+    assert!(flags.contains(&sysroot_flag));
+    // every link arg is a single item of encoded flags
+    assert!(encoded_rustflags(&flags)
+      .split('\x1f')
+      .any(|f| f == format!("-Clink-arg={}", sysroot_flag)));
+    assert!(join_flags(&flags).contains(&format!("\"{}\"", sysroot_flag)));
+  }
+}
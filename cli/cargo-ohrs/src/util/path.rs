@@ -0,0 +1,66 @@
+use anyhow::Error;
+use std::path::{Component, Path, PathBuf};
+
+/// resolve `.` and `..` without touching the file system, the output paths may not exist yet
+pub fn normalize_path(path: &Path) -> PathBuf {
+  let mut ret = PathBuf::new();
+  for component in path.components() {
+    match component {
+      Component::CurDir => {}
+      Component::ParentDir => {
+        // `..` of root is still root
+        if !ret.pop() && !ret.has_root() {
+          ret.push("..");
+        }
+      }
+      c => ret.push(c),
+    }
+  }
+  ret
+}
+
+/// the output path should be inside the project or one of the allowed dirs
+pub fn check_output_path(
+  flag: &str,
+  path: &Path,
+  root: &Path,
+  allowed: &[PathBuf],
+) -> anyhow::Result<()> {
+  let path = normalize_path(path);
+  if path.starts_with(normalize_path(root))
+    || allowed.iter().any(|d| path.starts_with(normalize_path(d)))
+  {
+    return Ok(());
+  }
+  Err(Error::msg(format!(
+    "The output path {} of {} is outside of the project {}, please use --allow-dir or --allow-outside if it's expected.",
+    path.display(),
+    flag,
+    root.display()
+  )))
+}
+
+#[cfg(test)]
+mod test {
+  use super::{check_output_path, normalize_path};
+  use std::path::{Path, PathBuf};
+
+  #[test]
+  fn test_check_output_path() {
+    assert_eq!(
+      normalize_path(Path::new("/ws/crate/./dist/../out")),
+      PathBuf::from("/ws/crate/out")
+    );
+    assert_eq!(normalize_path(Path::new("/../etc")), PathBuf::from("/etc"));
+
+    let root = Path::new("/ws");
+    let check = |path: &str, allowed: &[PathBuf]| {
+      check_output_path("--dist", Path::new(path), root, allowed).is_ok()
+    };
+    assert!(check("/ws/crate/dist", &[]));
+    assert!(check("/ws/crate/../dist", &[]));
+    assert!(!check("/ws/crate/../../etc", &[]));
+    assert!(!check("/wsx/dist", &[]));
+    assert!(check("/tmp/out/index.d.ts", &[PathBuf::from("/tmp/out")]));
+  }
+}
@@ -0,0 +1,58 @@
+use indicatif::{ProgressBar, ProgressStyle};
+use std::io::IsTerminal;
+use std::time::Duration;
+
+/// progress indicator for build, it's hidden when stderr is not a terminal
+#[derive(Debug, Clone)]
+pub struct Progress {
+  bar: Option<ProgressBar>,
+}
+
+impl Progress {
+  /// show a spinner, and the position of total when there are multi steps
+  pub fn new(total: u64) -> Self {
+    if !std::io::stderr().is_terminal() {
+      return Progress { bar: None };
+    }
+    let template = if total > 1 {
+      "{spinner:.green} [{pos}/{len}] {msg}"
+    } else {
+      "{spinner:.green} {msg}"
+    };
+    let bar = ProgressBar::new(total).with_style(
+      ProgressStyle::with_template(template).unwrap_or(ProgressStyle::default_spinner()),
+    );
+    bar.enable_steady_tick(Duration::from_millis(100));
+    Progress { bar: Some(bar) }
+  }
+
+  pub fn is_visible(&self) -> bool {
+    self.bar.is_some()
+  }
+
+  pub fn set_message(&self, msg: impl Into<String>) {
+    if let Some(bar) = &self.bar {
+      bar.set_message(msg.into());
+    }
+  }
+
+  pub fn inc(&self) {
+    if let Some(bar) = &self.bar {
+      bar.inc(1);
+    }
+  }
+
+  /// print message above the progress, avoid breaking it
+  pub fn println(&self, msg: impl AsRef<str>) {
+    match &self.bar {
+      Some(bar) => bar.println(msg),
+      None => eprintln!("{}", msg.as_ref()),
+    }
+  }
+
+  pub fn finish(&self) {
+    if let Some(bar) = &self.bar {
+      bar.finish_and_clear();
+    }
+  }
+}
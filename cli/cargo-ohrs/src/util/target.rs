@@ -0,0 +1,70 @@
+use crate::util::Arch;
+
+/// all the OHOS targets supported by ohrs
+pub const SUPPORTED_TARGETS: [&str; 3] = [
+  Arch::ARM64.rust_target(),
+  Arch::ARM32.rust_target(),
+  Arch::X86_64.rust_target(),
+];
+
+// short names and the c target used by clang
+const TARGET_ALIASES: [(&str, &str); 13] = [
+  ("aarch", "aarch64-unknown-linux-ohos"),
+  ("aarch64", "aarch64-unknown-linux-ohos"),
+  ("arm64", "aarch64-unknown-linux-ohos"),
+  ("arm64-v8a", "aarch64-unknown-linux-ohos"),
+  ("aarch64-linux-ohos", "aarch64-unknown-linux-ohos"),
+  ("arm", "armv7-unknown-linux-ohos"),
+  ("arm32", "armv7-unknown-linux-ohos"),
+  ("armv7", "armv7-unknown-linux-ohos"),
+  ("armeabi-v7a", "armv7-unknown-linux-ohos"),
+  ("arm-linux-ohos", "armv7-unknown-linux-ohos"),
+  ("x64", "x86_64-unknown-linux-ohos"),
+  ("x86_64", "x86_64-unknown-linux-ohos"),
+  ("x86_64-linux-ohos", "x86_64-unknown-linux-ohos"),
+];
+
+/// resolve the target triple or alias to the canonical target triple
+pub fn normalize_target(target: &str) -> Result<&'static str, String> {
+  let target = target.trim().to_lowercase();
+  if let Some(t) = SUPPORTED_TARGETS.iter().find(|t| **t == target) {
+    return Ok(t);
+  }
+  TARGET_ALIASES
+    .iter()
+    .find(|(alias, _)| *alias == target)
+    .map(|(_, t)| *t)
+    .ok_or(format!(
+      "Unsupported target {}, only supports {} and their aliases aarch/arm64, arm/arm32, and x86_64/x64.",
+      target,
+      SUPPORTED_TARGETS.join(", ")
+    ))
+}
+
+#[cfg(test)]
+mod test {
+  use super::normalize_target;
+
+  #[test]
+  fn test_normalize_target() {
+    assert_eq!(
+      normalize_target("arm64").unwrap(),
+      "aarch64-unknown-linux-ohos"
+    );
+    assert_eq!(
+      normalize_target("ARMv7").unwrap(),
+      "armv7-unknown-linux-ohos"
+    );
+    assert_eq!(
+      normalize_target("x64").unwrap(),
+      "x86_64-unknown-linux-ohos"
+    );
+    assert_eq!(
+      normalize_target("x86_64-unknown-linux-ohos").unwrap(),
+      "x86_64-unknown-linux-ohos"
+    );
+
+    assert!(normalize_target("aarch64-unknown-linux-gnu").is_err());
+    assert!(normalize_target("riscv64").is_err());
+  }
+}
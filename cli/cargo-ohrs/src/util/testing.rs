@@ -0,0 +1,19 @@
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+/// a new temp dir for test, it's removed when dropped, even if the test panics
+pub fn test_dir() -> TempDir {
+  tempfile::Builder::new()
+    .prefix("ohrs-")
+    .tempdir()
+    .expect("Create temp dir failed.")
+}
+
+/// write the content into a file of a new temp dir, keep the dir until the file is unused
+pub fn test_file(name: &str, content: &str) -> (TempDir, PathBuf) {
+  let dir = test_dir();
+  let file = dir.path().join(name);
+  fs::write(&file, content).expect("Write temp file failed.");
+  (dir, file)
+}
@@ -29,6 +29,8 @@ pub struct NapiFn {
   pub catch_unwind: bool,
   pub unsafe_: bool,
   pub register_name: Ident,
+  /// the old js name, exported with the same value
+  pub alias: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -82,6 +84,9 @@ pub struct NapiStruct {
   pub use_nullable: bool,
   pub register_name: Ident,
   pub kind: NapiStructKind,
+  /// key and value type of index signature, `[key: string]: unknown`
+  pub index_signature: Option<(String, String)>,
+  pub alias: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -162,6 +167,7 @@ pub struct NapiEnum {
   pub skip_typescript: bool,
   pub register_name: Ident,
   pub is_string_enum: bool,
+  pub alias: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -196,6 +202,22 @@ pub struct NapiConst {
   pub comments: Vec<String>,
   pub skip_typescript: bool,
   pub register_name: Ident,
+  pub alias: Option<String>,
+  /// sort key of registration, the exports in the same namespace are registered in ascending order
+  pub register_order: Option<i32>,
+  /// declare the const with the literal type of its value instead of `type_name`
+  pub literal_const: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct NapiTypeAlias {
+  pub name: Ident,
+  pub js_name: String,
+  pub ty: Type,
+  pub js_mod: Option<String>,
+  pub comments: Vec<String>,
+  pub skip_typescript: bool,
+  pub register_name: Ident,
 }
 
 #[derive(Debug, Clone)]
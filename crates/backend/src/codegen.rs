@@ -6,6 +6,7 @@ mod r#const;
 mod r#enum;
 mod r#fn;
 mod r#struct;
+mod r#type;
 
 pub use r#struct::rm_raw_prefix;
 
@@ -30,6 +31,22 @@ fn get_intermediate_ident(name: &str) -> Ident {
   Ident::new(&new_name, Span::call_site())
 }
 
+// register the alias after the export, `js_name` should be ended with `\0`
+fn alias_register_token_stream(
+  js_mod: Option<&String>,
+  js_name: &str,
+  alias: Option<&String>,
+) -> TokenStream {
+  let Some(alias) = alias else {
+    return quote! {};
+  };
+  let js_mod_ident = js_mod_to_token_stream(js_mod);
+  let alias = format!("{}\0", alias);
+  quote! {
+    napi_ohos::bindgen_prelude::register_module_export_alias(#js_mod_ident, #js_name, #alias);
+  }
+}
+
 fn js_mod_to_token_stream(js_mod: Option<&String>) -> TokenStream {
   js_mod
     .map(|i| {
@@ -1,7 +1,10 @@
 use proc_macro2::{Ident, Literal, TokenStream};
 use quote::ToTokens;
 
-use crate::{codegen::js_mod_to_token_stream, BindgenResult, NapiConst, TryToTokens};
+use crate::{
+  codegen::{alias_register_token_stream, js_mod_to_token_stream},
+  BindgenResult, NapiConst, TryToTokens,
+};
 
 impl TryToTokens for NapiConst {
   fn try_to_tokens(&self, tokens: &mut TokenStream) -> BindgenResult<()> {
@@ -26,6 +29,20 @@ impl NapiConst {
       self.name.span(),
     );
     let js_mod_ident = js_mod_to_token_stream(self.js_mod.as_ref());
+    let reflection = self.gen_reflection_register();
+    let register_export = match self.register_order {
+      Some(order) => quote! {
+        napi_ohos::bindgen_prelude::register_module_export_with_order(#js_mod_ident, #js_name_lit, #cb_name, #order);
+      },
+      None => quote! {
+        napi_ohos::bindgen_prelude::register_module_export(#js_mod_ident, #js_name_lit, #cb_name);
+      },
+    };
+    let alias_register = alias_register_token_stream(
+      self.js_mod.as_ref(),
+      &format!("{}\0", self.name),
+      self.alias.as_ref(),
+    );
 
     quote! {
       #[allow(non_snake_case)]
@@ -38,7 +55,9 @@ impl NapiConst {
       #[cfg(all(not(test), not(feature = "noop"), not(target_family = "wasm")))]
       #[napi_ohos::bindgen_prelude::ctor]
       fn #register_name() {
-        napi_ohos::bindgen_prelude::register_module_export(#js_mod_ident, #js_name_lit, #cb_name);
+        #register_export
+        #alias_register
+        #reflection
       }
 
       #[allow(non_snake_case)]
@@ -46,8 +65,62 @@ impl NapiConst {
       #[cfg(all(not(test), not(feature = "noop"), target_family = "wasm"))]
       #[no_mangle]
       unsafe extern "C" fn #register_name() {
-        napi_ohos::bindgen_prelude::register_module_export(#js_mod_ident, #js_name_lit, #cb_name);
+        #register_export
+        #alias_register
+        #reflection
       }
     }
   }
+
+  #[cfg(feature = "reflection")]
+  fn gen_reflection_register(&self) -> TokenStream {
+    let js_name_lit = Literal::string(&self.js_name);
+    let type_name = &self.type_name;
+    let type_name_lit = Literal::string(&type_name.to_token_stream().to_string());
+    let js_mod = match &self.js_mod {
+      Some(m) => quote! { Some(#m) },
+      None => quote! { None },
+    };
+    quote! {
+      napi_ohos::bindgen_prelude::register_const_metadata(#js_mod, #js_name_lit, #type_name_lit);
+    }
+  }
+
+  #[cfg(not(feature = "reflection"))]
+  fn gen_reflection_register(&self) -> TokenStream {
+    TokenStream::new()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::NapiConst;
+  use proc_macro2::{Ident, Span};
+  use syn::parse_quote;
+
+  fn napi_const(register_order: Option<i32>) -> NapiConst {
+    NapiConst {
+      name: Ident::new("DEFAULT_COST", Span::call_site()),
+      js_name: "DEFAULT_COST".to_owned(),
+      type_name: parse_quote!(u32),
+      value: parse_quote!(12),
+      js_mod: None,
+      comments: vec![],
+      skip_typescript: false,
+      register_name: Ident::new("__napi_register__DEFAULT_COST", Span::call_site()),
+      alias: None,
+      register_order,
+      literal_const: false,
+    }
+  }
+
+  #[test]
+  fn test_register_order() {
+    let tokens = napi_const(Some(-1)).gen_module_register().to_string();
+    assert!(tokens.contains("register_module_export_with_order (None , \"DEFAULT_COST\\0\" , __register__const____napi_register__DEFAULT_COST_callback__ , - 1i32)"));
+
+    let tokens = napi_const(None).gen_module_register().to_string();
+    assert!(!tokens.contains("register_module_export_with_order"));
+    assert!(tokens.contains("register_module_export (None"));
+  }
 }
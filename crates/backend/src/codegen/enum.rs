@@ -1,7 +1,10 @@
 use proc_macro2::{Ident, Literal, Span, TokenStream};
 use quote::ToTokens;
 
-use crate::{codegen::js_mod_to_token_stream, BindgenResult, NapiEnum, TryToTokens};
+use crate::{
+  codegen::{alias_register_token_stream, js_mod_to_token_stream},
+  BindgenResult, NapiEnum, TryToTokens,
+};
 
 impl TryToTokens for NapiEnum {
   fn try_to_tokens(&self, tokens: &mut TokenStream) -> BindgenResult<()> {
@@ -174,6 +177,11 @@ impl NapiEnum {
     );
 
     let js_mod_ident = js_mod_to_token_stream(self.js_mod.as_ref());
+    let alias_register = alias_register_token_stream(
+      self.js_mod.as_ref(),
+      &format!("{}\0", &self.js_name),
+      self.alias.as_ref(),
+    );
 
     quote! {
       #[allow(non_snake_case)]
@@ -199,6 +207,7 @@ impl NapiEnum {
       #[napi_ohos::bindgen_prelude::ctor]
       fn #register_name() {
         napi_ohos::bindgen_prelude::register_module_export(#js_mod_ident, #js_name_lit, #callback_name);
+        #alias_register
       }
       #[allow(non_snake_case)]
       #[allow(clippy::all)]
@@ -206,6 +215,7 @@ impl NapiEnum {
       #[no_mangle]
       extern "C" fn #register_name() {
         napi_ohos::bindgen_prelude::register_module_export(#js_mod_ident, #js_name_lit, #callback_name);
+        #alias_register
       }
     }
   }
@@ -3,7 +3,7 @@ use quote::ToTokens;
 use syn::spanned::Spanned;
 
 use crate::{
-  codegen::{get_intermediate_ident, js_mod_to_token_stream},
+  codegen::{alias_register_token_stream, get_intermediate_ident, js_mod_to_token_stream},
   BindgenResult, CallbackArg, Diagnostic, FnKind, FnSelf, NapiFn, NapiFnArgKind, TryToTokens,
   TYPEDARRAY_SLICE_TYPES,
 };
@@ -159,24 +159,7 @@ impl TryToTokens for NapiFn {
     };
 
     let function_call = if self.catch_unwind {
-      quote! {
-        {
-          std::panic::catch_unwind(|| { #function_call })
-            .map_err(|e| {
-              let message = {
-                if let Some(string) = e.downcast_ref::<String>() {
-                  string.clone()
-                } else if let Some(string) = e.downcast_ref::<&str>() {
-                  string.to_string()
-                } else {
-                  format!("panic from Rust code: {:?}", e)
-                }
-              };
-              napi_ohos::Error::new(napi_ohos::Status::GenericFailure, message)
-            })
-            .and_then(|r| r)
-        }
-      }
+      catch_unwind_guard(function_call)
     } else {
       quote! {
         #function_call
@@ -646,6 +629,8 @@ impl NapiFn {
       let intermediate_ident = get_intermediate_ident(&name_str);
       let js_mod_ident = js_mod_to_token_stream(self.js_mod.as_ref());
       let cb_name = Ident::new(&format!("{}_js_function", name_str), Span::call_site());
+      let alias_register =
+        alias_register_token_stream(self.js_mod.as_ref(), &js_name, self.alias.as_ref());
 
       quote! {
         #[allow(non_snake_case)]
@@ -675,6 +660,7 @@ impl NapiFn {
         #[napi_ohos::bindgen_prelude::ctor]
         fn #module_register_name() {
           napi_ohos::bindgen_prelude::register_module_export(#js_mod_ident, #js_name, #cb_name);
+          #alias_register
         }
 
         #[allow(clippy::all)]
@@ -683,6 +669,7 @@ impl NapiFn {
         #[no_mangle]
         extern "C" fn #module_register_name() {
           napi_ohos::bindgen_prelude::register_module_export(#js_mod_ident, #js_name, #cb_name);
+          #alias_register
         }
       }
     }
@@ -709,3 +696,49 @@ impl NapiArgType {
     matches!(self, NapiArgType::Ref | NapiArgType::MutRef)
   }
 }
+
+/// Convert the panic to a JavaScript error with `panic = "unwind"`.
+/// Nothing can be caught with `panic = "abort"`, the process is aborted after the panic hook runs,
+/// so the call is wrapped in a closure only to keep the early returns of the unwind branch.
+fn catch_unwind_guard(function_call: TokenStream) -> TokenStream {
+  quote! {
+    {
+      #[cfg(panic = "unwind")]
+      let ret = std::panic::catch_unwind(|| { #function_call })
+        .map_err(|e| {
+          let message = {
+            if let Some(string) = e.downcast_ref::<String>() {
+              string.clone()
+            } else if let Some(string) = e.downcast_ref::<&str>() {
+              string.to_string()
+            } else {
+              format!("panic from Rust code: {:?}", e)
+            }
+          };
+          napi_ohos::Error::new(napi_ohos::Status::GenericFailure, message)
+        })
+        .and_then(|r| r);
+      #[cfg(not(panic = "unwind"))]
+      let ret = (|| { #function_call })();
+      ret
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::catch_unwind_guard;
+  use quote::quote;
+
+  #[test]
+  fn test_catch_unwind_guard() {
+    let tokens = catch_unwind_guard(quote! { call() }).to_string();
+    // unwind
+    assert!(tokens.contains(
+      "# [cfg (panic = \"unwind\")] let ret = std :: panic :: catch_unwind (|| { call () })"
+    ));
+    // abort
+    assert!(tokens.contains("# [cfg (not (panic = \"unwind\"))] let ret = (|| { call () }) () ;"));
+    assert_eq!(tokens.matches("catch_unwind").count(), 1);
+  }
+}
@@ -5,7 +5,7 @@ use proc_macro2::{Ident, Literal, Span, TokenStream};
 use quote::ToTokens;
 
 use crate::{
-  codegen::{get_intermediate_ident, js_mod_to_token_stream},
+  codegen::{alias_register_token_stream, get_intermediate_ident, js_mod_to_token_stream},
   BindgenResult, FnKind, NapiImpl, NapiStruct, NapiStructKind, TryToTokens,
 };
 use crate::{NapiClass, NapiObject, NapiStructuredEnum};
@@ -808,6 +808,8 @@ impl NapiStruct {
 
       props.push(prop);
     }
+    let alias_register =
+      alias_register_token_stream(self.js_mod.as_ref(), &js_name, self.alias.as_ref());
     let js_mod_ident = js_mod_to_token_stream(self.js_mod.as_ref());
     quote! {
       #[allow(non_snake_case)]
@@ -816,6 +818,7 @@ impl NapiStruct {
       #[napi_ohos::bindgen_prelude::ctor]
       fn #struct_register_name() {
         napi_ohos::__private::register_class(#name_str, #js_mod_ident, #js_name, vec![#(#props),*]);
+        #alias_register
       }
 
       #[allow(non_snake_case)]
@@ -824,6 +827,7 @@ impl NapiStruct {
       #[no_mangle]
       extern "C" fn #struct_register_name() {
         napi_ohos::__private::register_class(#name_str, #js_mod_ident, #js_name, vec![#(#props),*]);
+        #alias_register
       }
     }
   }
@@ -0,0 +1,10 @@
+use proc_macro2::TokenStream;
+
+use crate::{BindgenResult, NapiTypeAlias, TryToTokens};
+
+impl TryToTokens for NapiTypeAlias {
+  fn try_to_tokens(&self, _tokens: &mut TokenStream) -> BindgenResult<()> {
+    // type alias only lives in the type definition, there is nothing to register at runtime
+    Ok(())
+  }
+}
@@ -65,6 +65,7 @@ napi_ast_impl! {
  (Impl, NapiImpl),
  (Enum, NapiEnum),
  (Const, NapiConst),
+ (TypeAlias, NapiTypeAlias),
 }
 
 pub(crate) static PRIMITIVE_TYPES: &[(&str, (&str, bool, bool))] = &[
@@ -1,7 +1,9 @@
 mod r#const;
+pub use r#const::literal_type;
 mod r#enum;
 mod r#fn;
 pub(crate) mod r#struct;
+mod r#type;
 
 use std::{
   cell::RefCell,
@@ -20,6 +22,35 @@ pub struct TypeDef {
   pub def: String,
   pub js_mod: Option<String>,
   pub js_doc: String,
+  /// structured members for enum, so the variant value and docs can be rendered without parsing `def`
+  pub members: Vec<TypeDefMember>,
+  /// the function returns `Result`, so it may throw in JavaScript
+  pub throws: bool,
+  /// index signature of interface or class, like `[key: string]: unknown`
+  pub index_signature: Option<String>,
+  /// where the declaration is defined in rust
+  pub source: Option<TypeDefSource>,
+  /// emitted in `declare global { ... }` instead of exported
+  pub global: bool,
+  /// the old name which is exported with the same value, for renamed declarations
+  pub alias: Option<String>,
+  /// the literal type of const value, with `#[napi(literal_const)]`
+  pub literal: Option<String>,
+}
+
+#[derive(Default, Debug)]
+pub struct TypeDefSource {
+  pub krate: String,
+  /// relative to the crate root, missing if the compiler doesn't support span location
+  pub file: Option<String>,
+  pub line: Option<usize>,
+}
+
+#[derive(Default, Debug)]
+pub struct TypeDefMember {
+  pub name: String,
+  pub value: String,
+  pub js_doc: String,
 }
 
 thread_local! {
@@ -106,13 +137,76 @@ impl Display for TypeDef {
     } else {
       "".to_string()
     };
+    let members = if self.members.is_empty() {
+      "".to_string()
+    } else {
+      format!(
+        ", \"members\": [{}]",
+        self
+          .members
+          .iter()
+          .map(|m| format!(
+            r#"{{"name": "{}", "value": "{}", "js_doc": "{}"}}"#,
+            m.name,
+            escape_json(&m.value),
+            escape_json(&m.js_doc)
+          ))
+          .collect::<Vec<_>>()
+          .join(", ")
+      )
+    };
+    let throws = if self.throws {
+      ", \"throws\": true"
+    } else {
+      ""
+    };
+    let index_signature = if let Some(index_signature) = &self.index_signature {
+      format!(
+        ", \"index_signature\": \"{}\"",
+        escape_json(index_signature)
+      )
+    } else {
+      "".to_string()
+    };
+    let source = if let Some(source) = &self.source {
+      let file = source
+        .file
+        .as_ref()
+        .map(|file| format!(r#", "file": "{}""#, escape_json(file)))
+        .unwrap_or_default();
+      let line = source
+        .line
+        .map(|line| format!(r#", "line": {}"#, line))
+        .unwrap_or_default();
+      format!(
+        r#", "source": {{"crate": "{}"{}{}}}"#,
+        source.krate, file, line
+      )
+    } else {
+      "".to_string()
+    };
+    let global = if self.global {
+      ", \"global\": true"
+    } else {
+      ""
+    };
+    let alias = if let Some(alias) = &self.alias {
+      format!(", \"alias\": \"{}\"", alias)
+    } else {
+      "".to_string()
+    };
+    let literal = if let Some(literal) = &self.literal {
+      format!(", \"literal\": \"{}\"", escape_json(literal))
+    } else {
+      "".to_string()
+    };
     // TODO: remove this in v3
     // This is a workaround for lower version of @napi-rs/cli
     // See https://github.com/napi-rs/napi-rs/pull/1531
     let prefix = "".to_string();
     write!(
       f,
-      r#"{}{{"kind": "{}", "name": "{}", "js_doc": "{}", "def": "{}"{}{}}}"#,
+      r#"{}{{"kind": "{}", "name": "{}", "js_doc": "{}", "def": "{}"{}{}{}{}{}{}{}{}{}}}"#,
       prefix,
       self.kind,
       self.name,
@@ -120,6 +214,13 @@ impl Display for TypeDef {
       escape_json(&self.def),
       original_name,
       js_mod,
+      members,
+      throws,
+      index_signature,
+      source,
+      global,
+      alias,
+      literal,
     )
   }
 }
@@ -392,6 +493,12 @@ pub fn ty_to_ts_type(
               Some((rust_ty, false))
             }
           });
+        } else if rust_ty == FUNCTION_TY || rust_ty == FUNCTION_REF_TY {
+          // `Function` without generic accepts any function, and `Return` defaults to `Unknown`
+          let mut args = args.into_iter().map(|(arg, _)| arg);
+          let fn_args = args.next().unwrap_or("...args: any[]".to_owned());
+          let return_ty = args.next().unwrap_or("unknown".to_owned());
+          ts_ty = Some((format!("({fn_args}) => {return_ty}"), false));
         } else if let Some(&(known_ty, _, _)) = KNOWN_TYPES.get(rust_ty.as_str()) {
           if rust_ty == "()" && is_return_ty {
             ts_ty = Some(("void".to_owned(), false));
@@ -420,7 +527,7 @@ pub fn ty_to_ts_type(
           let return_ty = args
             .get(1)
             .map(|(ty, _)| ty.clone())
-            .unwrap_or("any".to_owned());
+            .unwrap_or("unknown".to_owned());
           ts_ty = if fatal_tsfn {
             Some((format!("(({fn_args}) => {return_ty})"), false))
           } else {
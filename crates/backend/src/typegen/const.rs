@@ -1,3 +1,6 @@
+use convert_case::{Case, Casing};
+use syn::{Expr, Lit, Member, UnOp};
+
 use super::{ToTypeDef, TypeDef};
 
 use crate::{js_doc_from_comments, ty_to_ts_type, typegen::add_alias, NapiConst};
@@ -21,6 +24,113 @@ impl ToTypeDef for NapiConst {
       ),
       js_mod: self.js_mod.to_owned(),
       js_doc: js_doc_from_comments(&self.comments),
+      alias: self.alias.clone(),
+      literal: if self.literal_const {
+        literal_type(&self.value)
+      } else {
+        None
+      },
+      ..Default::default()
     })
   }
 }
+
+/// The `as const` type of the value, like `{ readonly version: 1; readonly name: "x" }`
+/// `None` if the value isn't built from literals only
+pub fn literal_type(expr: &Expr) -> Option<String> {
+  match expr {
+    Expr::Lit(lit) => match &lit.lit {
+      Lit::Str(s) => Some(format!(
+        "\"{}\"",
+        s.value()
+          .replace('\\', "\\\\")
+          .replace('"', "\\\"")
+          .replace('\n', "\\n")
+      )),
+      Lit::Int(i) => Some(i.base10_digits().to_owned()),
+      Lit::Float(f) => Some(f.base10_digits().to_owned()),
+      Lit::Bool(b) => Some(b.value.to_string()),
+      _ => None,
+    },
+    Expr::Unary(unary) if matches!(unary.op, UnOp::Neg(_)) => match &*unary.expr {
+      Expr::Lit(lit) if matches!(lit.lit, Lit::Int(_) | Lit::Float(_)) => {
+        Some(format!("-{}", literal_type(&unary.expr)?))
+      }
+      _ => None,
+    },
+    Expr::Array(array) => Some(format!(
+      "readonly [{}]",
+      array
+        .elems
+        .iter()
+        .map(literal_type)
+        .collect::<Option<Vec<_>>>()?
+        .join(", ")
+    )),
+    Expr::Tuple(tuple) => Some(format!(
+      "readonly [{}]",
+      tuple
+        .elems
+        .iter()
+        .map(literal_type)
+        .collect::<Option<Vec<_>>>()?
+        .join(", ")
+    )),
+    // the fields of #[napi(object)] are camelCase in JavaScript
+    Expr::Struct(s) if s.rest.is_none() => {
+      let fields = s
+        .fields
+        .iter()
+        .map(|f| match &f.member {
+          Member::Named(ident) => Some(format!(
+            "readonly {}: {}",
+            ident.to_string().to_case(Case::Camel),
+            literal_type(&f.expr)?
+          )),
+          Member::Unnamed(_) => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+      if fields.is_empty() {
+        return Some("{}".to_owned());
+      }
+      Some(format!("{{ {} }}", fields.join("; ")))
+    }
+    Expr::Reference(r) => literal_type(&r.expr),
+    Expr::Paren(p) => literal_type(&p.expr),
+    Expr::Group(g) => literal_type(&g.expr),
+    _ => None,
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::literal_type;
+  use syn::parse_quote;
+
+  #[test]
+  fn test_literal_type() {
+    assert_eq!(
+      literal_type(&parse_quote!(Config {
+        version: 1,
+        display_name: "x",
+        ratio: -0.5,
+        tags: &["a", "b"],
+      }))
+      .as_deref(),
+      Some(
+        r#"{ readonly version: 1; readonly displayName: "x"; readonly ratio: -0.5; readonly tags: readonly ["a", "b"] }"#
+      )
+    );
+    assert_eq!(
+      literal_type(&parse_quote!((1u8, true))).as_deref(),
+      Some("readonly [1, true]")
+    );
+    assert_eq!(
+      literal_type(&parse_quote!("say \"hi\"")).as_deref(),
+      Some(r#""say \"hi\"""#)
+    );
+    assert!(literal_type(&parse_quote!(Config { version: VERSION })).is_none());
+    assert!(literal_type(&parse_quote!(Config { ..DEFAULT })).is_none());
+    assert!(literal_type(&parse_quote!(String::new())).is_none());
+  }
+}
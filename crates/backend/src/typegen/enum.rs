@@ -1,4 +1,4 @@
-use super::{add_alias, ToTypeDef, TypeDef};
+use super::{add_alias, ToTypeDef, TypeDef, TypeDefMember};
 use crate::{js_doc_from_comments, NapiEnum, NapiEnumValue};
 
 impl ToTypeDef for NapiEnum {
@@ -20,23 +20,48 @@ impl ToTypeDef for NapiEnum {
       def: self.gen_ts_variants(),
       js_doc: js_doc_from_comments(&self.comments),
       js_mod: self.js_mod.to_owned(),
+      members: self.gen_ts_members(),
+      alias: self.alias.clone(),
+      ..Default::default()
     })
   }
 }
 
+fn gen_ts_value(val: &NapiEnumValue) -> String {
+  match val {
+    NapiEnumValue::Number(num) => format!("{}", num),
+    NapiEnumValue::String(string) => {
+      format!("'{}'", string.replace('\\', "\\\\").replace('\'', "\\'"))
+    }
+  }
+}
+
 impl NapiEnum {
   fn gen_ts_variants(&self) -> String {
     self
       .variants
       .iter()
       .map(|v| {
-        let val = match &v.val {
-          NapiEnumValue::Number(num) => format!("{}", num),
-          NapiEnumValue::String(string) => format!("'{}'", string),
-        };
-        format!("{}{} = {}", js_doc_from_comments(&v.comments), v.name, val)
+        format!(
+          "{}{} = {}",
+          js_doc_from_comments(&v.comments),
+          v.name,
+          gen_ts_value(&v.val)
+        )
       })
       .collect::<Vec<_>>()
       .join(",\n ")
   }
+
+  fn gen_ts_members(&self) -> Vec<TypeDefMember> {
+    self
+      .variants
+      .iter()
+      .map(|v| TypeDefMember {
+        name: v.name.to_string(),
+        value: gen_ts_value(&v.val),
+        js_doc: js_doc_from_comments(&v.comments),
+      })
+      .collect()
+  }
 }
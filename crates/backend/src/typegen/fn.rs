@@ -98,6 +98,10 @@ impl ToTypeDef for NapiFn {
       def,
       js_mod: self.js_mod.to_owned(),
       js_doc: js_doc_from_comments(&self.comments),
+      // async fn will reject the promise instead of throwing
+      throws: self.is_ret_result && !self.is_async,
+      alias: self.alias.clone(),
+      ..Default::default()
     })
   }
 }
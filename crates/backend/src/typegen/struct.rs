@@ -30,6 +30,12 @@ impl ToTypeDef for NapiStruct {
       def: self.gen_ts_class(),
       js_mod: self.js_mod.to_owned(),
       js_doc: js_doc_from_comments(&self.comments),
+      index_signature: self
+        .index_signature
+        .as_ref()
+        .map(|(key, value)| format!("[key: {}]: {}", key, value)),
+      alias: self.alias.clone(),
+      ..Default::default()
     })
   }
 }
@@ -75,6 +81,7 @@ impl ToTypeDef for NapiImpl {
         ),
         js_mod: self.js_mod.to_owned(),
         js_doc: "".to_string(),
+        ..Default::default()
       })
     } else {
       Some(TypeDef {
@@ -100,6 +107,7 @@ impl ToTypeDef for NapiImpl {
           .join("\\n"),
         js_mod: self.js_mod.to_owned(),
         js_doc: "".to_string(),
+        ..Default::default()
       })
     }
   }
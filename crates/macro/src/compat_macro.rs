@@ -98,7 +98,8 @@ pub fn get_execute_js_code(new_fn_name: Ident, function_kind: FunctionKind) -> T
     }
   };
   quote! {
-    match panic::catch_unwind(AssertUnwindSafe(move || #new_fn_name(ctx))).map_err(|e| {
+    #[cfg(panic = "unwind")]
+    let ret = panic::catch_unwind(AssertUnwindSafe(move || #new_fn_name(ctx))).map_err(|e| {
       let message = {
         if let Some(string) = e.downcast_ref::<String>() {
           string.clone()
@@ -109,7 +110,11 @@ pub fn get_execute_js_code(new_fn_name: Ident, function_kind: FunctionKind) -> T
         }
       };
       Error::from_reason(message)
-    }).and_then(|v| v) {
+    }).and_then(|v| v);
+    // the process is aborted by the panic, there is nothing to catch
+    #[cfg(not(panic = "unwind"))]
+    let ret = #new_fn_name(ctx);
+    match ret {
       #return_token_stream
       Err(e) => {
         unsafe { napi_ohos::JsError::from(e).throw_into(raw_env) };
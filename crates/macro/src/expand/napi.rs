@@ -6,10 +6,11 @@ use std::sync::atomic::{AtomicBool, Ordering};
 
 use crate::parser::{attrs::BindgenAttrs, ParseNapi};
 #[cfg(feature = "type-def")]
-use napi_derive_backend_ohos::ToTypeDef;
+use napi_derive_backend_ohos::{js_doc_from_comments, ToTypeDef, TypeDef, TypeDefSource};
 use napi_derive_backend_ohos::{BindgenResult, Napi, TryToTokens};
-use proc_macro2::TokenStream;
+use proc_macro2::{Span, TokenStream};
 use quote::ToTokens;
+use syn::spanned::Spanned;
 use syn::{Attribute, Item};
 
 /// a flag indicate whether or never at least one `napi` macro has been expanded.
@@ -51,6 +52,10 @@ pub fn expand(attr: TokenStream, input: TokenStream) -> BindgenResult<TokenStrea
       || js_mod.ident.to_string(),
       |(js_name, _)| js_name.to_owned(),
     );
+
+    #[cfg(feature = "type-def")]
+    output_mod_doc(&js_name, &js_mod.attrs);
+
     if let Some((_, mut items)) = js_mod.content.clone() {
       for item in items.iter_mut() {
         let mut empty_attrs = vec![];
@@ -61,6 +66,7 @@ pub fn expand(attr: TokenStream, input: TokenStream) -> BindgenResult<TokenStrea
             Item::Struct(ref mut struct_) => &mut struct_.attrs,
             Item::Enum(ref mut enum_) => &mut enum_.attrs,
             Item::Const(ref mut const_) => &mut const_.attrs,
+            Item::Type(ref mut type_) => &mut type_.attrs,
             Item::Impl(ref mut impl_) => &mut impl_.attrs,
             Item::Mod(mod_) => {
               let mod_in_mod = mod_
@@ -80,13 +86,17 @@ pub fn expand(attr: TokenStream, input: TokenStream) -> BindgenResult<TokenStrea
             _ => &mut empty_attrs,
           },
         ) {
+          #[cfg_attr(not(feature = "type-def"), allow(unused_variables))]
+          let span = item_span(item);
+          #[cfg_attr(not(feature = "type-def"), allow(unused_variables))]
+          let global = item_opts.global().is_some();
           let napi = item.parse_napi(&mut tokens, &item_opts)?;
           item_opts.check_used()?;
           napi.try_to_tokens(&mut tokens)?;
 
           #[cfg(feature = "type-def")]
           {
-            output_type_def(&napi);
+            output_type_def(&napi, span, global);
             output_wasi_register_def(&napi);
           }
         } else {
@@ -107,13 +117,17 @@ pub fn expand(attr: TokenStream, input: TokenStream) -> BindgenResult<TokenStrea
     let mod_tokens = quote! { #(#js_mod_attrs)* #visible mod #mod_name { #tokens } };
     Ok(mod_tokens)
   } else {
+    #[cfg_attr(not(feature = "type-def"), allow(unused_variables))]
+    let span = item_span(&item);
+    #[cfg_attr(not(feature = "type-def"), allow(unused_variables))]
+    let global = opts.global().is_some();
     let napi = item.parse_napi(&mut tokens, &opts)?;
     opts.check_used()?;
     napi.try_to_tokens(&mut tokens)?;
 
     #[cfg(feature = "type-def")]
     {
-      output_type_def(&napi);
+      output_type_def(&napi, span, global);
       output_wasi_register_def(&napi);
     }
     Ok(tokens)
@@ -142,23 +156,81 @@ fn output_wasi_register_def(napi: &Napi) {
 }
 
 #[cfg(feature = "type-def")]
-fn output_type_def(napi: &Napi) {
-  if let Ok(type_def_file) = env::var("TYPE_DEF_TMP_PATH") {
-    if let Some(type_def) = napi.to_type_def() {
-      fs::OpenOptions::new()
-        .append(true)
-        .create(true)
-        .open(type_def_file)
-        .and_then(|file| {
-          let mut writer = BufWriter::<fs::File>::new(file);
-          writer.write_all(type_def.to_string().as_bytes())?;
-          writer.write_all("\n".as_bytes())?;
-          writer.flush()
-        })
-        .unwrap_or_else(|e| {
-          println!("Failed to write type def file: {:?}", e);
-        });
+fn output_type_def(napi: &Napi, span: Span, global: bool) {
+  if let Some(mut type_def) = napi.to_type_def() {
+    type_def.source = Some(type_def_source(span));
+    type_def.global = global;
+    write_type_def(&type_def);
+  }
+}
+
+// the ident is more accurate than the whole item which starts from doc comments
+#[allow(unused)]
+fn item_span(item: &Item) -> Span {
+  match item {
+    Item::Fn(f) => f.sig.ident.span(),
+    Item::Struct(s) => s.ident.span(),
+    Item::Enum(e) => e.ident.span(),
+    Item::Const(c) => c.ident.span(),
+    Item::Type(t) => t.ident.span(),
+    _ => item.span(),
+  }
+}
+
+/// file and line are only available with rustc 1.88 or later
+#[cfg(feature = "type-def")]
+fn type_def_source(span: Span) -> TypeDefSource {
+  let file = span.local_file().map(|file| {
+    let file = fs::canonicalize(&file).unwrap_or(file);
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR")
+      .map(std::path::PathBuf::from)
+      .map(|p| fs::canonicalize(&p).unwrap_or(p));
+    match manifest_dir {
+      Ok(dir) => file.strip_prefix(&dir).unwrap_or(&file).to_path_buf(),
+      Err(_) => file,
     }
+    .to_string_lossy()
+    .replace('\\', "/")
+  });
+  TypeDefSource {
+    krate: env::var("CARGO_PKG_NAME").unwrap_or_default(),
+    file,
+    line: Some(span.start().line).filter(|l| *l > 0),
+  }
+}
+
+/// module doc will be emitted above the namespace
+#[cfg(feature = "type-def")]
+fn output_mod_doc(js_name: &str, attrs: &[Attribute]) {
+  let comments = crate::parser::extract_doc_comments(attrs);
+  if comments.is_empty() {
+    return;
+  }
+  write_type_def(&TypeDef {
+    kind: "mod".to_owned(),
+    name: js_name.to_owned(),
+    js_mod: Some(js_name.to_owned()),
+    js_doc: js_doc_from_comments(&comments),
+    ..Default::default()
+  });
+}
+
+#[cfg(feature = "type-def")]
+fn write_type_def(type_def: &TypeDef) {
+  if let Ok(type_def_file) = env::var("TYPE_DEF_TMP_PATH") {
+    fs::OpenOptions::new()
+      .append(true)
+      .create(true)
+      .open(type_def_file)
+      .and_then(|file| {
+        let mut writer = BufWriter::<fs::File>::new(file);
+        writer.write_all(type_def.to_string().as_bytes())?;
+        writer.write_all("\n".as_bytes())?;
+        writer.flush()
+      })
+      .unwrap_or_else(|e| {
+        println!("Failed to write type def file: {:?}", e);
+      });
   }
 }
 
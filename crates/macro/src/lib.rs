@@ -110,6 +110,7 @@ pub fn contextless_function(_attr: TokenStream, input: TokenStream) -> TokenStre
       cb_info: napi_ohos::sys::napi_callback_info,
     ) -> napi_ohos::sys::napi_value {
       use std::ptr;
+      #[cfg(panic = "unwind")]
       use std::panic::{self, AssertUnwindSafe};
       use std::ffi::CString;
       use napi_ohos::{Env, NapiValue, NapiRaw, Error, Status};
@@ -146,6 +147,7 @@ pub fn js_function(attr: TokenStream, input: TokenStream) -> TokenStream {
       cb_info: napi_ohos::sys::napi_callback_info,
     ) -> napi_ohos::sys::napi_value {
       use std::ptr;
+      #[cfg(panic = "unwind")]
       use std::panic::{self, AssertUnwindSafe};
       use std::ffi::CString;
       use napi_ohos::{Env, Error, Status, NapiValue, NapiRaw, CallContext};
@@ -74,6 +74,11 @@ macro_rules! attrgen {
       (string_enum, StringEnum(Span, Option<(String, Span)>)),
       (use_nullable, UseNullable(Span, Option<bool>), false),
       (discriminant, Discriminant(Span, String, Span)),
+      (index_signature, IndexSignature(Span, String, Span)),
+      (global, Global(Span)),
+      (alias, Alias(Span, String, Span)),
+      (register_order, RegisterOrder(Span, syn::Expr)),
+      (literal_const, LiteralConst(Span)),
 
       // impl later
       // (inspectable, Inspectable(Span)),
@@ -13,7 +13,7 @@ use napi_derive_backend_ohos::{
   rm_raw_prefix, BindgenResult, CallbackArg, Diagnostic, FnKind, FnSelf, Napi, NapiClass,
   NapiConst, NapiEnum, NapiEnumValue, NapiEnumVariant, NapiFn, NapiFnArg, NapiFnArgKind, NapiImpl,
   NapiItem, NapiObject, NapiStruct, NapiStructField, NapiStructKind, NapiStructuredEnum,
-  NapiStructuredEnumVariant,
+  NapiStructuredEnumVariant, NapiTypeAlias,
 };
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::ToTokens;
@@ -30,6 +30,31 @@ thread_local! {
 
 static REGISTER_INDEX: AtomicUsize = AtomicUsize::new(0);
 
+// the alias is exported as is, so it must be a valid JavaScript identifier
+fn parse_alias(opts: &BindgenAttrs) -> BindgenResult<Option<String>> {
+  let Some((alias, span)) = opts.alias() else {
+    return Ok(None);
+  };
+  let mut chars = alias.chars();
+  let valid = chars
+    .next()
+    .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+    && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$');
+  if !valid {
+    return Err(
+      Diagnostic::span_error(
+        span,
+        format!(
+          "#[napi(alias)] `{}` is not a valid JavaScript identifier",
+          alias
+        ),
+      )
+      .into(),
+    );
+  }
+  Ok(Some(alias.to_owned()))
+}
+
 fn get_register_ident(name: &str) -> Ident {
   let new_name = format!(
     "__napi_register__{}_{}",
@@ -337,7 +362,7 @@ fn get_expr(mut expr: &syn::Expr) -> &syn::Expr {
 }
 
 /// Extract the documentation comments from a Vec of attributes
-fn extract_doc_comments(attrs: &[syn::Attribute]) -> Vec<String> {
+pub(crate) fn extract_doc_comments(attrs: &[syn::Attribute]) -> Vec<String> {
   attrs
     .iter()
     .filter_map(|a| {
@@ -628,6 +653,15 @@ fn napi_fn_from_decl(
       }
     }
   };
+  // tuple is only supported in arguments, it's the arguments list of callback in return position
+  if let Some(syn::Type::Tuple(tuple)) = &ret {
+    if !tuple.elems.is_empty() {
+      errors.push(err_span!(
+        tuple,
+        "tuple can't be returned to JavaScript, please return an array or a #[napi(object)] struct instead"
+      ));
+    }
+  }
 
   Diagnostic::from_vec(errors).and_then(|_| {
     let js_name = if let Some(prop_name) = opts.getter() {
@@ -694,6 +728,13 @@ fn napi_fn_from_decl(
       bail_span!(sig.ident, "Constructor don't support asynchronous function");
     }
 
+    if opts.alias().is_some() && parent.is_some() {
+      bail_span!(
+        sig.ident,
+        "#[napi(alias)] can only be applied to the exported fn, struct, enum and const"
+      );
+    }
+
     Ok(NapiFn {
       name: ident.clone(),
       js_name,
@@ -721,6 +762,7 @@ fn napi_fn_from_decl(
       catch_unwind: opts.catch_unwind().is_some(),
       unsafe_: sig.unsafety.is_some(),
       register_name: get_register_ident(ident.to_string().as_str()),
+      alias: parse_alias(opts)?,
     })
   })
 }
@@ -733,9 +775,10 @@ impl ParseNapi for syn::Item {
       syn::Item::Impl(i) => i.parse_napi(tokens, opts),
       syn::Item::Enum(e) => e.parse_napi(tokens, opts),
       syn::Item::Const(c) => c.parse_napi(tokens, opts),
+      syn::Item::Type(t) => t.parse_napi(tokens, opts),
       _ => bail_span!(
         self,
-        "#[napi] can only be applied to a function, struct, enum, const, mod or impl."
+        "#[napi] can only be applied to a function, struct, enum, const, type alias, mod or impl."
       ),
     }
   }
@@ -888,6 +931,36 @@ impl ParseNapi for syn::ItemConst {
   }
 }
 
+impl ParseNapi for syn::ItemType {
+  fn parse_napi(&mut self, tokens: &mut TokenStream, opts: &BindgenAttrs) -> BindgenResult<Napi> {
+    if opts.ts_args_type().is_some()
+      || opts.ts_return_type().is_some()
+      || opts.ts_type().is_some()
+      || opts.custom_finalize().is_some()
+    {
+      bail_span!(
+        self,
+        "#[napi] can't be applied to a type alias with #[napi(ts_args_type)], #[napi(ts_return_type)] or #[napi(ts_type)] or #[napi(custom_finalize)]"
+      );
+    }
+    if opts.return_if_invalid().is_some() {
+      bail_span!(
+        self,
+        "#[napi(return_if_invalid)] can only be applied to a function or method."
+      );
+    }
+    if opts.catch_unwind().is_some() {
+      bail_span!(
+        self,
+        "#[napi(catch_unwind)] can only be applied to a function or method."
+      );
+    }
+    let napi = self.convert_to_ast(opts);
+    self.to_tokens(tokens);
+    napi
+  }
+}
+
 fn fn_kind(opts: &BindgenAttrs) -> FnKind {
   let mut kind = FnKind::Normal;
 
@@ -991,6 +1064,26 @@ impl ConvertToAST for syn::ItemStruct {
     let use_nullable = opts.use_nullable();
     let (fields, is_tuple) = convert_fields(&mut self.fields, true)?;
 
+    // `#[napi(index_signature = "string, unknown")]`
+    let index_signature = match opts.index_signature() {
+      Some((s, span)) => match s.split_once(',') {
+        Some((key, value)) if !key.trim().is_empty() && !value.trim().is_empty() => {
+          Some((key.trim().to_owned(), value.trim().to_owned()))
+        }
+        _ => {
+          errors.push(Diagnostic::span_error(
+            span,
+            format!(
+              r#"index_signature should be "<key type>, <value type>", like "string, unknown", but got "{}""#,
+              s
+            ),
+          ));
+          None
+        }
+      },
+      None => None,
+    };
+
     record_struct(&struct_name, js_name.clone(), opts);
     let namespace = opts.namespace().map(|(m, _)| m.to_owned());
     let implement_iterator = opts.iterator().is_some();
@@ -1034,6 +1127,7 @@ impl ConvertToAST for syn::ItemStruct {
       }
     };
 
+    let alias = parse_alias(opts)?;
     Diagnostic::from_vec(errors).map(|()| Napi {
       item: NapiItem::Struct(NapiStruct {
         js_name,
@@ -1043,6 +1137,8 @@ impl ConvertToAST for syn::ItemStruct {
         use_nullable,
         register_name: get_register_ident(format!("{struct_name}_struct").as_str()),
         comments: extract_doc_comments(&self.attrs),
+        index_signature,
+        alias,
       }),
     })
   }
@@ -1185,6 +1281,7 @@ impl ConvertToAST for syn::ItemEnum {
         });
       }
       let struct_name = self.ident.clone();
+      let alias = parse_alias(opts)?;
       return Diagnostic::from_vec(errors).map(|()| Napi {
         item: NapiItem::Struct(NapiStruct {
           name: struct_name.clone(),
@@ -1199,6 +1296,8 @@ impl ConvertToAST for syn::ItemEnum {
             object_from_js: opts.object_from_js(),
             object_to_js: opts.object_to_js(),
           }),
+          index_signature: None,
+          alias,
         }),
       });
     }
@@ -1321,6 +1420,7 @@ impl ConvertToAST for syn::ItemEnum {
         skip_typescript: opts.skip_typescript().is_some(),
         register_name: get_register_ident(self.ident.to_string().as_str()),
         is_string_enum,
+        alias: parse_alias(opts)?,
       }),
     })
   }
@@ -1328,6 +1428,31 @@ impl ConvertToAST for syn::ItemEnum {
 
 impl ConvertToAST for syn::ItemConst {
   fn convert_to_ast(&mut self, opts: &BindgenAttrs) -> BindgenResult<Napi> {
+    let register_order = match opts.register_order() {
+      // `-1` is parsed as unary expression
+      Some(order) => match order
+        .to_token_stream()
+        .to_string()
+        .replace(' ', "")
+        .parse::<i32>()
+      {
+        Ok(order) => Some(order),
+        Err(_) => bail_span!(
+          order,
+          "#[napi(register_order)] must be an integer, but got `{}`",
+          order.to_token_stream()
+        ),
+      },
+      None => None,
+    };
+    let literal_const = opts.literal_const().is_some();
+    #[cfg(feature = "type-def")]
+    if literal_const && napi_derive_backend_ohos::literal_type(&self.expr).is_none() {
+      bail_span!(
+        self.expr,
+        "#[napi(literal_const)] only supports the literals, negative numbers, arrays, tuples and struct expressions of them"
+      );
+    }
     match self.vis {
       Visibility::Public(_) => Ok(Napi {
         item: NapiItem::Const(NapiConst {
@@ -1341,9 +1466,39 @@ impl ConvertToAST for syn::ItemConst {
           comments: extract_doc_comments(&self.attrs),
           skip_typescript: opts.skip_typescript().is_some(),
           register_name: get_register_ident(self.ident.to_string().as_str()),
+          alias: parse_alias(opts)?,
+          register_order,
+          literal_const,
         }),
       }),
       _ => bail_span!(self, "only public const allowed"),
     }
   }
 }
+
+impl ConvertToAST for syn::ItemType {
+  fn convert_to_ast(&mut self, opts: &BindgenAttrs) -> BindgenResult<Napi> {
+    if !self.generics.params.is_empty() {
+      bail_span!(
+        self.generics,
+        "generic type alias is not supported by #[napi]"
+      );
+    }
+    match self.vis {
+      Visibility::Public(_) => Ok(Napi {
+        item: NapiItem::TypeAlias(NapiTypeAlias {
+          name: self.ident.clone(),
+          js_name: opts
+            .js_name()
+            .map_or_else(|| self.ident.to_string(), |(s, _)| s.to_string()),
+          ty: *self.ty.clone(),
+          js_mod: opts.namespace().map(|(m, _)| m.to_owned()),
+          comments: extract_doc_comments(&self.attrs),
+          skip_typescript: opts.skip_typescript().is_some(),
+          register_name: get_register_ident(self.ident.to_string().as_str()),
+        }),
+      }),
+      _ => bail_span!(self, "only public type alias allowed"),
+    }
+  }
+}
@@ -49,8 +49,12 @@ impl<K, V> Default for PersistedPerInstanceHashMap<K, V> {
   }
 }
 
+/// (js_mod, (export name, callback), register order)
 type ModuleRegisterCallback =
-  RwLock<Vec<(Option<&'static str>, (&'static str, ExportRegisterCallback))>>;
+  RwLock<Vec<(Option<&'static str>, (&'static str, ExportRegisterCallback), i32)>>;
+
+/// (js_mod, export name, alias)
+type ModuleExportAliases = RwLock<Vec<(Option<&'static str>, &'static str, &'static str)>>;
 
 type ModuleClassProperty = PersistedPerInstanceHashMap<
   &'static str,
@@ -66,6 +70,7 @@ type RegisteredClassesMap = PersistedPerInstanceHashMap<ThreadId, RegisteredClas
 
 static MODULE_REGISTER_CALLBACK: Lazy<ModuleRegisterCallback> = Lazy::new(Default::default);
 static MODULE_CLASS_PROPERTIES: Lazy<ModuleClassProperty> = Lazy::new(Default::default);
+static MODULE_EXPORT_ALIASES: Lazy<ModuleExportAliases> = Lazy::new(Default::default);
 #[cfg(not(feature = "noop"))]
 static IS_FIRST_MODULE: AtomicBool = AtomicBool::new(true);
 #[cfg(not(feature = "noop"))]
@@ -123,11 +128,85 @@ pub fn register_module_export(
   js_mod: Option<&'static str>,
   name: &'static str,
   cb: ExportRegisterCallback,
+) {
+  register_module_export_with_order(js_mod, name, cb, 0);
+}
+
+#[doc(hidden)]
+/// The exports in the same namespace are registered in ascending `order`, and the exports with the same
+/// order keep the order of `#[ctor]` calls.
+///
+/// The `#[ctor]` functions are placed in `.init_array`, their order is decided by the linker and the
+/// order of object files, it's not stable across targets and compiler versions. And `ctor` 0.2 has no
+/// priority support. So the order is applied when the module is registered, instead of when the ctor
+/// is called, all exports are already collected at that time.
+pub fn register_module_export_with_order(
+  js_mod: Option<&'static str>,
+  name: &'static str,
+  cb: ExportRegisterCallback,
+  order: i32,
 ) {
   MODULE_REGISTER_CALLBACK
     .write()
     .expect("Register module export failed")
-    .push((js_mod, (name, cb)));
+    .push((js_mod, (name, cb), order));
+}
+
+#[doc(hidden)]
+/// Export the same value of `name` with `alias`, it's resolved after all exports and classes are registered
+pub fn register_module_export_alias(
+  js_mod: Option<&'static str>,
+  name: &'static str,
+  alias: &'static str,
+) {
+  MODULE_EXPORT_ALIASES
+    .write()
+    .expect("Register module export alias failed")
+    .push((js_mod, name, alias));
+}
+
+/// Metadata of a `#[napi] const`, recorded when the `reflection` feature is enabled.
+#[cfg(feature = "reflection")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConstMetadata {
+  pub js_mod: Option<&'static str>,
+  pub name: &'static str,
+  /// The Rust type of the const, for example `u32`
+  pub type_name: &'static str,
+}
+
+#[cfg(feature = "reflection")]
+static CONST_METADATA: Lazy<RwLock<Vec<ConstMetadata>>> = Lazy::new(Default::default);
+
+#[doc(hidden)]
+#[cfg(feature = "reflection")]
+pub fn register_const_metadata(
+  js_mod: Option<&'static str>,
+  name: &'static str,
+  type_name: &'static str,
+) {
+  CONST_METADATA
+    .write()
+    .expect("Register const metadata failed")
+    .push(ConstMetadata {
+      js_mod,
+      name,
+      type_name,
+    });
+}
+
+/// Get the metadata of all registered `#[napi] const`.
+///
+/// Only available with the `reflection` feature of both `napi-ohos` and `napi-derive-ohos`.
+/// Every const costs an extra `ConstMetadata` entry (5 words) in a global `Vec` for the whole
+/// lifetime of the module, plus the name and type strings in the binary, so leave it off unless
+/// you need to introspect the module at runtime.
+#[cfg(feature = "reflection")]
+pub fn get_const_metadata() -> Vec<ConstMetadata> {
+  CONST_METADATA
+    .read()
+    .expect("Read const metadata failed")
+    .clone()
 }
 
 #[doc(hidden)]
@@ -233,11 +312,13 @@ pub unsafe extern "C" fn napi_register_module_v1(
     let mut register_callback = MODULE_REGISTER_CALLBACK
       .write()
       .expect("Write MODULE_REGISTER_CALLBACK in napi_register_module_v1 failed");
+    // stable sort, keep the order of ctor calls for the same order
+    register_callback.sort_by_key(|(_, _, order)| *order);
     register_callback
       .iter_mut()
       .fold(
         HashMap::<Option<&'static str>, Vec<(&'static str, ExportRegisterCallback)>>::new(),
-        |mut acc, (js_mod, item)| {
+        |mut acc, (js_mod, item, _)| {
           if let Some(k) = acc.get_mut(js_mod) {
             k.push(*item);
           } else {
@@ -404,6 +485,53 @@ pub unsafe extern "C" fn napi_register_module_v1(
     });
   });
 
+  {
+    let aliases = MODULE_EXPORT_ALIASES
+      .read()
+      .expect("Read MODULE_EXPORT_ALIASES in napi_register_module_v1 failed");
+    for (js_mod, name, alias) in aliases.iter() {
+      let register_alias = || -> Result<()> {
+        let mut exported_object = exports;
+        if let Some(js_mod_str) = js_mod {
+          let mod_name_c_str =
+            unsafe { CStr::from_bytes_with_nul_unchecked(js_mod_str.as_bytes()) };
+          check_status!(
+            unsafe {
+              sys::napi_get_named_property(
+                env,
+                exports,
+                mod_name_c_str.as_ptr(),
+                &mut exported_object,
+              )
+            },
+            "Get mod {} from exports failed",
+            js_mod_str,
+          )?;
+        }
+        let js_name = unsafe { CStr::from_bytes_with_nul_unchecked(name.as_bytes()) };
+        let js_alias = unsafe { CStr::from_bytes_with_nul_unchecked(alias.as_bytes()) };
+        let mut value = ptr::null_mut();
+        check_status!(
+          unsafe {
+            sys::napi_get_named_property(env, exported_object, js_name.as_ptr(), &mut value)
+          },
+          "Get export `{}` for alias `{}` failed",
+          name,
+          alias,
+        )?;
+        check_status!(
+          unsafe { sys::napi_set_named_property(env, exported_object, js_alias.as_ptr(), value) },
+          "Failed to register alias `{}` of export `{}`",
+          alias,
+          name,
+        )
+      };
+      if let Err(e) = register_alias() {
+        unsafe { JsError::from(e).throw_into(env) };
+      }
+    }
+  }
+
   #[cfg(feature = "compat-mode")]
   {
     let module_exports = MODULE_EXPORTS.read().expect("Read MODULE_EXPORTS failed");
@@ -29,6 +29,16 @@ fn get_num_arr() -> [u32; 2] {
   [1, 2]
 }
 
+#[napi]
+/// Tuples are declared as `[number, string]`
+fn sum_tuples(
+  pair: (u32, String),
+  nested: Vec<((u32, bool), Option<String>)>,
+  maybe: Option<(u32, u32)>,
+) -> u32 {
+  pair.0 + nested.iter().map(|((n, _), _)| n).sum::<u32>() + maybe.map_or(0, |(a, b)| a + b)
+}
+
 #[napi]
 fn get_nested_num_arr() -> [[[u32; 1]; 1]; 2] {
   [[[1]], [[1]]]
@@ -1,4 +1,5 @@
 use napi_ohos::bindgen_prelude::*;
+use std::collections::HashMap;
 
 #[napi]
 fn either_string_or_number(input: Either<String, u32>) -> u32 {
@@ -146,3 +147,24 @@ pub async fn promise_in_either(input: Either<u32, Promise<u32>>) -> Result<bool>
 
 #[napi]
 pub fn either_bool_or_tuple(_input: Either<bool, (bool, String)>) {}
+
+#[napi]
+pub fn either_nested_generics(
+  input: Either<Vec<String>, HashMap<String, Either<u32, String>>>,
+) -> u32 {
+  match input {
+    Either::A(a) => a.len() as u32,
+    Either::B(b) => b.len() as u32,
+  }
+}
+
+#[napi]
+pub fn either3_nested_generics(
+  input: Either3<Option<u32>, Vec<Either<bool, String>>, Promise<u32>>,
+) -> u32 {
+  match input {
+    Either3::A(a) => a.unwrap_or_default(),
+    Either3::B(b) => b.len() as u32,
+    Either3::C(_) => 0,
+  }
+}
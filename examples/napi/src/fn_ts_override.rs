@@ -25,3 +25,11 @@ fn override_individual_arg_on_function_with_cb_arg<
 ) -> Result<Object> {
   callback(format!("World({})", not_overridden), None)
 }
+
+#[napi(
+  ts_args_type = "stream: AsyncIterable<number>",
+  ts_return_type = "AsyncIterableIterator<number>"
+)]
+fn pass_through_stream(stream: Object) -> Object {
+  stream
+}
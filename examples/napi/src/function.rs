@@ -1,7 +1,7 @@
 #![allow(deprecated)]
 
 use napi_ohos::{
-  bindgen_prelude::{ClassInstance, Function, FunctionRef, PromiseRaw},
+  bindgen_prelude::{ClassInstance, Function, FunctionRef, Null, PromiseRaw},
   threadsafe_function::ThreadsafeFunctionCallMode,
   Env, Error, Result, Status,
 };
@@ -23,6 +23,17 @@ pub fn call2(callback: Function<(u32, u32), u32>, arg1: u32, arg2: u32) -> Resul
   callback.call((arg1, arg2))
 }
 
+/// renamed from `callThree`, which is still exported as a deprecated alias
+#[napi(alias = "callThree")]
+pub fn call3(
+  callback: Function<(u32, u32, u32), u32>,
+  arg1: u32,
+  arg2: u32,
+  arg3: u32,
+) -> Result<u32> {
+  callback.call((arg1, arg2, arg3))
+}
+
 #[napi]
 pub fn apply0(ctx: ClassInstance<Animal>, callback: Function<(), ()>) -> Result<()> {
   callback.apply(ctx, ())
@@ -127,3 +138,15 @@ pub fn build_threadsafe_function_from_function_callee_handle(
 
   Ok(())
 }
+
+#[napi]
+pub fn call_node_style_callback(callback: Function<(Null, String), ()>) -> Result<()> {
+  callback.call((Null, "data".to_owned()))
+}
+
+#[napi(ts_args_type = "callback: (err: Error | null, data: string) => void")]
+pub fn call_node_style_callback_with_override(
+  callback: Function<(Null, String), ()>,
+) -> Result<()> {
+  callback.call((Null, "data".to_owned()))
+}
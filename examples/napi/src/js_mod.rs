@@ -1,4 +1,5 @@
 #[napi]
+/// xxh3 hash functions
 mod xxh3 {
   use napi_ohos::bindgen_prelude::{BigInt, Buffer};
 
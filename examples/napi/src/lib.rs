@@ -37,6 +37,18 @@ pub const DEFAULT_COST: u32 = 12;
 #[napi(skip_typescript)]
 pub const TYPE_SKIPPED_CONST: u32 = 12;
 
+#[napi(global)]
+/// This const is declared in `declare global`
+pub const GLOBAL_COST: u32 = 24;
+
+#[napi(register_order = -1)]
+/// This const is registered before the others
+pub const BASE_COST: u32 = 6;
+
+#[napi(literal_const)]
+/// This const is declared with the literal type of its value
+pub const LITERAL_GREETING: &str = "hello";
+
 mod array;
 mod r#async;
 mod bigint;
@@ -66,4 +78,5 @@ mod shared;
 mod string;
 mod task;
 mod threadsafe_function;
+mod type_alias;
 mod typed_array;
@@ -89,6 +89,12 @@ pub struct TsTypeChanged {
   pub type_override_optional: Option<String>,
 }
 
+#[napi(object, index_signature = "string, unknown")]
+pub struct ObjectWithIndexSignature {
+  pub name: String,
+  pub age: Option<u32>,
+}
+
 #[napi(ts_return_type = "{ value: ArrayBuffer, get getter(): number }")]
 pub fn create_obj_with_property(env: Env) -> Result<JsObject> {
   let mut obj = env.create_object()?;
@@ -148,3 +154,17 @@ fn return_object_only_to_js() -> ObjectOnlyToJs {
 
 #[napi(object)]
 pub struct TupleObject(pub u32, pub u32);
+
+#[napi(object)]
+pub struct LiteralConfig {
+  pub version: u32,
+  pub enabled: bool,
+  pub max_ratio: f64,
+}
+
+#[napi(literal_const)]
+pub const LITERAL_CONFIG: LiteralConfig = LiteralConfig {
+  version: 1,
+  enabled: true,
+  max_ratio: 0.5,
+};
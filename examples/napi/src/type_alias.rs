@@ -0,0 +1,11 @@
+#[napi]
+/// Identifier of a record
+pub type RecordId = u32;
+
+#[napi]
+pub type NullableName = Option<String>;
+
+#[napi]
+pub fn get_record_id(id: RecordId) -> RecordId {
+  id + 1
+}